@@ -0,0 +1,251 @@
+use argon2::Argon2;
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore},
+};
+
+use crate::utils::unix_now;
+
+/// Start of the store file, followed by the salt, the nonce and the encrypted records
+const MAGIC: &[u8] = b"toadfill1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Why the master passphrase is asked for
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UnlockReason {
+    /// To offer fills for the focused field
+    Fill,
+    /// To save the last sent login
+    Save,
+}
+
+/// Values sent from a form, by field name
+#[derive(Clone, PartialEq, Debug)]
+pub struct AutofillRecord {
+    /// Origin of the page the form was on, like `https://example.com`
+    pub origin: String,
+    /// Tells records of the same origin apart, like the username. Never a password
+    pub user: String,
+    pub fields: Vec<(String, String)>,
+    /// Unix timestamp of when the record was saved
+    pub saved: u64,
+}
+impl AutofillRecord {
+    pub fn new(origin: String, user: String, fields: Vec<(String, String)>) -> Self {
+        Self {
+            origin,
+            user,
+            fields,
+            saved: unix_now(),
+        }
+    }
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.0 == field)
+            .map(|f| f.1.as_str())
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Autofill {
+    pub records: Vec<AutofillRecord>,
+}
+
+/// Makes text fit in a tab separated line
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+impl Autofill {
+    /// Indices of the records of an origin with a value for the field, newest first
+    pub fn fills(&self, origin: &str, field: &str) -> Vec<usize> {
+        let mut fills: Vec<usize> = (0..self.records.len())
+            .filter(|f| {
+                let record = &self.records[*f];
+                record.origin == origin && record.get(field).is_some_and(|f| !f.is_empty())
+            })
+            .collect();
+        fills.sort_by_key(|f| std::cmp::Reverse(self.records[*f].saved));
+        fills
+    }
+    /// Whether the exact same values are already saved
+    pub fn contains(&self, record: &AutofillRecord) -> bool {
+        self.records
+            .iter()
+            .any(|f| f.origin == record.origin && f.fields == record.fields)
+    }
+    /// Saves a record, replacing the one of the same origin and user
+    pub fn save(&mut self, record: AutofillRecord) {
+        let existing = self
+            .records
+            .iter()
+            .position(|f| f.origin == record.origin && f.user == record.user);
+        match existing {
+            Some(index) => self.records[index] = record,
+            None => self.records.push(record),
+        }
+    }
+    /// Stored as one record per line, with the saved date, origin, user, and then each field name and value separated by tabs
+    pub fn serialize(&self) -> String {
+        let mut text = String::new();
+        for record in self.records.iter() {
+            text += &format!(
+                "{}\t{}\t{}",
+                record.saved,
+                escape(&record.origin),
+                escape(&record.user)
+            );
+            for (name, value) in record.fields.iter() {
+                text += &format!("\t{}\t{}", escape(name), escape(value));
+            }
+            text += "\n";
+        }
+        text
+    }
+    pub fn deserialize(text: &str) -> Self {
+        let mut records = Vec::new();
+        for line in text.lines() {
+            let mut parts = line.split('\t');
+            let (Some(saved), Some(origin), Some(user)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let parts: Vec<String> = parts.map(unescape).collect();
+            records.push(AutofillRecord {
+                origin: unescape(origin),
+                user: unescape(user),
+                fields: parts
+                    .chunks_exact(2)
+                    .map(|f| (f[0].clone(), f[1].clone()))
+                    .collect(),
+                saved: saved.parse().unwrap_or_default(),
+            });
+        }
+        Self { records }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, &'static str> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|_| "the autofill key couldn't be derived")?;
+    Ok(key)
+}
+
+/// The autofill store once unlocked, with the key it's encrypted with
+pub struct AutofillStore {
+    pub autofill: Autofill,
+    key: Key,
+    salt: [u8; SALT_LEN],
+}
+impl AutofillStore {
+    /// A new empty store, encrypted with the passphrase
+    pub fn create(passphrase: &str) -> Result<Self, &'static str> {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Ok(Self {
+            autofill: Autofill::default(),
+            key: derive_key(passphrase, &salt)?,
+            salt,
+        })
+    }
+    /// Decrypts a store file with the passphrase
+    pub fn unlock(data: &[u8], passphrase: &str) -> Result<Self, &'static str> {
+        let data = data
+            .strip_prefix(MAGIC)
+            .filter(|f| f.len() >= SALT_LEN + NONCE_LEN)
+            .ok_or("the autofill store is damaged")?;
+        let (salt, data) = data.split_at(SALT_LEN);
+        let (nonce, encrypted) = data.split_at(NONCE_LEN);
+        let key = derive_key(passphrase, salt)?;
+        let text = ChaCha20Poly1305::new(&key)
+            .decrypt(Nonce::from_slice(nonce), encrypted)
+            .map_err(|_| "wrong passphrase")?;
+        Ok(Self {
+            autofill: Autofill::deserialize(&String::from_utf8_lossy(&text)),
+            key,
+            salt: salt.try_into().unwrap_or_default(),
+        })
+    }
+    /// Encrypts the store for writing, with a new nonce every time
+    pub fn seal(&self) -> Result<Vec<u8>, &'static str> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let encrypted = ChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, self.autofill.serialize().as_bytes())
+            .map_err(|_| "the autofill store couldn't be encrypted")?;
+        Ok([MAGIC, &self.salt, &nonce, &encrypted].concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::autofill::{AutofillRecord, AutofillStore};
+
+    #[test]
+    fn test_autofill() {
+        let mut store = AutofillStore::create("correct horse").unwrap();
+        let login = |origin: &str, user: &str, pass: &str| {
+            AutofillRecord::new(
+                String::from(origin),
+                String::from(user),
+                vec![
+                    (String::from("user"), String::from(user)),
+                    (String::from("pass"), String::from(pass)),
+                ],
+            )
+        };
+        let record = login("https://example.com", "toad", "a\tb");
+        store.autofill.save(record.clone());
+        assert!(store.autofill.contains(&record));
+        // a new password for the same user replaces the old one
+        store
+            .autofill
+            .save(login("https://example.com", "toad", "hunter2"));
+        store
+            .autofill
+            .save(login("https://example.org", "frog", "lily"));
+        assert_eq!(store.autofill.records.len(), 2);
+        assert_eq!(store.autofill.fills("https://example.com", "pass"), [0]);
+        assert!(
+            store
+                .autofill
+                .fills("https://example.com", "email")
+                .is_empty()
+        );
+
+        let sealed = store.seal().unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("hunter2"));
+        assert!(AutofillStore::unlock(&sealed, "wrong").is_err());
+        let unlocked = AutofillStore::unlock(&sealed, "correct horse").unwrap();
+        assert_eq!(unlocked.autofill, store.autofill);
+
+        store.autofill.records = vec![login("https://example.com", "", "b\\t\n")];
+        let unlocked = AutofillStore::unlock(&store.seal().unwrap(), "correct horse").unwrap();
+        assert_eq!(unlocked.autofill, store.autofill);
+    }
+}
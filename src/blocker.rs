@@ -0,0 +1,591 @@
+use std::collections::{HashMap, HashSet};
+
+use regex::{Regex, RegexBuilder};
+use reqwest::Url;
+
+use crate::{element::Element, network::RequestKind};
+
+/// Error of requests that were blocked, shown in the network panel
+pub const BLOCKED_ERROR: &str = "blocked by filter list";
+
+/// Types of request a network rule applies to, as bits
+const TYPE_IMAGE: u8 = 1;
+const TYPE_STYLESHEET: u8 = 2;
+const TYPE_DOCUMENT: u8 = 4;
+/// Scripts, fonts and everything else that isn't fetched
+const TYPE_OTHER: u8 = 8;
+/// Rules without type options apply to everything but pages
+const TYPE_DEFAULT: u8 = TYPE_IMAGE | TYPE_STYLESHEET | TYPE_OTHER;
+
+/// Options that change what a rule does instead of what it matches, so it can't be treated as blocking
+const UNSUPPORTED_OPTIONS: &[&str] = &[
+    "csp",
+    "redirect",
+    "redirect-rule",
+    "removeparam",
+    "rewrite",
+    "replace",
+    "badfilter",
+    "header",
+    "permissions",
+];
+/// Procedural cosmetic filters, which aren't CSS
+const PROCEDURAL_OPERATORS: &[&str] = &[
+    ":-abp-",
+    ":has-text(",
+    ":xpath(",
+    ":upward(",
+    ":remove(",
+    ":style(",
+    ":matches-css",
+    ":min-text-length(",
+    ":watch-attr(",
+];
+
+fn type_bit(name: &str) -> u8 {
+    match name {
+        "image" => TYPE_IMAGE,
+        "stylesheet" | "css" => TYPE_STYLESHEET,
+        "document" | "doc" => TYPE_DOCUMENT,
+        "all" => TYPE_DEFAULT | TYPE_DOCUMENT,
+        _ => TYPE_OTHER,
+    }
+}
+fn request_type(kind: RequestKind) -> u8 {
+    match kind {
+        RequestKind::Image => TYPE_IMAGE,
+        RequestKind::Stylesheet => TYPE_STYLESHEET,
+        RequestKind::Document | RequestKind::Redirect | RequestKind::Form => TYPE_DOCUMENT,
+    }
+}
+
+/// Whether a host is the domain or one of its subdomains
+fn is_subdomain(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|f| f.ends_with('.'))
+}
+/// The last two labels of a host, like `example.com` for `www.example.com`
+fn site(host: &str) -> &str {
+    let mut dots = host.rmatch_indices('.');
+    dots.next();
+    match dots.next() {
+        Some((index, _)) => &host[index + 1..],
+        None => host,
+    }
+}
+/// Splits a list of domains like `a.com|~b.com` into included and excluded ones
+fn parse_domains(text: &str, separator: char) -> (Vec<String>, Vec<String>) {
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    for domain in text
+        .split(separator)
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+    {
+        match domain.strip_prefix('~') {
+            Some(domain) => excluded.push(domain.to_lowercase()),
+            None => included.push(domain.to_lowercase()),
+        }
+    }
+    (included, excluded)
+}
+/// Whether a rule limited to domains applies on a page
+fn domains_match(included: &[String], excluded: &[String], host: &str) -> bool {
+    (included.is_empty() || included.iter().any(|f| is_subdomain(host, f)))
+        && !excluded.iter().any(|f| is_subdomain(host, f))
+}
+
+fn is_separator(char: u8) -> bool {
+    !(char.is_ascii_alphanumeric() || matches!(char, b'_' | b'-' | b'.' | b'%'))
+}
+/// Matches text against a filter pattern, where `*` matches anything and `^` a single separator
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some(b'^') if is_separator(text[t]) => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            Some(char) if *char == text[t] => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+        // retry from the last star, with it matching one more char
+        let Some((star_p, star_t)) = star else {
+            return false;
+        };
+        p = star_p + 1;
+        t = star_t + 1;
+        star = Some((star_p, star_t + 1));
+    }
+    pattern[p..].iter().all(|f| *f == b'*')
+}
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    /// Matches any URL, for `||host^` rules which are looked up by host
+    Any,
+    /// `/regex/`
+    Regex(Regex),
+    /// A pattern with wildcards, prepared for [glob_match]
+    Glob {
+        pattern: Vec<u8>,
+        /// `||`, matching from the start of the host or any of its subdomains
+        host_anchored: bool,
+    },
+}
+
+/// A rule blocking or allowing requests
+#[derive(Clone, Debug)]
+struct NetworkRule {
+    pattern: Pattern,
+    types: u8,
+    /// `third-party` or `~third-party`
+    third_party: Option<bool>,
+    /// `domain=`, the pages the rule applies on
+    domains: (Vec<String>, Vec<String>),
+}
+impl NetworkRule {
+    /// Parses a rule, returning it with the host it's looked up by if it only matches by host
+    fn parse(line: &str) -> Option<(Self, Option<String>)> {
+        let (pattern, options) = match line.rsplit_once('$') {
+            // a `$` in a regex isn't the start of the options
+            Some((pattern, options)) if !options.contains('/') => (pattern, options),
+            _ => (line, ""),
+        };
+        let mut rule = Self {
+            pattern: Pattern::Any,
+            types: 0,
+            third_party: None,
+            domains: (Vec::new(), Vec::new()),
+        };
+        let mut excluded_types = 0;
+        for option in options.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            let option = option.to_lowercase();
+            let (name, value) = option.split_once('=').unwrap_or((&option, ""));
+            if UNSUPPORTED_OPTIONS.contains(&name) {
+                return None;
+            }
+            match name {
+                "third-party" | "3p" => rule.third_party = Some(true),
+                "~third-party" | "1p" | "first-party" => rule.third_party = Some(false),
+                "domain" | "from" => rule.domains = parse_domains(value, '|'),
+                "important" | "match-case" => {}
+                _ => match name.strip_prefix('~') {
+                    Some(name) => excluded_types |= type_bit(name),
+                    None => rule.types |= type_bit(name),
+                },
+            }
+        }
+        if rule.types == 0 {
+            rule.types = TYPE_DEFAULT & !excluded_types;
+        }
+
+        let pattern = pattern.to_lowercase();
+        if pattern.len() > 2 && pattern.starts_with('/') && pattern.ends_with('/') {
+            let regex = RegexBuilder::new(&pattern[1..pattern.len() - 1])
+                .case_insensitive(true)
+                .build()
+                .ok()?;
+            rule.pattern = Pattern::Regex(regex);
+            return Some((rule, None));
+        }
+        let (host_anchored, start_anchored, pattern) = if let Some(f) = pattern.strip_prefix("||") {
+            (true, false, f)
+        } else if let Some(f) = pattern.strip_prefix('|') {
+            (false, true, f)
+        } else {
+            (false, false, pattern.as_str())
+        };
+        // the most common rule, a whole host, is looked up instead of matched
+        if let Some(host) = pattern.strip_suffix('^')
+            && host_anchored
+            && !host.is_empty()
+            && host
+                .bytes()
+                .all(|f| f.is_ascii_alphanumeric() || matches!(f, b'.' | b'-' | b'_'))
+        {
+            return Some((rule, Some(host.to_string())));
+        }
+        let (end_anchored, pattern) = match pattern.strip_suffix('|') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        // matched against the URL followed by a nul, so `^` can match the end
+        let mut glob = Vec::new();
+        if !host_anchored && !start_anchored {
+            glob.push(b'*');
+        }
+        glob.extend_from_slice(pattern.as_bytes());
+        if !end_anchored {
+            glob.push(b'*');
+        } else if !pattern.ends_with('^') {
+            glob.push(0);
+        }
+        rule.pattern = Pattern::Glob {
+            pattern: glob,
+            host_anchored,
+        };
+        Some((rule, None))
+    }
+    fn matches(&self, request: &Request) -> bool {
+        if self.types & request.ty == 0 {
+            return false;
+        }
+        if let Some(third_party) = self.third_party
+            && third_party != request.third_party
+        {
+            return false;
+        }
+        if !self.domains.0.is_empty() || !self.domains.1.is_empty() {
+            let Some(page_host) = request.page_host else {
+                return false;
+            };
+            if !domains_match(&self.domains.0, &self.domains.1, page_host) {
+                return false;
+            }
+        }
+        match &self.pattern {
+            Pattern::Any => true,
+            Pattern::Regex(regex) => regex.is_match(&request.url[..request.url.len() - 1]),
+            Pattern::Glob {
+                pattern,
+                host_anchored: false,
+            } => glob_match(pattern, request.url.as_bytes()),
+            Pattern::Glob {
+                pattern,
+                host_anchored: true,
+            } => {
+                // the host, or any of its subdomains
+                let (start, end) = request.host_range;
+                std::iter::once(start)
+                    .chain(
+                        (start..end)
+                            .filter(|f| request.url.as_bytes()[*f] == b'.')
+                            .map(|f| f + 1),
+                    )
+                    .any(|f| glob_match(pattern, &request.url.as_bytes()[f..]))
+            }
+        }
+    }
+}
+
+/// A request being checked against the rules
+struct Request<'a> {
+    /// The lowercase URL, followed by a nul
+    url: String,
+    host: &'a str,
+    /// Where the host is in the URL
+    host_range: (usize, usize),
+    page_host: Option<&'a str>,
+    third_party: bool,
+    ty: u8,
+}
+
+#[derive(Default, Debug)]
+struct RuleSet {
+    /// `||host^` rules, by host
+    by_host: HashMap<String, Vec<NetworkRule>>,
+    other: Vec<NetworkRule>,
+}
+impl RuleSet {
+    fn add(&mut self, rule: NetworkRule, host: Option<String>) {
+        match host {
+            Some(host) => self.by_host.entry(host).or_default().push(rule),
+            None => self.other.push(rule),
+        }
+    }
+    fn matches(&self, request: &Request) -> bool {
+        // the host and every domain above it
+        let mut host = request.host;
+        loop {
+            if let Some(rules) = self.by_host.get(host)
+                && rules.iter().any(|f| f.matches(request))
+            {
+                return true;
+            }
+            match host.split_once('.') {
+                Some((_, parent)) => host = parent,
+                None => break,
+            }
+        }
+        self.other.iter().any(|f| f.matches(request))
+    }
+}
+
+/// A `##selector` rule, hiding elements
+#[derive(Clone, Debug)]
+struct CosmeticRule {
+    selector: String,
+    /// The sites the rule applies on, or every site if there are none included
+    domains: (Vec<String>, Vec<String>),
+    /// `#@#`, keeping a selector from being hidden
+    exception: bool,
+}
+impl CosmeticRule {
+    fn parse(domains: &str, selector: &str, exception: bool) -> Option<Self> {
+        let selector = selector.trim();
+        // scriptlets, HTML filters and procedural filters aren't CSS
+        if selector.is_empty()
+            || selector.starts_with('+')
+            || selector.starts_with('^')
+            || selector.contains(['{', '}'])
+            || PROCEDURAL_OPERATORS.iter().any(|f| selector.contains(f))
+        {
+            return None;
+        }
+        Some(Self {
+            selector: selector.to_string(),
+            domains: parse_domains(domains, ','),
+            exception,
+        })
+    }
+    fn applies(&self, host: Option<&str>) -> bool {
+        match host {
+            Some(host) => domains_match(&self.domains.0, &self.domains.1, host),
+            None => self.domains.0.is_empty(),
+        }
+    }
+}
+
+/// The classes and ids of `.class` and `#id` in a selector
+fn selector_tokens(selector: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    for (index, char) in selector.char_indices() {
+        if char != '.' && char != '#' {
+            continue;
+        }
+        let start = index + 1;
+        let end = selector[start..]
+            .find(|f: char| !(f.is_alphanumeric() || f == '-' || f == '_'))
+            .map_or(selector.len(), |f| start + f);
+        if end > start {
+            tokens.push(&selector[index..end]);
+        }
+    }
+    tokens
+}
+/// Collects `.class` and `#id` of every element
+fn page_tokens(element: &Element, tokens: &mut HashSet<String>) {
+    for class in element.classes.iter() {
+        tokens.insert(format!(".{class}"));
+    }
+    if let Some(id) = element.get_attribute("id") {
+        tokens.insert(format!("#{id}"));
+    }
+    for child in element.children.iter() {
+        page_tokens(child, tokens);
+    }
+}
+
+/// Blocks requests and hides elements with EasyList and uBlock Origin style filter lists
+#[derive(Default, Debug)]
+pub struct Blocker {
+    block: RuleSet,
+    /// `@@` exception rules
+    allow: RuleSet,
+    cosmetic: Vec<CosmeticRule>,
+    /// Sites nothing is blocked on, matching their subdomains too
+    pub allowlist: Vec<String>,
+    /// How many rules were loaded, and from how many lists
+    pub rule_count: usize,
+    pub list_count: usize,
+}
+impl Blocker {
+    /// Adds the rules of a filter list
+    pub fn add_list(&mut self, text: &str) {
+        self.list_count += 1;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('!') || line.starts_with('[') {
+                continue;
+            }
+            if let Some((domains, selector)) = line.split_once("#@#") {
+                if let Some(rule) = CosmeticRule::parse(domains, selector, true) {
+                    self.cosmetic.push(rule);
+                    self.rule_count += 1;
+                }
+                continue;
+            }
+            if let Some((domains, selector)) = line.split_once("##") {
+                if let Some(rule) = CosmeticRule::parse(domains, selector, false) {
+                    self.cosmetic.push(rule);
+                    self.rule_count += 1;
+                }
+                continue;
+            }
+            // other extended syntax, like `#?#` or `#$#`
+            if line.contains("#?#") || line.contains("#$#") || line.contains("#%#") {
+                continue;
+            }
+            let (set, line) = match line.strip_prefix("@@") {
+                Some(line) => (&mut self.allow, line),
+                None => (&mut self.block, line),
+            };
+            if let Some((rule, host)) = NetworkRule::parse(line) {
+                set.add(rule, host);
+                self.rule_count += 1;
+            }
+        }
+    }
+    /// Whether content isn't blocked on a page
+    pub fn is_allowlisted(&self, page: Option<&Url>) -> bool {
+        page.and_then(|f| f.host_str())
+            .is_some_and(|host| self.allowlist.iter().any(|f| is_subdomain(host, f)))
+    }
+    /// Stops or resumes blocking on a site, returning whether content is blocked on it now
+    pub fn toggle_allowlist(&mut self, host: &str) -> bool {
+        let before = self.allowlist.len();
+        self.allowlist.retain(|f| !is_subdomain(host, f));
+        if self.allowlist.len() == before {
+            self.allowlist.push(host.to_string());
+            false
+        } else {
+            true
+        }
+    }
+    /// Whether a request made for a page should be blocked
+    pub fn should_block(&self, url: &Url, page: Option<&Url>, kind: RequestKind) -> bool {
+        if self.is_allowlisted(page) || !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let text = format!("{}\0", url.as_str().to_lowercase());
+        let host = host.to_lowercase();
+        let host_start = text.find(&host).unwrap_or_default();
+        let page_host = page.and_then(|f| f.host_str());
+        let request = Request {
+            host_range: (host_start, host_start + host.len()),
+            url: text,
+            host: &host,
+            page_host,
+            third_party: page_host.is_some_and(|f| site(f) != site(&host)),
+            ty: request_type(kind),
+        };
+        self.block.matches(&request) && !self.allow.matches(&request)
+    }
+    /// CSS hiding the elements of a page that cosmetic rules match
+    pub fn cosmetic_css(&self, root: &Element, page: Option<&Url>) -> String {
+        if self.cosmetic.is_empty() || self.is_allowlisted(page) {
+            return String::new();
+        }
+        let host = page.and_then(|f| f.host_str());
+        let excepted: HashSet<&str> = self
+            .cosmetic
+            .iter()
+            .filter(|f| f.exception && f.applies(host))
+            .map(|f| f.selector.as_str())
+            .collect();
+        let mut tokens = HashSet::new();
+        page_tokens(root, &mut tokens);
+        let mut css = String::new();
+        for rule in self.cosmetic.iter() {
+            if rule.exception || excepted.contains(rule.selector.as_str()) || !rule.applies(host) {
+                continue;
+            }
+            // most rules are for classes and ids the page doesn't have
+            if !rule.selector.contains(',')
+                && !selector_tokens(&rule.selector)
+                    .iter()
+                    .all(|f| tokens.contains(*f))
+            {
+                continue;
+            }
+            css += &format!("{} {{ display: none; }}\n", rule.selector);
+        }
+        css
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+
+    use crate::{blocker::Blocker, network::RequestKind, parsing::parse_html};
+
+    #[test]
+    fn test_blocker() {
+        let mut blocker = Blocker::default();
+        blocker.add_list(
+            "[Adblock Plus 2.0]\n\
+            ! comment\n\
+            ||ads.example.net^\n\
+            /banner/*.gif$image\n\
+            ||tracker.com/pixel|\n\
+            ||cdn.example.org^$third-party,stylesheet\n\
+            @@||ads.example.net/ok^\n\
+            ||popups.example^$document\n\
+            ||example.com^$csp=script-src 'none'\n\
+            ##.ad-box\n\
+            example.com##.sidebar-ad\n\
+            news.example.com#@#.ad-box\n\
+            ##+js(nobab)\n",
+        );
+        assert_eq!(blocker.rule_count, 9);
+        let page = Url::parse("https://news.example.com/article").ok();
+        let blocked =
+            |url: &str, kind| blocker.should_block(&Url::parse(url).unwrap(), page.as_ref(), kind);
+        assert!(blocked(
+            "https://sub.ads.example.net/a.png",
+            RequestKind::Image
+        ));
+        assert!(!blocked(
+            "https://ads.example.network/a.png",
+            RequestKind::Image
+        ));
+        assert!(!blocked(
+            "https://ads.example.net/ok/a.png",
+            RequestKind::Image
+        ));
+        assert!(blocked("https://x.com/banner/big.gif", RequestKind::Image));
+        assert!(!blocked(
+            "https://x.com/banner/big.gif",
+            RequestKind::Stylesheet
+        ));
+        assert!(blocked("http://tracker.com/pixel", RequestKind::Image));
+        assert!(!blocked("http://tracker.com/pixel.png", RequestKind::Image));
+        assert!(blocked(
+            "https://cdn.example.org/a.css",
+            RequestKind::Stylesheet
+        ));
+        // pages are only blocked by rules for documents
+        assert!(!blocked("https://ads.example.net/", RequestKind::Redirect));
+        assert!(blocked("https://popups.example/", RequestKind::Redirect));
+
+        let root = parse_html(
+            "<html><body><div class=\"ad-box\"></div><div class=\"sidebar-ad\"></div></body></html>",
+        )
+        .unwrap()
+        .root
+        .unwrap();
+        assert_eq!(
+            blocker.cosmetic_css(&root, page.as_ref()),
+            ".sidebar-ad { display: none; }\n"
+        );
+        let other = Url::parse("https://example.org/").ok();
+        assert_eq!(
+            blocker.cosmetic_css(&root, other.as_ref()),
+            ".ad-box { display: none; }\n"
+        );
+
+        // allowlisting a site stops blocking on its subdomains too
+        let ad = Url::parse("https://sub.ads.example.net/a.png").unwrap();
+        assert!(!blocker.toggle_allowlist("example.com"));
+        assert!(!blocker.should_block(&ad, page.as_ref(), RequestKind::Image));
+        assert!(blocker.cosmetic_css(&root, page.as_ref()).is_empty());
+        assert!(blocker.toggle_allowlist("news.example.com"));
+        assert!(blocker.should_block(&ad, page.as_ref(), RequestKind::Image));
+    }
+}
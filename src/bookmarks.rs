@@ -0,0 +1,252 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::{parsing::*, utils::*};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: String,
+    /// Folder or tag the bookmark is grouped under. Empty if none
+    pub folder: String,
+    /// Unix timestamp of when the bookmark was added
+    pub added: u64,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Bookmarks {
+    pub items: Vec<Bookmark>,
+}
+
+static NETSCAPE_TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<h3[^>]*>(.*?)</h3>|<a\s([^>]*)>(.*?)</a>|<dl[^>]*>|</dl>").unwrap()
+});
+static ATTRIBUTE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)([\w-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+/// Makes text fit in a tab separated line
+fn strip_separators(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+impl Bookmarks {
+    pub fn get(&self, url: &str) -> Option<usize> {
+        self.items.iter().position(|f| f.url == url)
+    }
+    pub fn add(&mut self, url: &str, title: &str, folder: &str) {
+        if self.get(url).is_some() {
+            return;
+        }
+        self.items.push(Bookmark {
+            url: strip_separators(url),
+            title: strip_separators(title.trim()),
+            folder: strip_separators(folder.trim()),
+            added: unix_now(),
+        });
+    }
+    /// Adds the bookmark if the URL isn't bookmarked, otherwise removes it. Returns whether it is now bookmarked.
+    pub fn toggle(&mut self, url: &str, title: &str) -> bool {
+        if let Some(index) = self.get(url) {
+            self.items.remove(index);
+            false
+        } else {
+            self.add(url, title, "");
+            true
+        }
+    }
+    pub fn rename(&mut self, index: usize, title: &str, folder: &str) {
+        let Some(bookmark) = self.items.get_mut(index) else {
+            return;
+        };
+        if !title.trim().is_empty() {
+            bookmark.title = strip_separators(title.trim());
+        }
+        if !folder.trim().is_empty() {
+            bookmark.folder = strip_separators(folder.trim());
+        }
+    }
+    pub fn remove(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.items.remove(index);
+        }
+    }
+    /// Stored as one bookmark per line, with added date, folder, URL and title separated by tabs
+    pub fn serialize(&self) -> String {
+        let mut text = String::new();
+        for item in self.items.iter() {
+            text += &format!(
+                "{}\t{}\t{}\t{}\n",
+                item.added, item.folder, item.url, item.title
+            );
+        }
+        text
+    }
+    pub fn deserialize(text: &str) -> Self {
+        let items = text
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(4, '\t');
+                Some(Bookmark {
+                    added: parts.next()?.parse().ok()?,
+                    folder: parts.next()?.to_string(),
+                    url: parts.next()?.to_string(),
+                    title: parts.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect();
+        Self { items }
+    }
+    /// Folders in order of first appearance, with the indices of their bookmarks
+    fn folders(&self) -> Vec<(&str, Vec<usize>)> {
+        let mut folders: Vec<(&str, Vec<usize>)> = Vec::new();
+        for (index, item) in self.items.iter().enumerate() {
+            if let Some(folder) = folders.iter_mut().find(|f| f.0 == item.folder) {
+                folder.1.push(index);
+            } else {
+                folders.push((&item.folder, vec![index]));
+            }
+        }
+        folders
+    }
+    /// Exports to the Netscape bookmark file format, which other browsers can import
+    pub fn to_netscape(&self) -> String {
+        let mut text = String::from(
+            "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n<TITLE>Bookmarks</TITLE>\n<H1>Bookmarks</H1>\n<DL><p>\n",
+        );
+        for (folder, indices) in self.folders() {
+            let indent = if folder.is_empty() {
+                "    "
+            } else {
+                text += &format!("    <DT><H3>{}</H3>\n    <DL><p>\n", sanitize(folder));
+                "        "
+            };
+            for index in indices {
+                let item = &self.items[index];
+                text += &format!(
+                    "{indent}<DT><A HREF=\"{}\" ADD_DATE=\"{}\">{}</A>\n",
+                    sanitize(&item.url),
+                    item.added,
+                    sanitize(&item.title)
+                );
+            }
+            if !folder.is_empty() {
+                text += "    </DL><p>\n";
+            }
+        }
+        text += "</DL><p>\n";
+        text
+    }
+    /// Imports bookmarks from the Netscape bookmark file format, skipping ones already bookmarked.
+    ///
+    /// Nested folders are joined with `/`. Returns how many were added.
+    pub fn import_netscape(&mut self, text: &str) -> usize {
+        // one entry per open <DL>, the name of the <H3> before it, if any
+        let mut lists: Vec<Option<String>> = Vec::new();
+        let mut heading = None;
+        let mut added = 0;
+        for caps in NETSCAPE_TOKEN_RE.captures_iter(text) {
+            if let Some(name) = caps.get(1) {
+                heading = Some(parse_special(name.as_str().trim()));
+            } else if let Some(attributes) = caps.get(2) {
+                let mut url = None;
+                let mut added_date = None;
+                for attribute in ATTRIBUTE_RE.captures_iter(attributes.as_str()) {
+                    match attribute[1].to_ascii_lowercase().as_str() {
+                        "href" => url = Some(parse_special(&attribute[2])),
+                        "add_date" => added_date = attribute[2].parse().ok(),
+                        _ => {}
+                    }
+                }
+                let Some(url) = url else {
+                    continue;
+                };
+                if self.get(&url).is_some() {
+                    continue;
+                }
+                let folder: Vec<&str> = lists.iter().flatten().map(String::as_str).collect();
+                self.items.push(Bookmark {
+                    url: strip_separators(&url),
+                    title: strip_separators(&parse_special(caps[3].trim())),
+                    folder: strip_separators(&folder.join("/")),
+                    added: added_date.unwrap_or_else(unix_now),
+                });
+                added += 1;
+            } else if caps[0].starts_with("</") {
+                lists.pop();
+            } else {
+                lists.push(heading.take());
+            }
+        }
+        added
+    }
+    /// Generates the items of toad://bookmarks
+    pub fn to_html(&self) -> String {
+        if self.items.is_empty() {
+            return String::from("<p>no bookmarks yet.</p>");
+        }
+        let mut html = String::new();
+        for (folder, indices) in self.folders() {
+            if !folder.is_empty() {
+                html += &format!("<h2>{}</h2>", sanitize(folder));
+            }
+            for index in indices {
+                let item = &self.items[index];
+                let title = if item.title.is_empty() {
+                    &item.url
+                } else {
+                    &item.title
+                };
+                html += &format!(
+                    "<div class=\"bookmark\"><a href=\"{}\">{}</a> <span class=\"date\">{}</span><br>\
+                    <form action=\"rename_bookmark?id={index}\"><input type=\"text\" name=\"title\" placeholder=\"new title\"> \
+                    <input type=\"text\" name=\"folder\" placeholder=\"folder\"> \
+                    <button type=\"submit\" value=\"Rename\"></button></form> \
+                    <form action=\"delete_bookmark?id={index}\"><button type=\"submit\" value=\"Delete\"></button></form></div>",
+                    sanitize(&item.url),
+                    sanitize(title),
+                    format_date(item.added),
+                );
+            }
+        }
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bookmarks::Bookmarks, utils::format_date};
+
+    #[test]
+    fn test_netscape_roundtrip() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("https://example.com/", "Example & co", "");
+        bookmarks.add("https://toad.example/", "Toad", "frogs");
+        let exported = bookmarks.to_netscape();
+
+        let mut imported = Bookmarks::default();
+        assert_eq!(imported.import_netscape(&exported), 2);
+        assert_eq!(imported, bookmarks);
+        // already bookmarked ones are skipped
+        assert_eq!(imported.import_netscape(&exported), 0);
+
+        let firefox = r#"<DL><p>
+            <DT><H3 ADD_DATE="1">Toolbar</H3>
+            <DL><p>
+                <DT><H3>Nested</H3>
+                <DL><p>
+                    <DT><A HREF="https://a.example/" ADD_DATE="1700000000">A</A>
+                </DL><p>
+                <DT><A HREF="https://b.example/">B</A>
+            </DL><p>
+            <DT><A HREF="https://c.example/">C</A>
+        </DL>"#;
+        let mut imported = Bookmarks::default();
+        imported.import_netscape(firefox);
+        let folders: Vec<&str> = imported.items.iter().map(|f| f.folder.as_str()).collect();
+        assert_eq!(folders, vec!["Toolbar/Nested", "Toolbar", ""]);
+        assert_eq!(format_date(imported.items[0].added), "2023-11-14");
+
+        assert_eq!(Bookmarks::deserialize(&bookmarks.serialize()), bookmarks);
+    }
+}
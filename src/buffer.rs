@@ -10,7 +10,7 @@ use crossterm::{
 use image::{DynamicImage, GenericImageView};
 use unicode_width::UnicodeWidthChar;
 
-use crate::{ElementDrawContext, NonInheritedField, Theme};
+use crate::{ElementDrawContext, NonInheritedField, ScreenClip, Theme};
 
 #[derive(Clone, Copy)]
 struct Cell {
@@ -99,6 +99,8 @@ pub struct Buffer {
     width: usize,
     height: usize,
     theme: &'static Theme,
+    /// If set, nothing is drawn outside of this area
+    clip: Option<ScreenClip>,
 }
 impl Buffer {
     pub fn empty(width: u16, height: u16, theme: &'static Theme) -> Self {
@@ -108,8 +110,16 @@ impl Buffer {
             width: width as _,
             height: height as _,
             theme,
+            clip: None,
         }
     }
+    pub fn set_clip(&mut self, clip: Option<ScreenClip>) {
+        self.clip = clip;
+    }
+    fn in_clip(&self, x: usize, y: usize) -> bool {
+        self.clip
+            .is_none_or(|clip| clip.contains(x as i32, y as i32))
+    }
     pub fn clear_color(&mut self, color: Color) {
         let mut cell = Cell::new(self.theme);
         cell.background_color = color;
@@ -178,6 +188,35 @@ impl Buffer {
             self.interactables[y * self.width + x]
         }
     }
+    /// Finds the top left cell of every interactable at or below `min_y`.
+    ///
+    /// Returns the interactable index, X and Y of each, in the order they appear.
+    pub fn interactable_positions(&self, min_y: u16) -> Vec<(usize, u16, u16)> {
+        let mut positions: Vec<(usize, u16, u16)> = Vec::new();
+        for (index, interactable) in self.interactables.iter().enumerate() {
+            let (x, y) = (index % self.width, index / self.width);
+            if y < min_y as usize {
+                continue;
+            }
+            if let Some(interactable) = interactable
+                && !positions.iter().any(|(i, _, _)| i == interactable)
+            {
+                positions.push((*interactable, x as u16, y as u16));
+            }
+        }
+        positions
+    }
+    /// Finds the left edge of an interactable, and the row right below it
+    pub fn interactable_bottom_left(&self, interactable: usize) -> Option<(u16, u16)> {
+        let mut found: Option<(u16, u16)> = None;
+        for (index, cell) in self.interactables.iter().enumerate() {
+            if *cell == Some(interactable) {
+                let (x, y) = ((index % self.width) as u16, (index / self.width) as u16);
+                found = Some(found.map_or((x, y + 1), |(fx, fy)| (fx.min(x), fy.max(y + 1))));
+            }
+        }
+        found
+    }
     #[expect(clippy::too_many_arguments)]
     pub fn draw_input_box(
         &mut self,
@@ -188,15 +227,22 @@ impl Buffer {
         height: u16,
         text: &str,
         highlighted: bool,
-        interactable: usize,
+        interactable: Option<usize>,
     ) {
         let mut text_chars = text.chars();
-        let background_color = if !highlighted {
+        // inputs that can't be interacted with are disabled, and drawn faded
+        let background_color = if interactable.is_none() {
+            self.theme.background_color
+        } else if !highlighted {
             self.theme.ui_color
         } else {
             self.theme.interactive_color
         };
-        let border_color = self.theme.text_color;
+        let border_color = if interactable.is_none() {
+            self.theme.ui_color
+        } else {
+            self.theme.text_color
+        };
         let mut skip = false;
         for column in 0..width {
             if skip {
@@ -204,6 +250,9 @@ impl Buffer {
                 continue;
             }
             let index = column as usize + x as usize + y as usize * self.width;
+            if !self.in_clip(column as usize + x as usize, y as usize) {
+                continue;
+            }
             let char: Cow<str> = if row == 0 {
                 if column == 0 {
                     Cow::Borrowed(box_drawing::double::DOWN_RIGHT)
@@ -222,9 +271,7 @@ impl Buffer {
                 }
             } else if column == 0 || column == width - 1 {
                 Cow::Borrowed(box_drawing::double::VERTICAL)
-            } else if row == 1
-                && let Some(char) = text_chars.next()
-            {
+            } else if let Some(char) = text_chars.next() {
                 if column < width - 3 {
                     Cow::Owned(char.to_string())
                 } else {
@@ -242,12 +289,12 @@ impl Buffer {
                 ..Cell::new(self.theme)
             };
             self.data[index] = cell;
-            self.interactables[index] = Some(interactable);
+            self.interactables[index] = interactable;
             if let Some(w) = char.width()
                 && w > 1
             {
                 self.data[index + 1] = Cell { char: ' ', ..cell };
-                self.interactables[index + 1] = Some(interactable);
+                self.interactables[index + 1] = interactable;
                 skip = true;
             }
         }
@@ -258,6 +305,9 @@ impl Buffer {
             if index >= self.data.len() {
                 return;
             }
+            if !self.in_clip(column as usize + x as usize, y as usize) {
+                continue;
+            }
             let background_color = self.data[index].background_color;
             let top_rgba = image.get_pixel(column as _, row as _).0;
             let top_color = if top_rgba[3] == 0 {
@@ -295,7 +345,7 @@ impl Buffer {
                 if y + i >= self.height {
                     continue;
                 }
-                if x + j >= self.width {
+                if x + j >= self.width || !self.in_clip(x + j, y + i) {
                     continue;
                 }
                 let index = x + j + (y + i) * self.width;
@@ -305,6 +355,18 @@ impl Buffer {
             }
         }
     }
+    /// Recolors a row of cells, keeping their characters. Used to highlight text
+    pub fn highlight(&mut self, x: u16, y: u16, width: u16, color: Color) {
+        let (x, y) = (x as usize, y as usize);
+        for x in x..x + width as usize {
+            if x >= self.width || y >= self.height || !self.in_clip(x, y) {
+                continue;
+            }
+            let cell = &mut self.data[x + y * self.width];
+            cell.background_color = color;
+            cell.foreground_color = Color::Black;
+        }
+    }
     /// Insert a string somewhere. Newlines not permitted!
     pub fn draw_str(
         &mut self,
@@ -318,12 +380,31 @@ impl Buffer {
         if y >= self.height {
             return;
         }
+        let start_x = x as usize;
         let mut x = x as usize;
         for char in text.chars() {
             if x >= self.width {
                 continue;
             }
             let width = char.width().unwrap_or_default();
+            if let Some(clip) = self.clip
+                && x as i32 + width as i32 > clip.x1
+            {
+                // replace the last visible character with an ellipsis if the text is cut off
+                if clip.ellipsis
+                    && clip.x1 > clip.x0.max(start_x as i32)
+                    && clip.x1 as usize <= self.width
+                    && (clip.y0..clip.y1).contains(&(y as i32))
+                    && let Some(cell) = self.data.get_mut(clip.x1 as usize - 1 + y * self.width)
+                {
+                    cell.char = '…';
+                }
+                break;
+            }
+            if !self.in_clip(x, y) {
+                x += width;
+                continue;
+            }
             let i = x + y * self.width;
             let cell = self.data.get_mut(i).unwrap();
             self.interactables[i] = interactable;
@@ -345,7 +426,7 @@ impl Buffer {
 
 #[cfg(test)]
 mod tests {
-    use crate::{DEFAULT_DRAW_CTX, buffer::Buffer, config::THEMES};
+    use crate::{DEFAULT_DRAW_CTX, ScreenClip, buffer::Buffer, config::THEMES};
 
     #[test]
     fn test_write_str() {
@@ -375,4 +456,26 @@ mod tests {
         assert_eq!(buf.data[0].background_color, theme.background_color);
         assert_eq!(buf.data[1].background_color, theme.interactive_color);
     }
+    #[test]
+    fn test_clip() {
+        let theme = &THEMES[0];
+        let mut buf = Buffer::empty(10, 2, theme);
+        buf.set_clip(Some(ScreenClip {
+            x0: 1,
+            y0: 0,
+            x1: 5,
+            y1: 1,
+            shift_x: 0,
+            ellipsis: true,
+        }));
+        buf.draw_str(0, 0, "abcdefgh", &DEFAULT_DRAW_CTX, None);
+        // text below the clip is hidden, ellipsis included
+        buf.draw_str(0, 1, "abcdefgh", &DEFAULT_DRAW_CTX, None);
+        buf.draw_rect(0, 1, 10, 1, theme.interactive_color);
+        let text: String = buf.data[..10].iter().map(|c| c.char).collect();
+        assert_eq!(text, " bcd…     ");
+        let text: String = buf.data[10..].iter().map(|c| c.char).collect();
+        assert_eq!(text, "          ");
+        assert_eq!(buf.data[11].background_color, theme.background_color);
+    }
 }
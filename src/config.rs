@@ -1,36 +1,219 @@
-use std::collections::VecDeque;
+use std::{io::Write, path::PathBuf};
 
 use crossterm::style;
 
-use crate::consts::*;
+use crate::{
+    autofill::AutofillStore,
+    blocker::Blocker,
+    bookmarks::Bookmarks,
+    consts::*,
+    history::{History, HistoryEntry},
+    keybinds::Keybinds,
+    search::{DEFAULT_SEARCH_ENGINE, default_search_keywords},
+    utils::unix_now,
+};
 
-pub fn write_settings(settings: &ToadSettings) {
-    let path = if let Ok(p) = std::env::current_exe()
-        && let Some(d) = p.parent()
+/// Resolves an XDG base directory, like `$XDG_CONFIG_HOME/toad`, falling back to `fallback` in the home directory
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|f| f.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|f| PathBuf::from(f).join(fallback)))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    let dir = base.join("toad");
+    let _ = std::fs::create_dir_all(&dir);
+    Some(dir)
+}
+/// The directory config files are stored in
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+/// The directory state, like history, is stored in
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+pub fn user_stylesheet_path() -> Option<PathBuf> {
+    config_dir().map(|d| d.join(USER_STYLESHEET_FILENAME))
+}
+pub fn keybinds_path() -> Option<PathBuf> {
+    config_dir().map(|d| d.join(KEYBINDS_FILENAME))
+}
+pub fn load_keybinds() -> Keybinds {
+    keybinds_path()
+        .and_then(|f| std::fs::read_to_string(f).ok())
+        .map(|f| Keybinds::parse(&f))
+        .unwrap_or_default()
+}
+pub fn load_bookmarks() -> Bookmarks {
+    data_dir()
+        .and_then(|d| std::fs::read_to_string(d.join(BOOKMARKS_FILENAME)).ok())
+        .map(|f| Bookmarks::deserialize(&f))
+        .unwrap_or_default()
+}
+pub fn write_bookmarks(bookmarks: &Bookmarks) {
+    if let Some(path) = data_dir().map(|d| d.join(BOOKMARKS_FILENAME)) {
+        let _ = std::fs::write(path, bookmarks.serialize());
+    }
+}
+/// Where saved form values are stored, encrypted
+pub fn autofill_path() -> Option<PathBuf> {
+    data_dir().map(|d| d.join(AUTOFILL_FILENAME))
+}
+/// Unlocks the autofill store with the master passphrase, or creates an empty one if there's none yet
+pub fn load_autofill(passphrase: &str) -> Result<AutofillStore, &'static str> {
+    if passphrase.is_empty() {
+        return Err("the passphrase can't be empty");
+    }
+    let Some(path) = autofill_path() else {
+        return AutofillStore::create(passphrase);
+    };
+    // a store that can't be read isn't replaced, so saved logins aren't lost
+    match std::fs::read(path) {
+        Ok(data) => AutofillStore::unlock(&data, passphrase),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => AutofillStore::create(passphrase),
+        Err(_) => Err("the autofill store couldn't be read"),
+    }
+}
+/// Writes the autofill store, leaving the file as it was if it can't be encrypted
+pub fn write_autofill(store: &AutofillStore) -> Result<(), &'static str> {
+    let path = autofill_path().ok_or("there's no data directory to save autofill to")?;
+    let data = store.seal()?;
+    std::fs::write(path, data).map_err(|_| "the autofill store couldn't be written")
+}
+/// Where filter lists are loaded from
+pub fn filters_dir() -> Option<PathBuf> {
+    let dir = config_dir()?.join(FILTERS_DIRNAME);
+    let _ = std::fs::create_dir_all(&dir);
+    Some(dir)
+}
+/// Loads every filter list in the filters directory, and the sites they're not used on
+pub fn load_blocker() -> Blocker {
+    let mut blocker = Blocker::default();
+    let mut paths: Vec<PathBuf> = filters_dir()
+        .and_then(|f| std::fs::read_dir(f).ok())
+        .map(|f| {
+            f.flatten()
+                .map(|f| f.path())
+                .filter(|f| f.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    for path in paths {
+        if let Ok(text) = std::fs::read_to_string(path) {
+            blocker.add_list(&text);
+        }
+    }
+    if let Some(text) =
+        data_dir().and_then(|d| std::fs::read_to_string(d.join(ALLOWLIST_FILENAME)).ok())
     {
-        d.join(CONFIG_FILENAME)
+        blocker.allowlist = text
+            .lines()
+            .map(|f| f.trim().to_lowercase())
+            .filter(|f| !f.is_empty())
+            .collect();
+    }
+    blocker
+}
+pub fn write_allowlist(blocker: &Blocker) {
+    if let Some(path) = data_dir().map(|d| d.join(ALLOWLIST_FILENAME)) {
+        let _ = std::fs::write(
+            path,
+            blocker
+                .allowlist
+                .iter()
+                .map(|f| format!("{f}\n"))
+                .collect::<String>(),
+        );
+    }
+}
+/// Where bookmarks are imported from and exported to, by file name in the data directory.
+///
+/// Paths outside the data directory are refused, so pages can't name other files
+pub fn bookmarks_file_path(name: &str) -> Option<PathBuf> {
+    let name = if name.is_empty() {
+        BOOKMARKS_EXPORT_FILENAME
+    } else {
+        name
+    };
+    let dir = data_dir()?;
+    let path = PathBuf::from(name);
+    let relative = if path.is_absolute() {
+        path.strip_prefix(&dir).ok()?.to_path_buf()
     } else {
+        path
+    };
+    let plain = relative
+        .components()
+        .all(|f| matches!(f, std::path::Component::Normal(_)));
+    (plain && relative.file_name().is_some()).then(|| dir.join(relative))
+}
+/// Loads history, forgetting old entries, and compacts the history file
+pub fn load_history() -> History {
+    let now = unix_now();
+    let Some(path) = data_dir().map(|d| d.join(HISTORY_FILENAME)) else {
+        return History::default();
+    };
+    let mut history = std::fs::read_to_string(&path)
+        .map(|f| History::deserialize(&f, now))
+        .unwrap_or_default();
+    history.prune(now);
+    let _ = std::fs::write(path, history.serialize());
+    history
+}
+pub fn write_history(history: &History) {
+    if let Some(path) = data_dir().map(|d| d.join(HISTORY_FILENAME)) {
+        let _ = std::fs::write(path, history.serialize());
+    }
+}
+/// Appends a visit to the history file, so it doesn't have to be rewritten on every page load
+pub fn append_history_visit(visit: &HistoryEntry) {
+    let Some(path) = data_dir().map(|d| d.join(HISTORY_FILENAME)) else {
         return;
     };
-    let _ = std::fs::write(path, settings.serialize());
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        let _ = file.write_all(visit.serialize().as_bytes());
+    }
+}
+pub fn write_settings(settings: &ToadSettings) {
+    if let Some(path) = config_dir().map(|d| d.join(SETTINGS_FILENAME)) {
+        let _ = std::fs::write(path, settings.serialize());
+    }
 }
 pub fn load_settings() -> ToadSettings {
-    let path = if let Ok(p) = std::env::current_exe()
-        && let Some(d) = p.parent()
-    {
-        d.join(CONFIG_FILENAME)
-    } else {
+    let Some(path) = config_dir().map(|d| d.join(SETTINGS_FILENAME)) else {
         return ToadSettings::default();
     };
-    if path.exists()
-        && let Ok(data) = std::fs::read(path)
+    if let Ok(text) = std::fs::read_to_string(&path) {
+        return ToadSettings::deserialize(&text);
+    }
+    // migrate the binary config of older versions, which was stored next to the executable
+    let legacy_path = std::env::current_exe()
+        .ok()
+        .and_then(|f| f.parent().map(|d| d.join(LEGACY_CONFIG_FILENAME)));
+    if let Some(legacy_path) = legacy_path
+        && let Ok(data) = std::fs::read(&legacy_path)
     {
-        return ToadSettings::deserialize(&data);
+        let (settings, history) = ToadSettings::deserialize_legacy(&data);
+        write_settings(&settings);
+        if let Some(history_path) = data_dir().map(|d| d.join(HISTORY_FILENAME)) {
+            // one URL per line, read by [History::deserialize] as visits of today
+            let _ = std::fs::write(history_path, history.join("\n"));
+        }
+        if path.exists() {
+            let _ = std::fs::remove_file(legacy_path);
+        }
+        return settings;
     }
 
     ToadSettings::default()
 }
 
+#[derive(PartialEq, Debug)]
 pub struct Theme {
     /// White on light theme
     pub background_color: style::Color,
@@ -45,55 +228,107 @@ pub struct Theme {
     /// Used for CSS media selectors
     pub is_dark: bool,
 }
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct ToadSettings {
     pub images_enabled: bool,
     pub css_enabled: bool,
     pub theme: &'static Theme,
-    pub history: VecDeque<String>,
+    /// Whether the last session is reopened on start
+    pub restore_session: bool,
+    /// Name of the session tabs are saved to
+    pub session: String,
+    /// URL template searched with when text that isn't a URL is typed in the URL bar, where `%s` is the search terms
+    pub search_engine: String,
+    /// Search templates used when typed text starts with the keyword, like `w toad` for wikipedia
+    pub search_keywords: Vec<(String, String)>,
+    /// Keys of the settings file that aren't known, kept so they aren't lost when saving
+    pub unknown: Vec<(String, String)>,
 }
 impl ToadSettings {
-    pub fn serialize(&self) -> Vec<u8> {
-        let mut data = Vec::new();
-        data.push(if self.images_enabled { 1 } else { 0 });
-        data.push(if self.css_enabled { 1 } else { 0 });
-        data.push(
-            THEMES
-                .iter()
-                .position(|f| std::ptr::eq(f, self.theme))
-                .unwrap() as u8,
-        );
-        for item in self.history.iter() {
-            data.append(&mut item.as_bytes().to_vec());
-            data.push(0);
+    pub fn serialize(&self) -> String {
+        let theme = THEMES
+            .iter()
+            .position(|f| std::ptr::eq(f, self.theme))
+            .unwrap();
+        let mut text = format!("# toad settings\nversion = {SETTINGS_VERSION}\n");
+        text += &format!("images_enabled = {}\n", self.images_enabled);
+        text += &format!("css_enabled = {}\n", self.css_enabled);
+        text += &format!("theme = {}\n", THEME_NAMES[theme]);
+        text += &format!("restore_session = {}\n", self.restore_session);
+        text += &format!("session = {}\n", self.session);
+        text += &format!("search_engine = {}\n", self.search_engine);
+        // keywords and templates can't contain spaces, so they're stored as pairs separated by spaces
+        let keywords: Vec<String> = self
+            .search_keywords
+            .iter()
+            .map(|(keyword, template)| format!("{keyword} {template}"))
+            .collect();
+        text += &format!("search_keywords = {}\n", keywords.join(" "));
+        for (key, value) in self.unknown.iter() {
+            text += &format!("{key} = {value}\n");
+        }
+        text
+    }
+    pub fn deserialize(text: &str) -> Self {
+        let mut settings = Self::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                // there's only been one version so far
+                "version" => {}
+                "images_enabled" => settings.images_enabled = value != "false",
+                "css_enabled" => settings.css_enabled = value != "false",
+                "theme" => {
+                    if let Some(index) = THEME_NAMES.iter().position(|f| *f == value) {
+                        settings.theme = &THEMES[index];
+                    }
+                }
+                "restore_session" => settings.restore_session = value != "false",
+                "session" => settings.session = value.to_string(),
+                "search_engine" => {
+                    if value.contains("%s") {
+                        settings.search_engine = value.to_string();
+                    }
+                }
+                "search_keywords" => {
+                    let parts: Vec<&str> = value.split_whitespace().collect();
+                    settings.search_keywords = parts
+                        .chunks_exact(2)
+                        .map(|f| (f[0].to_string(), f[1].to_string()))
+                        .collect();
+                }
+                _ => settings.unknown.push((key.to_string(), value.to_string())),
+            }
         }
-        data
+        settings
     }
-    pub fn deserialize(data: &[u8]) -> Self {
+    /// Reads the positional binary format of `toad_config.bin`, returning the settings and history URLs
+    pub fn deserialize_legacy(data: &[u8]) -> (Self, Vec<String>) {
         if data.len() < 3 {
-            return ToadSettings::default();
+            return (ToadSettings::default(), Vec::new());
         }
         let images_enabled = data[0] == 1;
         let css_enabled = data[1] == 1;
-        let theme_index = data[2] as usize;
-        let history_data = data[3..].to_vec();
-        let mut history = VecDeque::new();
-        let mut last = String::new();
-        for char in history_data.iter() {
-            if *char == 0 {
-                history.push_back(last);
-                last = String::new();
-            } else {
-                let char = *char as char;
-                last.push(char);
-            }
-        }
-        Self {
+        let theme_index = (data[2] as usize).min(THEMES.len() - 1);
+        let history = data[3..]
+            .split(|f| *f == 0)
+            .filter(|f| !f.is_empty())
+            .map(|f| String::from_utf8_lossy(f).to_string())
+            .collect();
+        let settings = Self {
             images_enabled,
             css_enabled,
             theme: &THEMES[theme_index],
-            history,
-        }
+            ..Default::default()
+        };
+        (settings, history)
     }
 }
 impl Default for ToadSettings {
@@ -102,11 +337,17 @@ impl Default for ToadSettings {
             images_enabled: true,
             css_enabled: true,
             theme: &THEMES[0],
-            history: VecDeque::new(),
+            restore_session: true,
+            session: String::from(DEFAULT_SESSION_NAME),
+            search_engine: String::from(DEFAULT_SEARCH_ENGINE),
+            search_keywords: default_search_keywords(),
+            unknown: Vec::new(),
         }
     }
 }
 
+/// Names of [THEMES] in the settings file
+static THEME_NAMES: &[&str] = &["light", "dark"];
 pub static THEMES: &[Theme] = &[
     Theme {
         background_color: style::Color::Rgb {
@@ -147,3 +388,49 @@ pub static THEMES: &[Theme] = &[
         is_dark: true,
     },
 ];
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{THEMES, ToadSettings},
+        history::History,
+    };
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let text = "version = 1\ncss_enabled = false\ntheme = dark\nfrom_the_future = 3\n";
+        let settings = ToadSettings::deserialize(text);
+        assert!(!settings.css_enabled);
+        assert!(settings.images_enabled);
+        assert!(std::ptr::eq(settings.theme, &THEMES[1]));
+        assert_eq!(ToadSettings::deserialize(&settings.serialize()), settings);
+        assert!(settings.serialize().contains("from_the_future = 3"));
+
+        let settings = ToadSettings::deserialize("search_keywords =\nsearch_engine = nope\n");
+        assert!(settings.search_keywords.is_empty());
+        assert_eq!(
+            settings.search_engine,
+            ToadSettings::default().search_engine
+        );
+        assert_eq!(ToadSettings::deserialize(&settings.serialize()), settings);
+    }
+    #[test]
+    fn test_legacy_migration() {
+        let mut data = vec![0, 1, 1];
+        data.extend_from_slice(
+            "https://example.com/\0https://ja.wikipedia.org/wiki/ヒキガエル\0".as_bytes(),
+        );
+        let (settings, history) = ToadSettings::deserialize_legacy(&data);
+        assert!(!settings.images_enabled);
+        assert!(std::ptr::eq(settings.theme, &THEMES[1]));
+        assert_eq!(history[1], "https://ja.wikipedia.org/wiki/ヒキガエル");
+        assert_eq!(ToadSettings::deserialize(&settings.serialize()), settings);
+
+        let history = History::deserialize(&history.join("\n"), 0);
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(
+            history.entries[1].url,
+            "https://ja.wikipedia.org/wiki/ヒキガエル"
+        );
+    }
+}
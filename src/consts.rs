@@ -1,4 +1,51 @@
+use crossterm::style::Color;
+
 pub const EM: u16 = 8;
 pub const LH: u16 = 16;
 
-pub const CONFIG_FILENAME: &str = "toad_config.bin";
+/// How long messages are shown in the status line, in seconds
+pub const STATUS_MESSAGE_DURATION: u64 = 3;
+
+/// Width reserved for the match counter next to the find-in-page bar
+pub const FIND_COUNTER_WIDTH: u16 = 16;
+
+/// Binary settings file of older versions, stored next to the executable
+pub const LEGACY_CONFIG_FILENAME: &str = "toad_config.bin";
+pub const SETTINGS_FILENAME: &str = "settings.conf";
+pub const SETTINGS_VERSION: u32 = 1;
+pub const HISTORY_FILENAME: &str = "history.txt";
+/// History entries not visited for this many days are forgotten
+pub const HISTORY_MAX_AGE_DAYS: u64 = 90;
+pub const SESSIONS_DIRNAME: &str = "sessions";
+pub const SESSION_EXTENSION: &str = "session";
+pub const DEFAULT_SESSION_NAME: &str = "default";
+/// Session saved to when restoring sessions is disabled, so the previous one can still be opened
+pub const LAST_SESSION_NAME: &str = "last";
+/// How often the session is saved, in seconds
+pub const SESSION_SAVE_INTERVAL: u64 = 30;
+pub const BOOKMARKS_FILENAME: &str = "bookmarks.txt";
+pub const AUTOFILL_FILENAME: &str = "autofill.bin";
+/// Directory filter lists are loaded from, in the config directory
+pub const FILTERS_DIRNAME: &str = "filters";
+/// Sites content isn't blocked on, one per line
+pub const ALLOWLIST_FILENAME: &str = "allowlist.txt";
+pub const BOOKMARKS_EXPORT_FILENAME: &str = "bookmarks.html";
+pub const USER_STYLESHEET_FILENAME: &str = "user.css";
+pub const KEYBINDS_FILENAME: &str = "keys.conf";
+
+pub const FIND_MATCH_COLOR: Color = Color::Rgb {
+    r: 255,
+    g: 235,
+    b: 59,
+};
+pub const FIND_CURRENT_COLOR: Color = Color::Rgb {
+    r: 255,
+    g: 152,
+    b: 0,
+};
+/// Background of the element selected in the inspector
+pub const INSPECTOR_HIGHLIGHT_COLOR: Color = Color::Rgb {
+    r: 111,
+    g: 168,
+    b: 220,
+};
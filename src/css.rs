@@ -1,8 +1,8 @@
 use crossterm::style;
 
 use crate::{
-    DEFAULT_DRAW_CTX, Display, ElementDrawContext, Measurement, NonInheritedField::*, StyleTarget,
-    StyleTargetType, TextAlignment, consts::*, utils::*,
+    DEFAULT_DRAW_CTX, Display, ElementDrawContext, Measurement, NonInheritedField::*, Overflow,
+    StyleTarget, StyleTargetType, TextAlignment, TextOverflow, WhiteSpace, consts::*, utils::*,
 };
 
 fn hex_to_rgb(value: u32) -> style::Color {
@@ -97,6 +97,33 @@ fn parse_display_mode(text: &str) -> Option<Display> {
         _ => None,
     }
 }
+fn parse_white_space(text: &str) -> Option<WhiteSpace> {
+    match text.to_lowercase().trim() {
+        "normal" => Some(WhiteSpace::Normal),
+        "nowrap" => Some(WhiteSpace::NoWrap),
+        "pre" => Some(WhiteSpace::Pre),
+        "pre-wrap" | "break-spaces" => Some(WhiteSpace::PreWrap),
+        "pre-line" => Some(WhiteSpace::PreLine),
+        _ => None,
+    }
+}
+fn parse_overflow(text: &str) -> Option<Overflow> {
+    // the shorthand can specify both axes, like `overflow: hidden auto`.
+    // since only horizontal overflow is handled, look at the first
+    match text.to_lowercase().split_whitespace().next()? {
+        "visible" => Some(Overflow::Visible),
+        "hidden" | "clip" => Some(Overflow::Hidden),
+        "auto" | "scroll" => Some(Overflow::Auto),
+        _ => None,
+    }
+}
+fn parse_text_overflow(text: &str) -> Option<TextOverflow> {
+    match text.to_lowercase().trim() {
+        "clip" => Some(TextOverflow::Clip),
+        "ellipsis" => Some(TextOverflow::Ellipsis),
+        _ => None,
+    }
+}
 fn parse_measurement(text: &str) -> Option<Measurement> {
     if text.ends_with("px") {
         text.trim_end_matches("px")
@@ -199,6 +226,15 @@ fn try_apply_rule(ctx: &mut ElementDrawContext, rule: &str) {
                 ctx.width = Specified(width);
             }
         }
+        "max-width" => {
+            if value == "inherit" {
+                ctx.max_width = Inherit;
+            } else if value == "none" {
+                ctx.max_width = Unset;
+            } else if let Some(width) = parse_horizontal_measurement(value) {
+                ctx.max_width = Specified(width);
+            }
+        }
         "height" => {
             if value == "inherit" {
                 ctx.height = Inherit;
@@ -206,6 +242,25 @@ fn try_apply_rule(ctx: &mut ElementDrawContext, rule: &str) {
                 ctx.height = Specified(height);
             }
         }
+        "white-space" => {
+            if let Some(white_space) = parse_white_space(value) {
+                ctx.white_space = Some(white_space);
+            }
+        }
+        "overflow" | "overflow-x" => {
+            if value == "inherit" {
+                ctx.overflow = Inherit;
+            } else if let Some(overflow) = parse_overflow(value) {
+                ctx.overflow = Specified(overflow);
+            }
+        }
+        "text-overflow" => {
+            if value == "inherit" {
+                ctx.text_overflow = Inherit;
+            } else if let Some(text_overflow) = parse_text_overflow(value) {
+                ctx.text_overflow = Specified(text_overflow);
+            }
+        }
         _ => {}
     }
 }
@@ -253,6 +308,14 @@ fn pop_exit_media_selector(text: &mut Vec<char>) -> (String, String) {
     (selector, content)
 }
 fn parse_target_type(specifier: &str, type_requirement: Option<String>) -> Option<StyleTargetType> {
+    if let Some(inner) = specifier.strip_suffix(":visited") {
+        let inner = if inner.is_empty() {
+            None
+        } else {
+            Some(Box::new(parse_target_type(inner, type_requirement)?))
+        };
+        return Some(StyleTargetType::Visited(inner));
+    }
     let char = specifier.chars().next()?;
     let target: StyleTargetType = if char == '#' {
         StyleTargetType::Id(specifier[1..].to_string(), type_requirement)
@@ -345,16 +408,78 @@ pub fn parse_stylesheet(
     }
 }
 
+/// Removes all `/* */` comments
+fn remove_comments(text: &str) -> String {
+    let mut new = String::new();
+    let mut rest = text;
+    while let Some((before, after)) = rest.split_once("/*") {
+        new += before;
+        rest = after
+            .split_once("*/")
+            .map(|(_, after)| after)
+            .unwrap_or_default();
+    }
+    new += rest;
+    new
+}
+
+/// Checks whether the domains of a `@-moz-document` rule, like `domain(example.com), domain("toad.rs")`,
+/// include a host. Subdomains of a domain are also included.
+fn document_rule_matches(rule: &str, host: &str) -> bool {
+    rule.split(',').any(|part| {
+        let Some(domain) = part
+            .trim()
+            .strip_prefix("domain(")
+            .and_then(|f| f.strip_suffix(')'))
+        else {
+            return false;
+        };
+        let domain = domain.trim().trim_matches(['"', '\'']);
+        host == domain || host.ends_with(&format!(".{domain}"))
+    })
+}
+
+/// Parses the user stylesheet. Rules inside of a `@-moz-document domain(...)` block are only included if `host` matches the domain.
+pub fn parse_user_stylesheet(
+    text: &str,
+    host: Option<&str>,
+    style: &mut Vec<(StyleTarget, ElementDrawContext)>,
+) {
+    let mut chars: Vec<char> = remove_comments(text).chars().collect();
+    chars.reverse();
+    let mut active = String::new();
+    while let Some(char) = chars.pop() {
+        if char == '@' {
+            let (selector, rule_contents) = pop_exit_media_selector(&mut chars);
+            if let Some(domains) = selector.trim().strip_prefix("-moz-document") {
+                if host.is_some_and(|host| document_rule_matches(domains, host)) {
+                    active += &rule_contents;
+                }
+            } else {
+                active += &format!("@{selector}{{{rule_contents}}}");
+            }
+            continue;
+        }
+        active.push(char);
+    }
+    parse_stylesheet(&active, style, MediaThemeSelector::Unset);
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        ElementTargetInfo, StyleTargetType,
-        css::{parse_target, parse_target_type, pop_exit_media_selector},
+        DEFAULT_DRAW_CTX, ElementTargetInfo,
+        NonInheritedField::*,
+        Overflow, StyleTargetType, TextOverflow, WhiteSpace,
+        css::{
+            parse_ruleset, parse_target, parse_target_type, parse_user_stylesheet,
+            pop_exit_media_selector,
+        },
     };
     #[test]
     fn test_parse_target() {
         let a = parse_target("div #div h1.div p").unwrap();
-        let expected = vec![
+        let expected = [
             StyleTargetType::ElementType(String::from("div")),
             StyleTargetType::Id(String::from("div"), None),
             StyleTargetType::Class(String::from("div"), Some(String::from("h1"))),
@@ -370,27 +495,32 @@ mod tests {
                 type_name: "initial extra whatever",
                 id: None,
                 classes: vec![],
+                visited: false,
             },
             // all following elements replicate the expected structure of test target
             ElementTargetInfo {
                 type_name: "div",
                 id: None,
                 classes: vec![],
+                visited: false,
             },
             ElementTargetInfo {
                 type_name: "whatver",
                 id: Some(String::from("div")),
                 classes: vec![],
+                visited: false,
             },
             ElementTargetInfo {
                 type_name: "h1",
                 id: None,
                 classes: vec![String::from("div")],
+                visited: false,
             },
             ElementTargetInfo {
                 type_name: "p",
                 id: None,
                 classes: vec![],
+                visited: false,
             },
         ];
         assert!(a.matches(&info, false));
@@ -405,6 +535,7 @@ mod tests {
             type_name: "p",
             id: Some(String::from("item")),
             classes: vec![],
+            visited: false,
         }];
         assert!(b.matches(&element, false));
 
@@ -414,10 +545,32 @@ mod tests {
             type_name: "p",
             id: Some(String::from("item")),
             classes: vec![],
+            visited: false,
         }];
         assert!(!c.matches(&element, false));
     }
 
+    #[test]
+    fn test_visited_target() {
+        assert_eq!(
+            parse_target_type("a:visited", None),
+            Some(StyleTargetType::Visited(Some(Box::new(
+                StyleTargetType::ElementType("a".to_string())
+            ))))
+        );
+
+        let target = parse_target("a:visited").unwrap();
+        let mut element = [ElementTargetInfo {
+            type_name: "a",
+            id: None,
+            classes: vec![],
+            visited: false,
+        }];
+        assert!(!target.matches(&element, false));
+        element[0].visited = true;
+        assert!(target.matches(&element, false));
+    }
+
     #[test]
     fn test_parse_target_type() {
         assert_eq!(
@@ -441,6 +594,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_overflow_rules() {
+        let mut ctx = DEFAULT_DRAW_CTX;
+        parse_ruleset(
+            "white-space: nowrap; overflow: hidden auto; text-overflow: ellipsis",
+            &mut ctx,
+        );
+        assert_eq!(ctx.white_space, Some(WhiteSpace::NoWrap));
+        assert_eq!(ctx.overflow, Specified(Overflow::Hidden));
+        assert_eq!(ctx.text_overflow, Specified(TextOverflow::Ellipsis));
+    }
+
+    #[test]
+    fn test_user_stylesheet_domains() {
+        let text = "/* comment { */ p { color: red; }
+            @-moz-document domain(example.com), domain(\"toad.rs\") { h1 { color: blue; } }";
+        let mut style = Vec::new();
+        parse_user_stylesheet(text, Some("en.example.com"), &mut style);
+        assert_eq!(style.len(), 2);
+        let mut style = Vec::new();
+        parse_user_stylesheet(text, Some("notexample.com"), &mut style);
+        assert_eq!(style.len(), 1);
+        let mut style = Vec::new();
+        parse_user_stylesheet(text, None, &mut style);
+        assert_eq!(style.len(), 1);
+    }
+
     #[test]
     fn test_pop_until_outside() {
         let mut chars: Vec<char> = "wahoo { h { rgr grg} wello {w aw a wa} }hello {wa}"
@@ -1,9 +1,21 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
 
 use crate::{
-    ActualMeasurement, DEFAULT_DRAW_CTX, Display, DrawCall, ElementDrawContext, ElementTargetInfo,
-    Form, GlobalDrawContext, Interactable, Measurement, NonInheritedField::*, TextPrefix,
-    consts::*, css, parsing::parse_special,
+    ActualMeasurement, Checkable, ClipInfo, DEFAULT_DRAW_CTX, Display, DrawCall,
+    ElementDrawContext, ElementTargetInfo, Form, GlobalDrawContext, Interactable, Measurement,
+    NonInheritedField::*,
+    Overflow, Submitter, TextOverflow, TextPrefix, WhiteSpace,
+    consts::*,
+    css,
+    file_picker::FileInput,
+    parsing::parse_special,
+    reader::inner_text,
+    select::Select,
+    textarea::wrap_text,
+    validation::{Constraints, mask},
 };
 use crossterm::style;
 use reqwest::{Method, Url};
@@ -127,10 +139,11 @@ static HTML: ElementType = ElementType {
 static PRE: ElementType = ElementType {
     name: "pre",
     draw_ctx: ElementDrawContext {
-        respect_whitespace: true,
+        white_space: Some(WhiteSpace::Pre),
         width: Specified(Measurement::FitContentWidth),
         height: Specified(Measurement::FitContentHeight),
         display: Specified(Display::Block),
+        overflow: Specified(Overflow::Auto),
         ..DEFAULT_DRAW_CTX
     },
     ..DEFAULT_ELEMENT_TYPE
@@ -159,7 +172,7 @@ static INPUT: ElementType = ElementType {
 static CODE: ElementType = ElementType {
     name: "code",
     draw_ctx: ElementDrawContext {
-        respect_whitespace: true,
+        white_space: Some(WhiteSpace::PreWrap),
         width: Specified(Measurement::FitContentWidth),
         height: Specified(Measurement::FitContentHeight),
         display: Specified(Display::Inline),
@@ -193,7 +206,14 @@ pub static ELEMENT_TYPES: &[ElementType] = &[
     },
     ElementType {
         name: "select",
-        stops_parsing: true,
+        ..DEFAULT_ELEMENT_TYPE
+    },
+    ElementType {
+        name: "option",
+        ..DEFAULT_ELEMENT_TYPE
+    },
+    ElementType {
+        name: "optgroup",
         ..DEFAULT_ELEMENT_TYPE
     },
     ElementType {
@@ -225,10 +245,17 @@ pub static ELEMENT_TYPES: &[ElementType] = &[
     },
     ElementType {
         name: "textarea",
+        // its content is the field's initial text, not markup
+        stops_parsing: true,
         ..PRE
     },
     ElementType {
         name: "blockquote",
+        draw_ctx: ElementDrawContext {
+            white_space: Some(WhiteSpace::PreWrap),
+            overflow: Unset,
+            ..PRE.draw_ctx
+        },
         ..PRE
     },
     ElementType { name: "dl", ..P },
@@ -402,16 +429,18 @@ pub static ELEMENT_TYPES: &[ElementType] = &[
     ElementType { name: "h5", ..H1 },
     ElementType { name: "h6", ..H1 },
 ];
+/// Splits text into lines, breaking at newlines and, if `parent_width` is known, wherever the text reaches the parent's edge.
+///
+/// Pass [None] as `parent_width` for text that shouldn't wrap.
 pub fn fit_text_in_width(
     text: &str,
-    parent_width: ActualMeasurement,
+    parent_width: Option<u16>,
     starting_x: u16,
     blocked_lines: &HashMap<u16, u16>,
     line_y_offset: u16,
 ) -> Vec<String> {
     let mut parts = vec![String::new()];
     let mut x = starting_x / EM;
-    let parent_width = parent_width.get_pixels();
     for char in text.chars() {
         if char == '\n' {
             x = blocked_lines
@@ -444,6 +473,17 @@ pub fn get_element_type(name: &str) -> Option<&'static ElementType> {
     }
     ELEMENT_TYPES.iter().find(|f| f.name == name)
 }
+/// Like [disrespect_whitespace], but keeps newlines. Used for `white-space: pre-line`
+fn disrespect_whitespace_keep_newlines(text: &str, allow_leading: bool) -> String {
+    let mut new = String::new();
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            new.push('\n');
+        }
+        new += disrespect_whitespace(line, allow_leading && index == 0).trim_end();
+    }
+    new
+}
 /// Removes repeated whitespace and newlines
 fn disrespect_whitespace(text: &str, allow_leading: bool) -> String {
     let text = text.replace("\n", "").replace("\r", "");
@@ -462,7 +502,7 @@ fn disrespect_whitespace(text: &str, allow_leading: bool) -> String {
     }
     new
 }
-fn is_whitespace(text: &str) -> bool {
+pub fn is_whitespace(text: &str) -> bool {
     text.chars().all(|c| c.is_whitespace())
 }
 fn actualize(
@@ -508,8 +548,14 @@ fn actualize(
         }
     }
 }
+/// Derives the anchor ID of a child from that of its parent and its index among its siblings.
+///
+/// Stays the same across reflows, and across reloads as long as the DOM doesn't change.
+pub fn anchor_id(parent: u64, index: usize) -> u64 {
+    (parent ^ (index as u64 + 1)).wrapping_mul(0x100000001b3)
+}
 fn parse_method(method: &str) -> Option<Method> {
-    match method {
+    match method.to_lowercase().as_str() {
         "post" => Some(Method::POST),
         "get" => Some(Method::GET),
         _ => None,
@@ -528,6 +574,12 @@ pub struct DrawData<'a> {
     pub found_element_y: Option<u16>,
     pub parent_interactable: Option<usize>,
     pub parent_form: Option<usize>,
+    /// Identifies the element drawn with this context by its position in the DOM, see [anchor_id]
+    pub anchor_id: u64,
+    /// Boxes of drawn elements, used to keep the same content in view when the page is drawn again, and by the inspector
+    pub boxes: Vec<ElementBox>,
+    /// Index of the clip of the closest ancestor with `overflow` set
+    pub clip: Option<usize>,
     pub ancestors_target_info: Vec<ElementTargetInfo>,
     /// Its hard to explain what this is, but basically,
     /// when an inline element with a height larger than 1 is drawn,
@@ -542,6 +594,43 @@ pub struct DrawData<'a> {
     /// and had a non-zero width. Used to tell whether a leading whitespace should be allowed for text.
     pub last_was_inline_and_sized: bool,
 }
+impl DrawData<'_> {
+    /// Draws a form control shown as a single line of text, like a checkbox or a select, inline
+    fn draw_inline_control(
+        &mut self,
+        box_index: usize,
+        text: String,
+        style: ElementDrawContext,
+        interactable: Option<usize>,
+    ) {
+        let width = text.width() as u16 * EM;
+        self.draw_calls.push(DrawCall::Text(
+            self.x,
+            self.y,
+            text,
+            style,
+            self.parent_width,
+            interactable,
+            self.clip,
+        ));
+        self.boxes[box_index].width = ActualMeasurement::Pixels(width);
+        self.boxes[box_index].height = ActualMeasurement::Pixels(LH);
+        self.x += width;
+        self.content_width = self.content_width.max(self.x);
+        self.content_height = self.content_height.max(self.y + LH);
+        self.last_was_inline_and_sized = true;
+    }
+}
+/// Where an element was laid out, in pixels
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ElementBox {
+    /// Anchor ID of the element, see [anchor_id]
+    pub id: u64,
+    pub x: u16,
+    pub y: u16,
+    pub width: ActualMeasurement,
+    pub height: ActualMeasurement,
+}
 #[derive(Clone)]
 pub struct Element {
     pub ty: &'static ElementType,
@@ -605,6 +694,39 @@ impl Element {
             self.ty.name, children_text, self.ty.name
         )
     }
+    /// Registers the control of a form field as interactable, returning its index
+    fn register_field(
+        &self,
+        global_ctx: &mut GlobalDrawContext,
+        interactable: Interactable,
+    ) -> usize {
+        global_ctx.interactables.push(interactable);
+        let index = global_ctx.interactables.len() - 1;
+        if self.get_attribute("autofocus").is_some() {
+            global_ctx.autofocus.get_or_insert(index);
+        }
+        index
+    }
+    /// What style targets are matched against for this element
+    pub fn target_info(
+        &self,
+        base_url: &Option<Url>,
+        visited: &HashSet<String>,
+    ) -> ElementTargetInfo {
+        let visited = self.ty.name == "a"
+            && self.get_attribute("href").is_some_and(|href| {
+                Url::options()
+                    .base_url(base_url.as_ref())
+                    .parse(href)
+                    .is_ok_and(|url| visited.contains(url.as_str()))
+            });
+        ElementTargetInfo {
+            type_name: self.ty.name,
+            id: self.get_attribute("id").cloned(),
+            classes: self.classes.clone(),
+            visited,
+        }
+    }
     pub fn get_active_style(
         &self,
         global_ctx: &GlobalDrawContext,
@@ -665,11 +787,7 @@ impl Element {
         draw_data: &mut DrawData,
     ) {
         let mut draw_data_ancestor_info = draw_data.ancestors_target_info.clone();
-        draw_data_ancestor_info.push(ElementTargetInfo {
-            type_name: self.ty.name,
-            id: self.get_attribute("id").cloned(),
-            classes: self.classes.clone(),
-        });
+        draw_data_ancestor_info.push(self.target_info(global_ctx.base_url, &global_ctx.visited));
 
         if let Some(id) = draw_data.find_element
             && let Some(self_id) = self.get_attribute("id")
@@ -687,7 +805,9 @@ impl Element {
             style
         };
 
-        if self.ty.stops_parsing || matches!(style.display, Specified(Display::None)) {
+        if (self.ty.stops_parsing && self.ty.name != "textarea")
+            || matches!(style.display, Specified(Display::None))
+        {
             return;
         }
 
@@ -702,15 +822,25 @@ impl Element {
             draw_data.y += draw_data.last_item_height.max(LH);
             draw_data.x = 0;
         }
+        let box_index = draw_data.boxes.len();
+        draw_data.boxes.push(ElementBox {
+            id: draw_data.anchor_id,
+            x: draw_data.x,
+            y: draw_data.y,
+            ..Default::default()
+        });
         let mut self_interactable = draw_data.parent_interactable;
         let mut self_form = draw_data.parent_form;
 
         if self.ty.name == "node" {
+            let white_space = style.white_space.unwrap_or(WhiteSpace::Normal);
             if let Some(text) = &self.text
-                && (!is_whitespace(text) || style.respect_whitespace)
+                && (!is_whitespace(text) || white_space.preserves_spaces())
             {
-                let mut text = if style.respect_whitespace {
+                let mut text = if white_space.preserves_spaces() {
                     text.clone()
+                } else if white_space.preserves_newlines() {
+                    disrespect_whitespace_keep_newlines(text, draw_data.last_was_inline_and_sized)
                 } else {
                     disrespect_whitespace(text, draw_data.last_was_inline_and_sized)
                 };
@@ -726,7 +856,11 @@ impl Element {
 
                 let mut lines = fit_text_in_width(
                     &text,
-                    draw_data.parent_width,
+                    if white_space.wraps() {
+                        draw_data.parent_width.get_pixels()
+                    } else {
+                        None
+                    },
                     draw_data.x,
                     &draw_data.blocked_lines,
                     draw_data.y,
@@ -747,6 +881,7 @@ impl Element {
                         style,
                         draw_data.parent_width,
                         draw_data.parent_interactable,
+                        draw_data.clip,
                     ));
                     draw_data.x += len * EM;
                     draw_data.content_width = draw_data.content_width.max(draw_data.x);
@@ -761,6 +896,16 @@ impl Element {
                 }
                 draw_data.content_height = draw_data.content_height.max(draw_data.y + LH);
 
+                let element_box = &mut draw_data.boxes[box_index];
+                if draw_data.y == element_box.y {
+                    element_box.width = ActualMeasurement::Pixels(draw_data.x - element_box.x);
+                } else {
+                    // text that wraps is boxed by the lines it covers
+                    element_box.x = 0;
+                    element_box.width = draw_data.parent_width;
+                }
+                element_box.height = ActualMeasurement::Pixels(draw_data.y - element_box.y + LH);
+
                 draw_data.last_was_inline_and_sized = !is_display_block && any_text;
             }
             return;
@@ -772,9 +917,7 @@ impl Element {
             global_ctx
                 .interactables
                 .push(Interactable::Link(link.clone()));
-        } else if self.ty.name == "form"
-            && let Some(action) = self.get_attribute("action")
-        {
+        } else if self.ty.name == "form" {
             let method =
                 if let Some(Some(method)) = self.get_attribute("method").map(|f| parse_method(f)) {
                     method
@@ -784,8 +927,11 @@ impl Element {
             // register link as interactable element
             self_form = Some(global_ctx.forms.len());
             global_ctx.forms.push(Form {
-                action: action.clone(),
+                // without an action, the form is sent to the page itself
+                action: self.get_attribute("action").cloned().unwrap_or_default(),
                 method,
+                enctype: self.get_attribute("enctype").cloned().unwrap_or_default(),
+                novalidate: self.get_attribute("novalidate").is_some(),
                 ..Default::default()
             });
         }
@@ -803,6 +949,24 @@ impl Element {
             &mut global_ctx.unknown_sized_elements,
             false,
         );
+        // max-width is only used once it's known in pixels
+        let max_width = match style.max_width {
+            Specified(max_width @ (Measurement::Pixels(_) | Measurement::PercentWidth(_))) => {
+                actualize(
+                    max_width,
+                    draw_data,
+                    &mut global_ctx.unknown_sized_elements,
+                    false,
+                )
+                .get_pixels()
+            }
+            _ => None,
+        };
+        if let Some(max_width) = max_width
+            && actual_width.get_pixels().is_some_and(|f| f > max_width)
+        {
+            actual_width = ActualMeasurement::Pixels(max_width);
+        }
 
         if self.ty.name == "img" {
             let mut height_pixels = 0;
@@ -858,6 +1022,7 @@ impl Element {
                     ActualMeasurement::Pixels(width_pixels),
                     ActualMeasurement::Pixels(height_pixels),
                     url,
+                    draw_data.clip,
                 ));
                 draw_data.content_width = draw_data.content_width.max(width_pixels);
                 draw_data.content_height = draw_data.content_height.max(height_pixels);
@@ -874,6 +1039,8 @@ impl Element {
                 }
             }
 
+            draw_data.boxes[box_index].width = ActualMeasurement::Pixels(width_pixels);
+            draw_data.boxes[box_index].height = ActualMeasurement::Pixels(height_pixels);
             draw_data.last_was_inline_and_sized = false;
             draw_data.x += width_pixels;
             if is_display_block && height_pixels > 0 {
@@ -881,9 +1048,12 @@ impl Element {
                 draw_data.x = 0;
             }
             return;
-        } else if (self.ty.name == "input" || self.ty.name == "button")
-            && let Some(ty) = self.get_attribute("type")
-        {
+        } else if self.ty.name == "input" || self.ty.name == "button" {
+            let ty = match self.get_attribute("type") {
+                Some(ty) => ty.to_lowercase(),
+                None if self.ty.name == "button" => String::from("submit"),
+                None => String::from("text"),
+            };
             if let ActualMeasurement::Waiting(wi) = actual_width {
                 actual_width = ActualMeasurement::Pixels(0);
                 global_ctx.unknown_sized_elements[wi] = Some(actual_width);
@@ -892,6 +1062,57 @@ impl Element {
                 actual_height = ActualMeasurement::Pixels(0);
                 global_ctx.unknown_sized_elements[hi] = Some(actual_height);
             }
+            let disabled = self.get_attribute("disabled").is_some();
+            if self.ty.name == "input"
+                && let Some(form) = self_form
+            {
+                let name = self.get_attribute("name").cloned().unwrap_or_default();
+                match ty.as_str() {
+                    "hidden" => {
+                        if !name.is_empty() && !disabled {
+                            let value = self.get_attribute("value").cloned().unwrap_or_default();
+                            global_ctx.forms[form].add_text_field(name, value, None);
+                        }
+                        return;
+                    }
+                    "checkbox" | "radio" => {
+                        let checkable = Checkable {
+                            name,
+                            value: self
+                                .get_attribute("value")
+                                .cloned()
+                                .unwrap_or(String::from("on")),
+                            radio: ty == "radio",
+                            checked: self.get_attribute("checked").is_some(),
+                            required: self.get_attribute("required").is_some(),
+                        };
+                        let marker = checkable.marker();
+                        // disabled inputs are drawn, but aren't part of the form
+                        let interactable = (!disabled).then(|| {
+                            let index = global_ctx.forms[form].add_checkable(checkable);
+                            self.register_field(global_ctx, Interactable::InputCheck(form, index))
+                        });
+                        draw_data.draw_inline_control(
+                            box_index,
+                            String::from(marker),
+                            style,
+                            interactable,
+                        );
+                        return;
+                    }
+                    "file" => {
+                        let input = FileInput::new(self);
+                        let text = input.display_text();
+                        let interactable = (!disabled).then(|| {
+                            let index = global_ctx.forms[form].add_file_input(input);
+                            self.register_field(global_ctx, Interactable::InputFile(form, index))
+                        });
+                        draw_data.draw_inline_control(box_index, text, style, interactable);
+                        return;
+                    }
+                    _ => {}
+                }
+            }
             if (self.ty.name != "button" || ty == "submit")
                 && let Some(form) = self_form
             {
@@ -903,15 +1124,32 @@ impl Element {
                         let Some(name) = self.get_attribute("name") else {
                             return;
                         };
-                        self_interactable = Some(global_ctx.interactables.len());
-                        global_ctx.interactables.push(Interactable::InputText(
-                            form,
-                            name.clone(),
-                            (width / EM).saturating_sub(2),
-                            None,
-                        ));
+                        let constraints = Constraints::new(self, &ty);
+                        let value = self.get_attribute("value").map(|f| {
+                            if constraints.masked() {
+                                mask(f)
+                            } else {
+                                f.clone()
+                            }
+                        });
+                        if !disabled {
+                            let field = global_ctx.forms[form].add_text_field(
+                                name.clone(),
+                                self.get_attribute("value").cloned().unwrap_or_default(),
+                                Some(constraints),
+                            );
+                            self_interactable = Some(self.register_field(
+                                global_ctx,
+                                Interactable::InputText(
+                                    form,
+                                    field,
+                                    (width / EM).saturating_sub(2),
+                                    None,
+                                ),
+                            ));
+                        }
                         Some(
-                            self.get_attribute("value").cloned().unwrap_or(
+                            value.unwrap_or(
                                 self.get_attribute("placeholder")
                                     .cloned()
                                     .unwrap_or(String::from("Input...")),
@@ -919,12 +1157,34 @@ impl Element {
                         )
                     }
                     "submit" => {
-                        self_interactable = Some(global_ctx.interactables.len());
-                        global_ctx
-                            .interactables
-                            .push(Interactable::InputSubmit(form));
+                        let value = self.get_attribute("value").cloned();
+                        if !disabled {
+                            let index = global_ctx.forms[form].add_submitter(Submitter {
+                                name: self.get_attribute("name").cloned().unwrap_or_default(),
+                                value: value.clone().unwrap_or_default(),
+                                action: self.get_attribute("formaction").cloned(),
+                                method: self
+                                    .get_attribute("formmethod")
+                                    .and_then(|f| parse_method(f)),
+                                enctype: self.get_attribute("formenctype").cloned(),
+                                novalidate: self.get_attribute("formnovalidate").is_some(),
+                            });
+                            self_interactable = Some(self.register_field(
+                                global_ctx,
+                                Interactable::InputSubmit(form, index),
+                            ));
+                        }
 
-                        if let Some(value) = self.get_attribute("value").cloned()
+                        // buttons are labeled by their content
+                        let label = if self.ty.name == "button" {
+                            let text = inner_text(self);
+                            text.split_whitespace().collect::<Vec<&str>>().join(" ")
+                        } else {
+                            String::new()
+                        };
+                        if !label.is_empty() {
+                            Some(label)
+                        } else if let Some(value) = value
                             && !value.is_empty()
                         {
                             Some(value)
@@ -941,9 +1201,12 @@ impl Element {
                         draw_data.y,
                         ActualMeasurement::Pixels(width),
                         ActualMeasurement::Pixels(height),
-                        self_interactable.unwrap(),
+                        if disabled { None } else { self_interactable },
                         text,
+                        draw_data.clip,
                     ));
+                    draw_data.boxes[box_index].width = ActualMeasurement::Pixels(width);
+                    draw_data.boxes[box_index].height = ActualMeasurement::Pixels(height);
                     draw_data.last_was_inline_and_sized = false;
                     draw_data.x += width;
                     if is_display_block
@@ -959,6 +1222,91 @@ impl Element {
                 }
             }
             return;
+        } else if self.ty.name == "select" {
+            if let ActualMeasurement::Waiting(wi) = actual_width {
+                global_ctx.unknown_sized_elements[wi] = Some(ActualMeasurement::Pixels(0));
+            }
+            if let ActualMeasurement::Waiting(hi) = actual_height {
+                global_ctx.unknown_sized_elements[hi] = Some(ActualMeasurement::Pixels(0));
+            }
+            let select = Select::new(self);
+            let text = select.display_text();
+            // selects outside of forms, or disabled ones, are drawn but can't be used
+            let disabled = self.get_attribute("disabled").is_some();
+            let interactable = self_form.filter(|_| !disabled).map(|form| {
+                let index = global_ctx.forms[form].add_select(select);
+                self.register_field(global_ctx, Interactable::InputSelect(form, index))
+            });
+            draw_data.draw_inline_control(box_index, text, style, interactable);
+            return;
+        } else if self.ty.name == "textarea" {
+            if let ActualMeasurement::Waiting(wi) = actual_width {
+                actual_width = ActualMeasurement::Pixels(0);
+                global_ctx.unknown_sized_elements[wi] = Some(actual_width);
+            }
+            if let ActualMeasurement::Waiting(hi) = actual_height {
+                actual_height = ActualMeasurement::Pixels(0);
+                global_ctx.unknown_sized_elements[hi] = Some(actual_height);
+            }
+            let attribute = |name: &str, default: u16| {
+                self.get_attribute(name)
+                    .and_then(|f| f.parse::<u16>().ok())
+                    .filter(|f| *f > 0)
+                    .unwrap_or(default)
+            };
+            // the box is sized by the cols and rows attributes, unless css sets a size
+            let width = match actual_width.get_pixels_lossy() {
+                0 => (attribute("cols", 20) + 3) * EM,
+                width => width.max(4 * EM),
+            };
+            let height = match actual_height.get_pixels_lossy() {
+                0 => (attribute("rows", 2) + 2) * LH,
+                height => height.max(3 * LH),
+            };
+            // a newline right after the start tag isn't part of the text
+            let text = parse_special(self.text.as_deref().unwrap_or_default());
+            let text = text
+                .strip_prefix("\r\n")
+                .or(text.strip_prefix('\n'))
+                .unwrap_or(&text)
+                .to_string();
+            let size = (
+                (width / EM).saturating_sub(3),
+                (height / LH).saturating_sub(2),
+            );
+            // text areas outside of forms, without a name or disabled are drawn, but can't be used
+            let field = self_form
+                .zip(self.get_attribute("name"))
+                .filter(|_| self.get_attribute("disabled").is_none());
+            let (interactable, text) = if let Some((form, name)) = field {
+                let field = global_ctx.forms[form].add_text_field(
+                    name.clone(),
+                    text.clone(),
+                    Some(Constraints::new(self, "textarea")),
+                );
+                let interactable = Interactable::InputTextArea(form, field, size, None);
+                (Some(self.register_field(global_ctx, interactable)), text)
+            } else {
+                // these aren't redrawn with a value, so the rows are split now
+                (None, wrap_text(&text, size.0).join("\n"))
+            };
+            draw_data.content_width = draw_data.content_width.max(draw_data.x + width);
+            draw_data.content_height = draw_data.content_height.max(draw_data.y + height);
+            draw_data.draw_calls.push(DrawCall::DrawInput(
+                draw_data.x,
+                draw_data.y,
+                ActualMeasurement::Pixels(width),
+                ActualMeasurement::Pixels(height),
+                interactable,
+                text,
+                draw_data.clip,
+            ));
+            draw_data.boxes[box_index].width = ActualMeasurement::Pixels(width);
+            draw_data.boxes[box_index].height = ActualMeasurement::Pixels(height);
+            draw_data.last_was_inline_and_sized = false;
+            draw_data.x += width;
+            draw_data.last_item_height = height;
+            return;
         }
 
         draw_data.content_width = draw_data.content_width.max(actual_width.get_pixels_lossy());
@@ -966,7 +1314,7 @@ impl Element {
             .content_height
             .max(actual_height.get_pixels_lossy());
 
-        let draw_data_parent_width = if let Some(pixels) = draw_data.parent_width.get_pixels()
+        let mut draw_data_parent_width = if let Some(pixels) = draw_data.parent_width.get_pixels()
             && pixels != 0
             && actual_width.get_pixels().is_none_or(|p| p > pixels)
         {
@@ -974,6 +1322,30 @@ impl Element {
         } else {
             actual_width
         };
+        // content is laid out within max-width, even if the width fits the content
+        if let Some(max_width) = max_width
+            && draw_data_parent_width
+                .get_pixels()
+                .is_none_or(|f| f > max_width)
+        {
+            draw_data_parent_width = ActualMeasurement::Pixels(max_width);
+        }
+
+        // register clip if overflow is set. the root elements are left alone,
+        // since clipping them would cut off the page itself
+        let overflow = style.overflow.unwrap_or(Overflow::Visible);
+        let self_clip = if overflow != Overflow::Visible && !is_body && self.ty.name != "html" {
+            global_ctx.clips.push(ClipInfo {
+                parent: draw_data.clip,
+                scrollable: overflow == Overflow::Auto,
+                ellipsis: matches!(style.text_overflow, Specified(TextOverflow::Ellipsis)),
+                content_width: 0,
+            });
+            Some(global_ctx.clips.len() - 1)
+        } else {
+            None
+        };
+
         let mut child_data = DrawData {
             parent_width: draw_data_parent_width,
             parent_height: actual_height,
@@ -982,6 +1354,7 @@ impl Element {
             last_was_inline_and_sized: draw_data.last_was_inline_and_sized,
             parent_form: self_form,
             find_element: draw_data.find_element,
+            clip: self_clip.or(draw_data.clip),
             ..Default::default()
         };
         if self.ty.name == "li"
@@ -1001,18 +1374,23 @@ impl Element {
                 style,
                 child_data.parent_width,
                 None,
+                child_data.clip,
             ));
             child_data.x += width;
         }
         let old_draw_data = draw_data.clone();
-        for child in self.children.iter() {
+        for (index, child) in self.children.iter().enumerate() {
+            child_data.anchor_id = anchor_id(draw_data.anchor_id, index);
             child.draw(style, global_ctx, &mut child_data);
-            draw_data.content_width = draw_data
-                .content_width
-                .max(draw_data.x + child_data.content_width);
-            draw_data.content_height = draw_data
-                .content_height
-                .max(draw_data.y.saturating_add(child_data.content_height));
+            // content of clipping elements doesn't take up space outside of them
+            if self_clip.is_none() {
+                draw_data.content_width = draw_data
+                    .content_width
+                    .max(draw_data.x + child_data.content_width);
+                draw_data.content_height = draw_data
+                    .content_height
+                    .max(draw_data.y.saturating_add(child_data.content_height));
+            }
             draw_data.found_element_y = draw_data
                 .found_element_y
                 .or(child_data.found_element_y.map(|f| f + draw_data.y));
@@ -1044,11 +1422,23 @@ impl Element {
                     *x += draw_data.x;
                     *y += draw_data.y;
                 }
+                DrawCall::Clip(x, y, ..) => {
+                    *x += draw_data.x;
+                    *y += draw_data.y;
+                }
                 DrawCall::ClearColor(_) => {}
             }
         }
+        draw_data
+            .boxes
+            .extend(child_data.boxes.iter().map(|element_box| ElementBox {
+                x: element_box.x.saturating_add(draw_data.x),
+                y: element_box.y.saturating_add(draw_data.y),
+                ..*element_box
+            }));
 
         // reactualize width and height with content size known
+        let unresolved_width = actual_width;
         if let ActualMeasurement::Waiting(index) = actual_width {
             actual_width = actualize(
                 style.width.unwrap_or(Measurement::Pixels(0)),
@@ -1056,6 +1446,11 @@ impl Element {
                 &mut global_ctx.unknown_sized_elements,
                 true,
             );
+            if let Some(max_width) = max_width
+                && actual_width.get_pixels().is_some_and(|f| f > max_width)
+            {
+                actual_width = ActualMeasurement::Pixels(max_width);
+            }
             global_ctx.unknown_sized_elements[index] = Some(actual_width);
         }
         if let ActualMeasurement::Waiting(index) = actual_height {
@@ -1067,7 +1462,34 @@ impl Element {
             );
             global_ctx.unknown_sized_elements[index] = Some(actual_height);
         }
-        if actual_height.get_pixels_lossy() < child_data.content_height {
+        if let Some(clip) = self_clip {
+            // clipping elements can't grow wider than their parent
+            if let Some(parent_width) = draw_data.parent_width.get_pixels()
+                && parent_width != 0
+                && actual_width.get_pixels_lossy() > parent_width
+            {
+                actual_width = ActualMeasurement::Pixels(parent_width);
+                if let ActualMeasurement::Waiting(index) = unresolved_width {
+                    global_ctx.unknown_sized_elements[index] = Some(actual_width);
+                }
+            }
+            // only grow to fit content if the height wasn't specified
+            if !matches!(
+                style.height,
+                Specified(Measurement::Pixels(_) | Measurement::PercentHeight(_))
+            ) && actual_height.get_pixels_lossy() < child_data.content_height
+            {
+                actual_height = ActualMeasurement::Pixels(child_data.content_height)
+            }
+            global_ctx.clips[clip].content_width = child_data.content_width;
+            draw_data.draw_calls.push(DrawCall::Clip(
+                draw_data.x,
+                draw_data.y,
+                actual_width,
+                actual_height,
+                clip,
+            ));
+        } else if actual_height.get_pixels_lossy() < child_data.content_height {
             actual_height = ActualMeasurement::Pixels(child_data.content_height)
         }
 
@@ -1081,9 +1503,13 @@ impl Element {
                 actual_width,
                 actual_height,
                 color,
+                draw_data.clip,
             ));
         }
 
+        draw_data.boxes[box_index].width = actual_width;
+        draw_data.boxes[box_index].height = actual_height;
+
         let width = actual_width.get_pixels_lossy();
         draw_data.content_width = draw_data.content_width.max(width);
         let height = actual_height.get_pixels_lossy();
@@ -0,0 +1,479 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    DEFAULT_DRAW_CTX, ElementDrawContext, NonInheritedField::*, Theme, buffer::Buffer,
+    element::Element, submission::FormValue, utils::truncate_to_width,
+};
+
+/// Width of the text showing the chosen files, so the control's size doesn't change
+const FILE_LABEL_WIDTH: usize = 20;
+
+/// The MIME type of a file, from its extension
+pub fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|f| f.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// State of an `<input type="file">` in a form
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileInput {
+    pub name: String,
+    pub multiple: bool,
+    pub required: bool,
+    /// File extensions and MIME types from `accept`, like `.pdf` or `image/*`
+    pub accept: Vec<String>,
+    pub files: Vec<PathBuf>,
+}
+impl FileInput {
+    pub fn new(element: &Element) -> Self {
+        let accept = element
+            .get_attribute("accept")
+            .map(|f| {
+                f.split(',')
+                    .map(|f| f.trim().to_lowercase())
+                    .filter(|f| !f.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            name: element.get_attribute("name").cloned().unwrap_or_default(),
+            multiple: element.get_attribute("multiple").is_some(),
+            required: element.get_attribute("required").is_some(),
+            accept,
+            files: Vec::new(),
+        }
+    }
+    /// Whether a file matches `accept`
+    pub fn accepts(&self, path: &Path) -> bool {
+        if self.accept.is_empty() {
+            return true;
+        }
+        let name = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mime = mime_type(path);
+        self.accept.iter().any(|f| {
+            if f.starts_with('.') {
+                name.ends_with(f.as_str())
+            } else if let Some(kind) = f.strip_suffix("/*") {
+                mime.split('/').next() == Some(kind)
+            } else {
+                mime == f
+            }
+        })
+    }
+    /// What the control shows, padded so its size doesn't change
+    pub fn display_text(&self) -> String {
+        let text = match self.files.as_slice() {
+            [] => String::from("No file chosen"),
+            [file] => file
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            files => format!("{} files", files.len()),
+        };
+        let text = truncate_to_width(&text, FILE_LABEL_WIDTH);
+        format!(
+            "[Browse…] {text}{}",
+            " ".repeat(FILE_LABEL_WIDTH - text.width())
+        )
+    }
+    pub fn is_missing(&self) -> bool {
+        self.required && self.files.is_empty()
+    }
+    /// The names and files this input submits. Without any chosen, an empty file is sent
+    pub fn data(&self) -> Vec<(String, FormValue)> {
+        if self.name.is_empty() {
+            return Vec::new();
+        }
+        if self.files.is_empty() {
+            return vec![(self.name.clone(), FormValue::File(None))];
+        }
+        self.files
+            .iter()
+            .map(|f| (self.name.clone(), FormValue::File(Some(f.clone()))))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PickerEntry {
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+}
+
+pub enum FilePickerInput {
+    Pending,
+    Cancel,
+    Choose(Vec<PathBuf>),
+}
+
+/// A popup for browsing directories and choosing the files of a file input
+pub struct FilePicker {
+    /// Identifier of the page the input is on
+    pub page_id: usize,
+    pub form: usize,
+    pub input: usize,
+    input_state: FileInput,
+    dir: PathBuf,
+    /// Everything in the directory, directories first
+    entries: Vec<PickerEntry>,
+    /// Only entries containing this are listed
+    filter: String,
+    /// Whether files that don't match `accept` are listed
+    show_all: bool,
+    /// Files marked to be chosen, for inputs accepting multiple
+    marked: Vec<PathBuf>,
+    /// Index of the visible row under the cursor
+    highlighted: usize,
+    /// First row in view
+    scroll: usize,
+    /// Where the rows were last drawn: X, Y, width and visible rows
+    area: (u16, u16, u16, u16),
+    error: Option<String>,
+}
+
+impl FilePicker {
+    pub fn new(page_id: usize, form: usize, index: usize, input: &FileInput) -> Self {
+        // start where the last file was chosen from
+        let dir = input
+            .files
+            .first()
+            .and_then(|f| f.parent())
+            .map(Path::to_path_buf)
+            .or(std::env::current_dir().ok())
+            .unwrap_or(PathBuf::from("/"));
+        let mut picker = Self {
+            page_id,
+            form,
+            input: index,
+            input_state: input.clone(),
+            dir: PathBuf::new(),
+            entries: Vec::new(),
+            filter: String::new(),
+            show_all: false,
+            marked: input.files.clone(),
+            highlighted: 0,
+            scroll: 0,
+            area: (0, 0, 0, 0),
+            error: None,
+        };
+        picker.open_dir(dir);
+        picker
+    }
+    fn open_dir(&mut self, dir: PathBuf) {
+        let read = match std::fs::read_dir(&dir) {
+            Ok(read) => read,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+        let mut entries: Vec<PickerEntry> = read
+            .flatten()
+            .map(|f| {
+                let path = f.path();
+                PickerEntry {
+                    name: f.file_name().to_string_lossy().to_string(),
+                    is_dir: path.is_dir(),
+                    path,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then(a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        self.dir = dir;
+        self.entries = entries;
+        self.filter.clear();
+        self.highlighted = 0;
+        self.scroll = 0;
+        self.error = None;
+    }
+    /// The rows listed: the parent directory, then the entries passing the filters
+    fn rows(&self) -> Vec<PickerEntry> {
+        let mut rows = Vec::new();
+        if let Some(parent) = self.dir.parent() {
+            rows.push(PickerEntry {
+                name: String::from(".."),
+                path: parent.to_path_buf(),
+                is_dir: true,
+            });
+        }
+        let filter = self.filter.to_lowercase();
+        rows.extend(
+            self.entries
+                .iter()
+                .filter(|f| f.name.to_lowercase().contains(&filter))
+                .filter(|f| f.is_dir || self.show_all || self.input_state.accepts(&f.path))
+                .cloned(),
+        );
+        rows
+    }
+    fn move_highlight(&mut self, amount: isize) {
+        let count = self.rows().len();
+        self.highlighted = self
+            .highlighted
+            .saturating_add_signed(amount)
+            .min(count.saturating_sub(1));
+    }
+    /// Opens the highlighted directory, or chooses the highlighted file
+    fn activate(&mut self) -> FilePickerInput {
+        let Some(entry) = self.rows().get(self.highlighted).cloned() else {
+            return FilePickerInput::Pending;
+        };
+        if entry.is_dir {
+            self.open_dir(entry.path);
+            FilePickerInput::Pending
+        } else if self.input_state.multiple && !self.marked.is_empty() {
+            FilePickerInput::Choose(self.marked.clone())
+        } else {
+            FilePickerInput::Choose(vec![entry.path])
+        }
+    }
+    /// Marks or unmarks the highlighted file
+    fn toggle_mark(&mut self) {
+        if let Some(entry) = self.rows().get(self.highlighted)
+            && !entry.is_dir
+        {
+            if let Some(index) = self.marked.iter().position(|f| *f == entry.path) {
+                self.marked.remove(index);
+            } else {
+                self.marked.push(entry.path.clone());
+            }
+        }
+    }
+    pub fn on_key(&mut self, key: KeyEvent) -> FilePickerInput {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return FilePickerInput::Cancel,
+            KeyCode::Char('c') if control => return FilePickerInput::Cancel,
+            KeyCode::Char('a') if control => {
+                self.show_all ^= true;
+                self.highlighted = 0;
+            }
+            KeyCode::Up => self.move_highlight(-1),
+            KeyCode::Down => self.move_highlight(1),
+            KeyCode::PageUp => self.move_highlight(-10),
+            KeyCode::PageDown => self.move_highlight(10),
+            KeyCode::Home => self.highlighted = 0,
+            KeyCode::End => self.move_highlight(isize::MAX),
+            KeyCode::Enter | KeyCode::Right => return self.activate(),
+            KeyCode::Tab if self.input_state.multiple => {
+                self.toggle_mark();
+                self.move_highlight(1);
+            }
+            KeyCode::Left => {
+                if let Some(parent) = self.dir.parent() {
+                    self.open_dir(parent.to_path_buf());
+                }
+            }
+            KeyCode::Backspace => {
+                // without a filter to erase, go up a directory
+                if self.filter.pop().is_none()
+                    && let Some(parent) = self.dir.parent()
+                {
+                    self.open_dir(parent.to_path_buf());
+                }
+                self.highlighted = 0;
+            }
+            KeyCode::Char(char) if !control => {
+                self.filter.push(char);
+                // highlight the first match rather than the parent directory
+                self.highlighted = usize::from(self.dir.parent().is_some());
+                self.move_highlight(0);
+            }
+            _ => {}
+        }
+        FilePickerInput::Pending
+    }
+    /// Handles a click, opening or choosing the row under it. Clicking outside the picker closes it
+    pub fn on_click(&mut self, x: u16, y: u16) -> FilePickerInput {
+        let (area_x, area_y, width, height) = self.area;
+        if !(area_x..area_x + width).contains(&x) {
+            return FilePickerInput::Cancel;
+        }
+        // the title and filter rows are above the list, and the hint row below it
+        if y + 2 < area_y || y > area_y + height {
+            return FilePickerInput::Cancel;
+        }
+        if !(area_y..area_y + height).contains(&y) {
+            return FilePickerInput::Pending;
+        }
+        let row = self.scroll + (y - area_y) as usize;
+        let Some(entry) = self.rows().get(row).cloned() else {
+            return FilePickerInput::Pending;
+        };
+        self.highlighted = row;
+        if !entry.is_dir && self.input_state.multiple {
+            self.toggle_mark();
+            return FilePickerInput::Pending;
+        }
+        self.activate()
+    }
+    /// Draws the picker in the middle of the screen
+    pub fn draw(&mut self, buffer: &mut Buffer, screen_size: (u16, u16), theme: &Theme) {
+        let (screen_width, screen_height) = screen_size;
+        let width = screen_width.saturating_sub(4).min(64);
+        // title, filter and hint rows
+        let height = screen_height.saturating_sub(8).clamp(1, 20);
+        let x = (screen_width - width) / 2;
+        let y = (screen_height.saturating_sub(height + 3) / 2).max(3);
+        self.area = (x, y + 2, width, height);
+
+        let rows = self.rows();
+        if self.highlighted < self.scroll {
+            self.scroll = self.highlighted;
+        } else if self.highlighted >= self.scroll + height as usize {
+            self.scroll = self.highlighted + 1 - height as usize;
+        }
+
+        let ctx = |foreground, background, bold| ElementDrawContext {
+            foreground_color: Some(foreground),
+            background_color: Specified(background),
+            bold,
+            ..DEFAULT_DRAW_CTX
+        };
+        let mut line = |row: u16, text: &str, ctx: &ElementDrawContext| {
+            let text = truncate_to_width(&format!(" {text}"), width as usize);
+            let padding = " ".repeat(width as usize - text.width());
+            buffer.draw_str(x, y + row, &(text + &padding), ctx, None);
+        };
+
+        let title = match &self.error {
+            Some(error) => format!("{} ({error})", self.dir.display()),
+            None => self.dir.display().to_string(),
+        };
+        line(
+            0,
+            &title,
+            &ctx(theme.background_color, theme.interactive_color, true),
+        );
+        let accept = if self.show_all || self.input_state.accept.is_empty() {
+            String::new()
+        } else {
+            format!("  ({})", self.input_state.accept.join(", "))
+        };
+        line(
+            1,
+            &format!("filter: {}{accept}", self.filter),
+            &ctx(theme.text_color, theme.ui_color, false),
+        );
+        for offset in 0..height {
+            let index = self.scroll + offset as usize;
+            let Some(entry) = rows.get(index) else {
+                line(
+                    offset + 2,
+                    "",
+                    &ctx(theme.text_color, theme.ui_color, false),
+                );
+                continue;
+            };
+            let mark = match (self.input_state.multiple, entry.is_dir) {
+                (false, _) => "",
+                (true, true) => "    ",
+                (true, false) if self.marked.contains(&entry.path) => "[x] ",
+                (true, false) => "[ ] ",
+            };
+            let slash = if entry.is_dir { "/" } else { "" };
+            let style = if index == self.highlighted {
+                ctx(theme.background_color, theme.interactive_color, false)
+            } else {
+                ctx(theme.text_color, theme.ui_color, entry.is_dir)
+            };
+            line(offset + 2, &format!("{mark}{}{slash}", entry.name), &style);
+        }
+        let hint = if self.input_state.multiple {
+            "enter: open/choose  tab: mark  ctrl+a: all files  esc: cancel"
+        } else {
+            "enter: open/choose  ctrl+a: all files  esc: cancel"
+        };
+        line(
+            height + 2,
+            hint,
+            &ctx(theme.background_color, theme.ui_color, false),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{
+        file_picker::{FileInput, FilePicker, FilePickerInput},
+        parsing::parse_html,
+    };
+
+    #[test]
+    fn test_file_picker() {
+        let dir = std::env::temp_dir().join(format!("toad-picker-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("photos")).unwrap();
+        for name in ["notes.txt", "cat.png", "dog.JPG"] {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+
+        let page = parse_html(
+            "<html><body><input type=\"file\" name=\"pics\" accept=\"image/*,.txt\" multiple required></body></html>",
+        )
+        .unwrap();
+        let body = &page.root.as_ref().unwrap().children[0];
+        let mut input = FileInput::new(&body.children[0]);
+        assert!(input.accepts(&dir.join("dog.JPG")));
+        assert!(!input.accepts(&dir.join("song.mp3")));
+        assert!(input.is_missing());
+        assert_eq!(input.display_text(), "[Browse…] No file chosen      ");
+
+        input.files.push(dir.join("cat.png"));
+        let mut picker = FilePicker::new(0, 0, 0, &input);
+        // directories come first, after the parent
+        let names: Vec<String> = picker.rows().into_iter().map(|f| f.name).collect();
+        assert_eq!(names, ["..", "photos", "cat.png", "dog.JPG", "notes.txt"]);
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        picker.on_key(key(KeyCode::Char('o')));
+        picker.on_key(key(KeyCode::Char('g')));
+        picker.on_key(key(KeyCode::Tab));
+        let FilePickerInput::Choose(files) = picker.on_key(key(KeyCode::Enter)) else {
+            panic!("files weren't chosen");
+        };
+        assert_eq!(files, [dir.join("cat.png"), dir.join("dog.JPG")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::{DrawCall, consts::*};
+
+/// A match of the find query, in a [DrawCall::Text]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FindMatch {
+    /// X of the text draw call, in pixels
+    pub x: u16,
+    /// Y of the text draw call, in pixels
+    pub y: u16,
+    /// Index of the first character of the match, in the text
+    pub start: usize,
+    /// Length of the match, in characters
+    pub len: usize,
+}
+
+/// State of find-in-page for a webpage
+#[derive(Clone, Default)]
+pub struct FindState {
+    pub query: String,
+    pub case_sensitive: bool,
+    /// Whether the query is a regular expression
+    pub regex: bool,
+    pub matches: Vec<FindMatch>,
+    /// Index of the selected match
+    pub current: usize,
+    /// The compiled query. [None] if the query is empty or an invalid regex
+    matcher: Option<Regex>,
+}
+impl FindState {
+    pub fn new(query: String, case_sensitive: bool, regex: bool) -> Self {
+        let mut state = Self {
+            query,
+            case_sensitive,
+            regex,
+            ..Default::default()
+        };
+        state.compile();
+        state
+    }
+    fn compile(&mut self) {
+        self.matcher = if self.query.is_empty() {
+            None
+        } else {
+            let pattern = if self.regex {
+                self.query.clone()
+            } else {
+                regex::escape(&self.query)
+            };
+            RegexBuilder::new(&pattern)
+                .case_insensitive(!self.case_sensitive)
+                .build()
+                .ok()
+        };
+    }
+    /// Whether the query is a regex that failed to compile
+    pub fn is_invalid(&self) -> bool {
+        !self.query.is_empty() && self.matcher.is_none()
+    }
+    /// Finds all matches in a string, as character index and character length
+    pub fn find_in_text(&self, text: &str) -> Vec<(usize, usize)> {
+        let Some(matcher) = &self.matcher else {
+            return Vec::new();
+        };
+        matcher
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| {
+                let start = text[..m.start()].chars().count();
+                (start, m.as_str().chars().count())
+            })
+            .collect()
+    }
+    /// Searches all text of a page's draw calls, sorting matches from top to bottom
+    pub fn search(&mut self, calls: &[DrawCall]) {
+        self.matches.clear();
+        for call in calls {
+            if let DrawCall::Text(x, y, text, ..) = call {
+                for (start, len) in self.find_in_text(text) {
+                    self.matches.push(FindMatch {
+                        x: *x,
+                        y: *y,
+                        start,
+                        len,
+                    });
+                }
+            }
+        }
+        self.matches.sort_by_key(|m| (m.y, m.x, m.start));
+        self.current = self.current.min(self.matches.len().saturating_sub(1));
+    }
+    /// Selects the first match at or below a row
+    pub fn select_from_row(&mut self, row: u16) {
+        self.current = self
+            .matches
+            .iter()
+            .position(|m| m.y / LH >= row)
+            .unwrap_or_default();
+    }
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = self
+                .current
+                .checked_sub(1)
+                .unwrap_or(self.matches.len() - 1);
+        }
+    }
+    pub fn current_match(&self) -> Option<&FindMatch> {
+        self.matches.get(self.current)
+    }
+    /// Gets all matches in the text of a draw call, as character index, character length, and whether it is the selected match
+    pub fn highlights(&self, x: u16, y: u16, text: &str) -> Vec<(usize, usize, bool)> {
+        let current = self.current_match();
+        self.find_in_text(text)
+            .into_iter()
+            .map(|(start, len)| {
+                let is_current = current.is_some_and(|m| m.x == x && m.y == y && m.start == start);
+                (start, len, is_current)
+            })
+            .collect()
+    }
+    /// Text shown in the top bar, like "2/15"
+    pub fn counter_text(&self) -> String {
+        let flags = format!(
+            "{}{}",
+            if self.case_sensitive { " Aa" } else { "" },
+            if self.regex { " .*" } else { "" }
+        );
+        if self.is_invalid() {
+            format!("invalid{flags}")
+        } else if self.matches.is_empty() {
+            format!("0/0{flags}")
+        } else {
+            format!("{}/{}{flags}", self.current + 1, self.matches.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::find::FindState;
+
+    #[test]
+    fn test_find_in_text() {
+        let state = FindState::new(String::from("toad"), false, false);
+        assert_eq!(
+            state.find_in_text("TOAD and toad, ünd toad"),
+            vec![(0, 4), (9, 4), (19, 4)]
+        );
+        let state = FindState::new(String::from("toad"), true, false);
+        assert_eq!(state.find_in_text("TOAD and toad"), vec![(9, 4)]);
+        let state = FindState::new(String::from("t.ad"), false, true);
+        assert_eq!(state.find_in_text("toad t.ad"), vec![(0, 4), (5, 4)]);
+        let state = FindState::new(String::from("t.ad"), false, false);
+        assert_eq!(state.find_in_text("toad t.ad"), vec![(5, 4)]);
+        let state = FindState::new(String::from("(unclosed"), false, true);
+        assert!(state.is_invalid());
+    }
+}
@@ -0,0 +1,146 @@
+use crossterm::style;
+
+use crate::{DEFAULT_DRAW_CTX, ElementDrawContext, NonInheritedField::*, buffer::Buffer};
+
+/// Characters hint labels are made of. Home row keys, so they are quick to type.
+const HINT_CHARS: &[char] = &['a', 's', 'd', 'f', 'g', 'h', 'j', 'k', 'l'];
+
+static HINT_DRAW_CTX: ElementDrawContext = ElementDrawContext {
+    foreground_color: Some(style::Color::Black),
+    background_color: Specified(style::Color::Rgb {
+        r: 255,
+        g: 214,
+        b: 76,
+    }),
+    bold: true,
+    ..DEFAULT_DRAW_CTX
+};
+static HINT_TYPED_DRAW_CTX: ElementDrawContext = ElementDrawContext {
+    foreground_color: Some(style::Color::Rgb {
+        r: 150,
+        g: 120,
+        b: 30,
+    }),
+    bold: false,
+    ..HINT_DRAW_CTX
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HintAction {
+    Follow,
+    FollowNewTab,
+    CopyUrl,
+}
+
+pub struct Hint {
+    pub label: String,
+    /// Index of the interactable this hint activates
+    pub interactable: usize,
+    pub x: u16,
+    pub y: u16,
+}
+
+/// State of link hint mode, where every visible interactable is labeled, and can be activated by typing its label.
+pub struct HintMode {
+    pub action: HintAction,
+    pub typed: String,
+    pub hints: Vec<Hint>,
+}
+
+pub enum HintInput {
+    /// Keep waiting for more input
+    Pending,
+    /// A hint was fully typed out, activate its interactable
+    Activate(usize),
+    /// No hint matches what was typed
+    Cancel,
+}
+
+/// Generates `count` labels of equal length, such that no label is the prefix of another.
+fn generate_labels(count: usize) -> Vec<String> {
+    let mut length = 1;
+    while HINT_CHARS.len().pow(length) < count {
+        length += 1;
+    }
+    (0..count)
+        .map(|mut index| {
+            let mut label = String::new();
+            for _ in 0..length {
+                label.insert(0, HINT_CHARS[index % HINT_CHARS.len()]);
+                index /= HINT_CHARS.len();
+            }
+            label
+        })
+        .collect()
+}
+
+impl HintMode {
+    /// Creates hints for every interactable visible in the buffer, below `min_y`.
+    pub fn new(action: HintAction, buffer: &Buffer, min_y: u16) -> Self {
+        let positions = buffer.interactable_positions(min_y);
+        let labels = generate_labels(positions.len());
+        let hints = positions
+            .into_iter()
+            .zip(labels)
+            .map(|((interactable, x, y), label)| Hint {
+                label,
+                interactable,
+                x,
+                y,
+            })
+            .collect();
+        Self {
+            action,
+            typed: String::new(),
+            hints,
+        }
+    }
+    pub fn on_char(&mut self, char: char) -> HintInput {
+        self.typed.push(char.to_ascii_lowercase());
+        let mut matching = self
+            .hints
+            .iter()
+            .filter(|f| f.label.starts_with(&self.typed));
+        let Some(first) = matching.next() else {
+            return HintInput::Cancel;
+        };
+        if first.label == self.typed {
+            HintInput::Activate(first.interactable)
+        } else {
+            HintInput::Pending
+        }
+    }
+    pub fn on_backspace(&mut self) {
+        self.typed.pop();
+    }
+    pub fn draw(&self, buffer: &mut Buffer) {
+        for hint in self.hints.iter() {
+            let Some(rest) = hint.label.strip_prefix(&self.typed) else {
+                continue;
+            };
+            buffer.draw_str(hint.x, hint.y, &self.typed, &HINT_TYPED_DRAW_CTX, None);
+            buffer.draw_str(
+                hint.x + self.typed.len() as u16,
+                hint.y,
+                rest,
+                &HINT_DRAW_CTX,
+                None,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hints::generate_labels;
+
+    #[test]
+    fn test_generate_labels() {
+        assert_eq!(generate_labels(3), vec!["a", "s", "d"]);
+        let labels = generate_labels(20);
+        assert_eq!(labels.len(), 20);
+        assert!(labels.iter().all(|f| f.len() == 2));
+        assert_eq!(labels[0], "aa");
+        assert_eq!(labels[9], "sa");
+    }
+}
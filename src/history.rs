@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{consts::*, parsing::*, utils::*};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub title: String,
+    /// Unix timestamp of the latest visit
+    pub last_visit: u64,
+    pub visit_count: u32,
+}
+impl HistoryEntry {
+    /// Stored as visit time, visit count, URL and title separated by tabs
+    pub fn serialize(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\n",
+            self.last_visit,
+            self.visit_count,
+            self.url,
+            self.title.replace(['\t', '\n', '\r'], " ")
+        )
+    }
+    /// How relevant this entry is for suggestions, by how often and how recently it was visited
+    pub fn frecency(&self, now: u64) -> u32 {
+        let age_days = now.saturating_sub(self.last_visit) / 86400;
+        let weight = match age_days {
+            0..4 => 100,
+            4..14 => 70,
+            14..31 => 50,
+            31..90 => 30,
+            _ => 10,
+        };
+        self.visit_count * weight
+    }
+}
+
+/// Pages that have been visited, ordered from oldest to latest visit
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Reads the history file, where each line is either a visit or an already merged entry.
+    ///
+    /// Lines of only a URL, from older versions, are counted as a visit at `now`.
+    pub fn deserialize(text: &str, now: u64) -> Self {
+        let mut history = Self::default();
+        let mut indices = HashMap::new();
+        for line in text.lines().filter(|f| !f.trim().is_empty()) {
+            let mut parts = line.splitn(4, '\t');
+            let entry = match (parts.next(), parts.next(), parts.next()) {
+                (Some(time), Some(count), Some(url)) => {
+                    let (Ok(last_visit), Ok(visit_count)) = (time.parse(), count.parse()) else {
+                        continue;
+                    };
+                    HistoryEntry {
+                        url: url.to_string(),
+                        title: parts.next().unwrap_or_default().to_string(),
+                        last_visit,
+                        visit_count,
+                    }
+                }
+                _ => HistoryEntry {
+                    url: line.trim().to_string(),
+                    title: String::new(),
+                    last_visit: now,
+                    visit_count: 1,
+                },
+            };
+            history.merge(entry, &mut indices);
+        }
+        history.entries.sort_by_key(|f| f.last_visit);
+        history
+    }
+    pub fn serialize(&self) -> String {
+        self.entries.iter().map(HistoryEntry::serialize).collect()
+    }
+    /// Adds the visits of an entry to the existing entry of the same URL, found through `indices`
+    fn merge(&mut self, entry: HistoryEntry, indices: &mut HashMap<String, usize>) {
+        if let Some(existing) = indices.get(&entry.url).map(|f| &mut self.entries[*f]) {
+            existing.visit_count += entry.visit_count;
+            if entry.last_visit >= existing.last_visit {
+                existing.last_visit = entry.last_visit;
+                if !entry.title.is_empty() {
+                    existing.title = entry.title;
+                }
+            }
+        } else {
+            indices.insert(entry.url.clone(), self.entries.len());
+            self.entries.push(entry);
+        }
+    }
+    /// Records a visit, returning the visit so it can be appended to the history file
+    pub fn visit(&mut self, url: &str, title: &str, now: u64) -> HistoryEntry {
+        let visit = HistoryEntry {
+            url: url.to_string(),
+            title: title.trim().to_string(),
+            last_visit: now,
+            visit_count: 1,
+        };
+        if let Some(index) = self.entries.iter().position(|f| f.url == url) {
+            let mut entry = self.entries.remove(index);
+            entry.visit_count += 1;
+            entry.last_visit = now;
+            if !visit.title.is_empty() {
+                entry.title = visit.title.clone();
+            }
+            self.entries.push(entry);
+        } else {
+            self.entries.push(visit.clone());
+        }
+        visit
+    }
+    /// Forgets entries last visited more than [HISTORY_MAX_AGE_DAYS] ago
+    pub fn prune(&mut self, now: u64) {
+        let max_age = HISTORY_MAX_AGE_DAYS * 86400;
+        self.entries
+            .retain(|f| now.saturating_sub(f.last_visit) <= max_age);
+    }
+    /// Forgets entries last visited after `since`
+    pub fn clear_since(&mut self, since: u64) {
+        self.entries.retain(|f| f.last_visit < since);
+    }
+    pub fn visited_urls(&self) -> HashSet<String> {
+        self.entries.iter().map(|f| f.url.clone()).collect()
+    }
+    /// URLs sorted by [HistoryEntry::frecency], most relevant first
+    pub fn ranked_urls(&self, now: u64) -> Vec<String> {
+        let mut entries: Vec<&HistoryEntry> = self.entries.iter().collect();
+        entries.sort_by_key(|f| std::cmp::Reverse(f.frecency(now)));
+        entries.into_iter().map(|f| f.url.clone()).collect()
+    }
+    /// Latest entries first, whose URL or title contains the query
+    pub fn search<'a>(&'a self, query: &str) -> impl Iterator<Item = &'a HistoryEntry> {
+        let query = query.to_lowercase();
+        self.entries.iter().rev().filter(move |f| {
+            f.url.to_lowercase().contains(&query) || f.title.to_lowercase().contains(&query)
+        })
+    }
+    /// Generates the items of toad://history, grouped by day
+    pub fn to_html(&self, query: &str) -> String {
+        let mut html = String::new();
+        let mut last_day = String::new();
+        for entry in self.search(query) {
+            let day = format_date(entry.last_visit);
+            if day != last_day {
+                html += &format!("<h2>{day}</h2>");
+                last_day = day;
+            }
+            let title = if entry.title.is_empty() {
+                &entry.url
+            } else {
+                &entry.title
+            };
+            let visits = if entry.visit_count == 1 {
+                String::new()
+            } else {
+                format!(", {} visits", entry.visit_count)
+            };
+            html += &format!(
+                "<span class=\"time\">{}</span> <a href=\"{}\">{}</a> <span class=\"url\">{}{visits}</span><br>",
+                format_time(entry.last_visit),
+                sanitize(&entry.url),
+                sanitize(title),
+                sanitize(&entry.url),
+            );
+        }
+        if html.is_empty() {
+            html = if query.is_empty() {
+                String::from("<p>no history yet.</p>")
+            } else {
+                format!("<p>nothing matches \"{}\".</p>", sanitize(query))
+            };
+        }
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::History;
+
+    #[test]
+    fn test_history() {
+        let day = 86400;
+        let now = 400 * day;
+        let text = format!(
+            "https://old.example/\n{}\t3\thttps://a.example/\tA\n{}\t1\thttps://b.example/\tB\n{}\t1\thttps://a.example/\tA again\n",
+            now - 40 * day,
+            now - day,
+            now - 2 * day,
+        );
+        let mut history = History::deserialize(&text, now);
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.entries[0].url, "https://a.example/");
+        assert_eq!(history.entries[0].visit_count, 4);
+        assert_eq!(history.entries[0].title, "A again");
+
+        history.visit("https://b.example/", "", now);
+        assert_eq!(history.entries.last().unwrap().title, "B");
+        assert_eq!(
+            history.ranked_urls(now),
+            vec![
+                "https://a.example/",
+                "https://b.example/",
+                "https://old.example/"
+            ]
+        );
+        assert_eq!(history.search("again").count(), 1);
+
+        history.entries[0].last_visit = now - 200 * day;
+        history.prune(now);
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(History::deserialize(&history.serialize(), now), history);
+        history.entries[0].last_visit = now - day;
+        history.clear_since(now - 3600);
+        assert_eq!(history.entries.len(), 1);
+    }
+}
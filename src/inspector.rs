@@ -0,0 +1,622 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crossterm::{
+    event::{KeyCode, KeyEvent},
+    style::Color,
+};
+use reqwest::Url;
+
+use crate::{
+    CachedDraw, DEFAULT_DRAW_CTX, Display, ElementDrawContext, GlobalDrawContext, Measurement,
+    NonInheritedField::{self, *},
+    StyleTarget, StyleTargetType, TextAlignment, TextPrefix, Theme, WhiteSpace, actualize_actual,
+    buffer::Buffer,
+    consts::*,
+    css::MediaThemeSelector,
+    element::{Element, anchor_id, is_whitespace},
+    utils::truncate_to_width,
+};
+
+/// Rows of the panel taken up by the element tree, out of every five
+const TREE_FIFTHS: u16 = 2;
+
+/// A visible row of the element tree
+struct TreeRow {
+    /// Child indices leading from the root to the element
+    path: Vec<usize>,
+    /// Anchor ID of the element, see [anchor_id]
+    id: u64,
+    label: String,
+    /// Whether the element has children that can be shown
+    expandable: bool,
+}
+
+pub enum InspectorInput {
+    /// The key isn't used by the inspector
+    Ignored,
+    /// The selection or tree changed
+    Changed,
+    Close,
+}
+
+/// A rule matching the inspected element, and the properties it sets
+pub struct MatchedRule {
+    pub selector: String,
+    /// Properties and their values, and whether a later rule sets them too
+    pub properties: Vec<(&'static str, String, bool)>,
+}
+
+/// The style of an element, and how it came to be
+pub struct Inspection {
+    pub rules: Vec<MatchedRule>,
+    pub computed: ElementDrawContext,
+}
+
+/// Panel showing the element tree of a page, along with the style and box of the selected element
+pub struct Inspector {
+    /// Root of the inspected page
+    pub root: Arc<Element>,
+    /// Anchor IDs of elements whose children are shown
+    expanded: HashSet<u64>,
+    rows: Vec<TreeRow>,
+    selected: usize,
+    /// First row of the tree in view
+    scroll: usize,
+}
+
+/// Elements from the root down to the element at `path`
+fn element_chain<'a>(root: &'a Element, path: &[usize]) -> Vec<&'a Element> {
+    let mut chain = vec![root];
+    for index in path {
+        let Some(child) = chain.last().unwrap().children.get(*index) else {
+            break;
+        };
+        chain.push(child);
+    }
+    chain
+}
+/// Whether an element is a text node of only whitespace, which isn't shown in the tree
+fn is_blank(element: &Element) -> bool {
+    element.ty.name == "node" && element.text.as_deref().is_none_or(is_whitespace)
+}
+fn element_label(element: &Element) -> String {
+    if element.ty.name == "node" {
+        let text = element.text.as_deref().unwrap_or_default();
+        return format!(
+            "\"{}\"",
+            text.split_whitespace().collect::<Vec<&str>>().join(" ")
+        );
+    }
+    let mut label = format!("<{}", element.ty.name);
+    if let Some(id) = element.get_attribute("id") {
+        label += &format!("#{id}");
+    }
+    for class in element.classes.iter().filter(|f| !f.is_empty()) {
+        label += &format!(".{class}");
+    }
+    label + ">"
+}
+
+fn color_text(color: Color) -> String {
+    match color {
+        Color::Rgb { r, g, b } => format!("#{r:02x}{g:02x}{b:02x}"),
+        color => format!("{color:?}").to_lowercase(),
+    }
+}
+fn measurement_text(measurement: Measurement) -> String {
+    match measurement {
+        Measurement::FitContentWidth | Measurement::FitContentHeight => String::from("fit-content"),
+        Measurement::PercentWidth(p) | Measurement::PercentHeight(p) => format!("{}%", p * 100.0),
+        Measurement::Pixels(p) => format!("{p}px"),
+    }
+}
+fn non_inherited_text<T>(
+    field: NonInheritedField<T>,
+    text: impl Fn(T) -> String,
+) -> Option<String> {
+    match field {
+        Unset => None,
+        Inherit => Some(String::from("inherit")),
+        Specified(value) => Some(text(value)),
+    }
+}
+/// The properties set in a style, as CSS
+pub fn style_properties(style: &ElementDrawContext) -> Vec<(&'static str, String)> {
+    let mut properties = Vec::new();
+    if let Some(align) = style.text_align {
+        let align = match align {
+            TextAlignment::Left => "left",
+            TextAlignment::Centre => "center",
+            TextAlignment::Right => "right",
+        };
+        properties.push(("text-align", String::from(align)));
+    }
+    if let Some(color) = style.foreground_color {
+        properties.push(("color", color_text(color)));
+    }
+    if let Some(color) = non_inherited_text(style.background_color, color_text) {
+        properties.push(("background-color", color));
+    }
+    let display = non_inherited_text(style.display, |display| {
+        String::from(match display {
+            Display::Inline => "inline",
+            Display::Block => "block",
+            Display::None => "none",
+        })
+    });
+    if let Some(display) = display {
+        properties.push(("display", display));
+    }
+    if style.bold {
+        properties.push(("font-weight", String::from("bold")));
+    }
+    if style.italics {
+        properties.push(("font-style", String::from("italic")));
+    }
+    if let Some(white_space) = style.white_space {
+        let white_space = match white_space {
+            WhiteSpace::Normal => "normal",
+            WhiteSpace::NoWrap => "nowrap",
+            WhiteSpace::Pre => "pre",
+            WhiteSpace::PreWrap => "pre-wrap",
+            WhiteSpace::PreLine => "pre-line",
+        };
+        properties.push(("white-space", String::from(white_space)));
+    }
+    if let Some(width) = non_inherited_text(style.width, measurement_text) {
+        properties.push(("width", width));
+    }
+    if let Some(max_width) = non_inherited_text(style.max_width, measurement_text) {
+        properties.push(("max-width", max_width));
+    }
+    if let Some(height) = non_inherited_text(style.height, measurement_text) {
+        properties.push(("height", height));
+    }
+    if let Some(prefix) = style.text_prefix {
+        let prefix = match prefix {
+            TextPrefix::Dot => "disc",
+            TextPrefix::Number => "decimal",
+        };
+        properties.push(("list-style-type", String::from(prefix)));
+    }
+    if let Some(overflow) = non_inherited_text(style.overflow, |f| format!("{f:?}")) {
+        properties.push(("overflow", overflow.to_lowercase()));
+    }
+    if let Some(overflow) = non_inherited_text(style.text_overflow, |f| format!("{f:?}")) {
+        properties.push(("text-overflow", overflow.to_lowercase()));
+    }
+    properties
+}
+
+fn target_type_text(ty: &StyleTargetType) -> String {
+    match ty {
+        StyleTargetType::ElementType(name) => name.clone(),
+        StyleTargetType::Class(class, ty) => format!("{}.{class}", ty.as_deref().unwrap_or("")),
+        StyleTargetType::Id(id, ty) => format!("{}#{id}", ty.as_deref().unwrap_or("")),
+        StyleTargetType::Theme(_) => String::new(),
+        StyleTargetType::Visited(ty) => {
+            format!(
+                "{}:visited",
+                ty.as_deref().map(target_type_text).unwrap_or_default()
+            )
+        }
+    }
+}
+/// The selector of a style target, as CSS
+pub fn selector_text(target: &StyleTarget) -> String {
+    let selector = target
+        .types
+        .iter()
+        .filter(|f| !matches!(f, StyleTargetType::Theme(_)))
+        .map(target_type_text)
+        .collect::<Vec<String>>()
+        .join(" ");
+    match target.types.last() {
+        Some(StyleTargetType::Theme(MediaThemeSelector::Dark)) => format!("{selector} (dark)"),
+        Some(StyleTargetType::Theme(MediaThemeSelector::Light)) => format!("{selector} (light)"),
+        _ => selector,
+    }
+}
+
+/// Works out the style of the last element of a chain, like it is when drawn
+pub fn inspect(
+    chain: &[&Element],
+    global_style: &Vec<(StyleTarget, ElementDrawContext)>,
+    base_url: &Option<Url>,
+    visited: &HashSet<String>,
+    is_dark: bool,
+    use_css: bool,
+) -> Inspection {
+    let global_ctx = GlobalDrawContext {
+        global_style,
+        unknown_sized_elements: Vec::new(),
+        interactables: Vec::new(),
+        forms: Vec::new(),
+        autofocus: None,
+        clips: Vec::new(),
+        cached_image_sizes: HashMap::new(),
+        base_url,
+        visited: HashSet::new(),
+        is_dark,
+        use_css,
+    };
+    let mut target_info = Vec::new();
+    let mut computed = DEFAULT_DRAW_CTX;
+    for element in chain {
+        target_info.push(element.target_info(base_url, visited));
+        computed = if use_css {
+            element.get_active_style(&global_ctx, computed, &target_info)
+        } else {
+            let mut style = element.ty.draw_ctx;
+            style.merge_inherit(&computed);
+            style
+        };
+    }
+    let Some(element) = chain.last() else {
+        return Inspection {
+            rules: Vec::new(),
+            computed,
+        };
+    };
+
+    // rules are applied in order, so the last one to set a property wins
+    let mut rules: Vec<(String, Vec<(&'static str, String)>)> = Vec::new();
+    let base_properties = style_properties(&element.ty.draw_ctx);
+    if !base_properties.is_empty() {
+        rules.push((
+            format!("default {} style", element.ty.name),
+            base_properties,
+        ));
+    }
+    if use_css {
+        for (target, style) in global_style.iter() {
+            if target.matches(&target_info, is_dark) {
+                rules.push((selector_text(target), style_properties(style)));
+            }
+        }
+        let inline = style_properties(&element.style);
+        if !inline.is_empty() {
+            rules.push((String::from("style attribute"), inline));
+        }
+    }
+    let rules = rules
+        .iter()
+        .enumerate()
+        .map(|(index, (selector, properties))| MatchedRule {
+            selector: selector.clone(),
+            properties: properties
+                .iter()
+                .map(|(name, value)| {
+                    let overridden = rules[index + 1..]
+                        .iter()
+                        .any(|(_, later)| later.iter().any(|f| f.0 == *name));
+                    (*name, value.clone(), overridden)
+                })
+                .collect(),
+        })
+        .collect();
+    Inspection { rules, computed }
+}
+
+impl Inspector {
+    pub fn new(root: Arc<Element>) -> Self {
+        // show the children of the root, which is usually <html>
+        let mut expanded = HashSet::from([0]);
+        for index in 0..root.children.len() {
+            expanded.insert(anchor_id(0, index));
+        }
+        let mut inspector = Self {
+            root,
+            expanded,
+            rows: Vec::new(),
+            selected: 0,
+            scroll: 0,
+        };
+        inspector.rebuild_rows();
+        inspector
+    }
+    fn rebuild_rows(&mut self) {
+        fn add_rows(
+            element: &Element,
+            path: &mut Vec<usize>,
+            id: u64,
+            expanded: &HashSet<u64>,
+            rows: &mut Vec<TreeRow>,
+        ) {
+            let expandable = element.children.iter().any(|f| !is_blank(f));
+            rows.push(TreeRow {
+                path: path.clone(),
+                id,
+                label: element_label(element),
+                expandable,
+            });
+            if !expandable || !expanded.contains(&id) {
+                return;
+            }
+            for (index, child) in element.children.iter().enumerate() {
+                if is_blank(child) {
+                    continue;
+                }
+                path.push(index);
+                add_rows(child, path, anchor_id(id, index), expanded, rows);
+                path.pop();
+            }
+        }
+        let selected = self.rows.get(self.selected).map(|f| f.id);
+        self.rows.clear();
+        add_rows(
+            &self.root,
+            &mut Vec::new(),
+            0,
+            &self.expanded,
+            &mut self.rows,
+        );
+        self.selected = selected
+            .and_then(|id| self.rows.iter().position(|f| f.id == id))
+            .unwrap_or_default();
+    }
+    /// Anchor ID of the selected element
+    pub fn selected_id(&self) -> Option<u64> {
+        self.rows.get(self.selected).map(|f| f.id)
+    }
+    fn selected_chain(&self) -> Vec<&Element> {
+        let path = self.rows.get(self.selected).map(|f| f.path.as_slice());
+        element_chain(&self.root, path.unwrap_or_default())
+    }
+    pub fn on_key(&mut self, key: KeyEvent) -> InspectorInput {
+        let Some(row) = self.rows.get(self.selected) else {
+            return InspectorInput::Close;
+        };
+        let id = row.id;
+        match key.code {
+            KeyCode::Esc => return InspectorInput::Close,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(self.rows.len() - 1),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(10),
+            KeyCode::PageDown => self.selected = (self.selected + 10).min(self.rows.len() - 1),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.rows.len() - 1,
+            KeyCode::Right | KeyCode::Enter if row.expandable && !self.expanded.contains(&id) => {
+                self.expanded.insert(id);
+                self.rebuild_rows();
+            }
+            KeyCode::Right => {
+                if row.expandable {
+                    self.selected = (self.selected + 1).min(self.rows.len() - 1);
+                }
+            }
+            KeyCode::Left | KeyCode::Enter if self.expanded.contains(&id) && row.expandable => {
+                self.expanded.remove(&id);
+                self.rebuild_rows();
+            }
+            KeyCode::Left => {
+                // go to the parent
+                let parent = &row.path[..row.path.len().saturating_sub(1)];
+                if let Some(index) = self.rows.iter().position(|f| f.path == parent) {
+                    self.selected = index;
+                }
+            }
+            _ => return InspectorInput::Ignored,
+        }
+        InspectorInput::Changed
+    }
+    /// Highlights the cells of the selected element on the page
+    pub fn highlight(&self, buffer: &mut Buffer, draws: &CachedDraw, scroll_y: u16, height: u16) {
+        let Some(element_box) = self.selected_id().and_then(|id| draws.element_box(id)) else {
+            return;
+        };
+        let x = element_box.x / EM;
+        let y = element_box.y / LH;
+        let width = actualize_actual(element_box.width, &draws.unknown_sized_elements) / EM;
+        let rows = actualize_actual(element_box.height, &draws.unknown_sized_elements) / LH;
+        for row in y..y + rows.max(1) {
+            if let Some(row) = row.checked_sub(scroll_y)
+                && (3..height).contains(&row)
+            {
+                buffer.highlight(x, row, width.max(1), INSPECTOR_HIGHLIGHT_COLOR);
+            }
+        }
+    }
+    /// Draws the panel over the right half of the screen
+    pub fn draw(
+        &mut self,
+        buffer: &mut Buffer,
+        screen_size: (u16, u16),
+        draws: &CachedDraw,
+        inspection: &Inspection,
+        theme: &Theme,
+    ) {
+        let (screen_width, screen_height) = screen_size;
+        let panel_width = (screen_width / 2).max(30).min(screen_width);
+        let x0 = screen_width - panel_width;
+        // leave the bottom row to the status line
+        let panel_height = screen_height.saturating_sub(4);
+        let Some(text_width) = panel_width.checked_sub(2) else {
+            return;
+        };
+        buffer.draw_rect(x0, 3, panel_width, panel_height, theme.background_color);
+        for y in 3..3 + panel_height {
+            buffer.draw_str(x0, y, "│", &self.ctx(theme.ui_color, false, theme), None);
+        }
+        let mut y = 3;
+        let mut line = |buffer: &mut Buffer, text: &str, ctx: &ElementDrawContext| {
+            if y < 3 + panel_height {
+                let text = truncate_to_width(text, text_width as usize);
+                buffer.draw_str(x0 + 2, y, &text, ctx, None);
+            }
+            y += 1;
+        };
+
+        // element tree
+        let tree_height = (panel_height * TREE_FIFTHS / 5).max(1) as usize;
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + tree_height {
+            self.scroll = self.selected + 1 - tree_height;
+        }
+        for (index, row) in self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(tree_height)
+        {
+            let marker = match (row.expandable, self.expanded.contains(&row.id)) {
+                (false, _) => ' ',
+                (true, true) => '▾',
+                (true, false) => '▸',
+            };
+            let text = format!("{}{marker} {}", "  ".repeat(row.path.len()), row.label);
+            let ctx = if index == self.selected {
+                ElementDrawContext {
+                    background_color: Specified(theme.interactive_color),
+                    ..self.ctx(theme.background_color, true, theme)
+                }
+            } else {
+                self.ctx(theme.text_color, false, theme)
+            };
+            let text = format!("{text:<0$}", text_width as usize);
+            line(buffer, &text, &ctx);
+        }
+        for _ in self.rows.len().saturating_sub(self.scroll)..tree_height {
+            line(buffer, "", &self.ctx(theme.text_color, false, theme));
+        }
+        line(
+            buffer,
+            &"─".repeat(text_width as usize),
+            &self.ctx(theme.ui_color, false, theme),
+        );
+
+        // details of the selected element
+        let heading = self.ctx(theme.text_color, true, theme);
+        let text = self.ctx(theme.text_color, false, theme);
+        let faded = self.ctx(theme.ui_color, false, theme);
+        let element_box = self.selected_id().and_then(|id| draws.element_box(id));
+        let box_text = match element_box {
+            Some(element_box) => {
+                let width = actualize_actual(element_box.width, &draws.unknown_sized_elements);
+                let height = actualize_actual(element_box.height, &draws.unknown_sized_elements);
+                format!(
+                    "box: {}, {} {}×{} ({}×{} cells)",
+                    element_box.x,
+                    element_box.y.saturating_sub(3 * LH),
+                    width,
+                    height,
+                    width / EM,
+                    height / LH
+                )
+            }
+            None => String::from("box: not drawn"),
+        };
+        line(buffer, &box_text, &text);
+        line(buffer, "", &text);
+        line(buffer, "computed style", &heading);
+        for (name, value) in style_properties(&inspection.computed) {
+            line(buffer, &format!("  {name}: {value}"), &text);
+        }
+        line(buffer, "", &text);
+        line(buffer, "matched rules", &heading);
+        if inspection.rules.is_empty() {
+            line(buffer, "  none", &faded);
+        }
+        for rule in inspection.rules.iter().rev() {
+            line(buffer, &format!("  {}", rule.selector), &heading);
+            for (name, value, overridden) in rule.properties.iter() {
+                if *overridden {
+                    line(buffer, &format!("    {name}: {value} (overridden)"), &faded);
+                } else {
+                    line(buffer, &format!("    {name}: {value}"), &text);
+                }
+            }
+        }
+    }
+    fn ctx(&self, color: Color, bold: bool, theme: &Theme) -> ElementDrawContext {
+        ElementDrawContext {
+            foreground_color: Some(color),
+            background_color: Specified(theme.background_color),
+            bold,
+            ..DEFAULT_DRAW_CTX
+        }
+    }
+    /// Inspects the selected element
+    pub fn inspect(
+        &self,
+        global_style: &Vec<(StyleTarget, ElementDrawContext)>,
+        base_url: &Option<Url>,
+        visited: &HashSet<String>,
+        is_dark: bool,
+        use_css: bool,
+    ) -> Inspection {
+        inspect(
+            &self.selected_chain(),
+            global_style,
+            base_url,
+            visited,
+            is_dark,
+            use_css,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{
+        css::{MediaThemeSelector, parse_color, parse_stylesheet},
+        inspector::Inspector,
+        parsing::parse_html,
+    };
+
+    #[test]
+    fn test_inspector() {
+        let html = "<html><body><div id=\"main\"><p class=\"note\">toads <b>croak</b></p>\n</div></body></html>";
+        let page = parse_html(html).unwrap();
+        let mut global_style = Vec::new();
+        parse_stylesheet(
+            "p { color: red; text-align: center; } .note { color: blue; } #main p { display: none; }",
+            &mut global_style,
+            MediaThemeSelector::Unset,
+        );
+        let mut inspector = Inspector::new(Arc::clone(page.root.as_ref().unwrap()));
+        let labels: Vec<&str> = inspector.rows.iter().map(|f| f.label.as_str()).collect();
+        assert_eq!(labels, ["<html>", "<body>", "<div#main>"]);
+
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        let right = KeyEvent::new(KeyCode::Right, KeyModifiers::NONE);
+        inspector.on_key(down);
+        inspector.on_key(down);
+        inspector.on_key(right);
+        inspector.on_key(right);
+        assert_eq!(inspector.rows[inspector.selected].label, "<p.note>");
+
+        let inspection = inspector.inspect(&global_style, &None, &HashSet::new(), false, true);
+        let selectors: Vec<&str> = inspection
+            .rules
+            .iter()
+            .map(|f| f.selector.as_str())
+            .collect();
+        assert_eq!(selectors, ["default p style", "p", ".note", "#main p"]);
+        // the color of `p` is overridden by `.note`, but its alignment isn't
+        assert_eq!(
+            inspection.rules[1].properties,
+            [
+                ("text-align", String::from("center"), false),
+                ("color", String::from("#ff0000"), true)
+            ]
+        );
+        assert_eq!(inspection.computed.foreground_color, parse_color("blue"));
+
+        // collapsing goes back to the parent first
+        let left = KeyEvent::new(KeyCode::Left, KeyModifiers::NONE);
+        inspector.on_key(left);
+        assert_eq!(inspector.rows[inspector.selected].label, "<div#main>");
+        inspector.on_key(left);
+        assert_eq!(inspector.rows.len(), 3);
+    }
+}
@@ -0,0 +1,481 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something a key binding can do
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    ScrollDown,
+    ScrollUp,
+    ScrollLeft,
+    ScrollRight,
+    PageDown,
+    PageUp,
+    ScrollTop,
+    ScrollBottom,
+    NextInteractable,
+    PreviousInteractable,
+    Follow,
+    FollowNewTab,
+    Back,
+    Forward,
+    Reload,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    OpenUrlBar,
+    History,
+    Bookmark,
+    ReaderMode,
+    ViewSource,
+    Network,
+    Find,
+    FindNext,
+    FindPrevious,
+    Cancel,
+    HintFollow,
+    HintFollowNewTab,
+    HintCopyUrl,
+    DebugInfo,
+    SaveLogin,
+    ToggleBlocking,
+    Quit,
+}
+
+/// Every action, in the order they are listed on the home page.
+///
+/// The name is what's used in the config file, the description what's shown on the home page.
+pub static ACTIONS: &[(Action, &str, &str)] = &[
+    (Action::NewTab, "new_tab", "open new tab"),
+    (Action::CloseTab, "close_tab", "close current tab"),
+    (Action::NextTab, "next_tab", "cycle through tabs"),
+    (
+        Action::PreviousTab,
+        "previous_tab",
+        "cycle through tabs backwards",
+    ),
+    (Action::OpenUrlBar, "open_url_bar", "edit the address"),
+    (Action::Back, "back", "go back"),
+    (Action::Forward, "forward", "go forward"),
+    (Action::Reload, "reload", "reload page"),
+    (Action::History, "history", "show history"),
+    (
+        Action::Bookmark,
+        "bookmark",
+        "bookmark current page, or remove its bookmark",
+    ),
+    (
+        Action::ReaderMode,
+        "reader_mode",
+        "show only the article of the page, or go back to the full page",
+    ),
+    (
+        Action::ViewSource,
+        "view_source",
+        "show the source of the page, or go back to the page",
+    ),
+    (Action::ScrollDown, "scroll_down", "scroll down"),
+    (Action::ScrollUp, "scroll_up", "scroll up"),
+    (
+        Action::ScrollLeft,
+        "scroll_left",
+        "scroll box under the mouse left",
+    ),
+    (
+        Action::ScrollRight,
+        "scroll_right",
+        "scroll box under the mouse right",
+    ),
+    (Action::PageDown, "page_down", "scroll down a page"),
+    (Action::PageUp, "page_up", "scroll up a page"),
+    (Action::ScrollTop, "scroll_top", "scroll to top"),
+    (Action::ScrollBottom, "scroll_bottom", "scroll to bottom"),
+    (
+        Action::NextInteractable,
+        "next_interactable",
+        "select next link",
+    ),
+    (
+        Action::PreviousInteractable,
+        "previous_interactable",
+        "select previous link",
+    ),
+    (Action::Follow, "follow", "open selected link"),
+    (
+        Action::FollowNewTab,
+        "follow_new_tab",
+        "open selected link in new tab",
+    ),
+    (
+        Action::HintFollow,
+        "hint_follow",
+        "follow a link by typing its hint",
+    ),
+    (
+        Action::HintFollowNewTab,
+        "hint_follow_new_tab",
+        "open a link in new tab by typing its hint",
+    ),
+    (
+        Action::HintCopyUrl,
+        "hint_copy_url",
+        "copy a link by typing its hint",
+    ),
+    (
+        Action::Find,
+        "find",
+        "find in page (Alt + C and Alt + R toggle case and regex)",
+    ),
+    (Action::FindNext, "find_next", "next match"),
+    (Action::FindPrevious, "find_previous", "previous match"),
+    (Action::Cancel, "cancel", "stop finding"),
+    (
+        Action::Network,
+        "network",
+        "show network requests of the page",
+    ),
+    (
+        Action::DebugInfo,
+        "debug_info",
+        "toggle the element inspector",
+    ),
+    (
+        Action::SaveLogin,
+        "save_login",
+        "save the login last sent from a form for autofill",
+    ),
+    (
+        Action::ToggleBlocking,
+        "toggle_blocking",
+        "stop or resume blocking content on the current site",
+    ),
+    (Action::Quit, "quit", "exit (press twice)"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    /// Only [KeyModifiers::CONTROL], [KeyModifiers::ALT] and [KeyModifiers::SHIFT] are considered
+    pub modifiers: KeyModifiers,
+}
+impl KeyBinding {
+    const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+    const fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+    const fn char(char: char) -> Self {
+        Self::plain(KeyCode::Char(char))
+    }
+    const fn ctrl(char: char) -> Self {
+        Self::new(KeyCode::Char(char), KeyModifiers::CONTROL)
+    }
+    /// Parses a key like `ctrl+shift+left`, `G` or `f12`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // allow binding '+' itself, like "ctrl++"
+        let key = if text.ends_with("++") || text == "+" {
+            parts.retain(|f| !f.is_empty());
+            "+"
+        } else {
+            parts.pop()?
+        };
+        for part in parts {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" | "c" => KeyModifiers::CONTROL,
+                "alt" | "a" | "m" => KeyModifiers::ALT,
+                "shift" | "s" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+        let code = if key.chars().count() == 1 {
+            let mut char = key.chars().next()?;
+            if modifiers.contains(KeyModifiers::SHIFT) && char.is_ascii_alphabetic() {
+                // shift is implied by the char being uppercase
+                modifiers.remove(KeyModifiers::SHIFT);
+                char = char.to_ascii_uppercase();
+            }
+            KeyCode::Char(char)
+        } else {
+            match key.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                other => KeyCode::F(other.strip_prefix('f')?.parse().ok()?),
+            }
+        };
+        Some(Self::new(code, modifiers))
+    }
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let considered = KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+        let mut modifiers = key.modifiers & considered;
+        let mut code = key.code;
+        if let KeyCode::Char(char) = code {
+            // the case of the char already says whether shift is held
+            modifiers.remove(KeyModifiers::SHIFT);
+            // terminals send ctrl+shift+letter as lowercase
+            if modifiers.contains(KeyModifiers::CONTROL)
+                && key.modifiers.contains(KeyModifiers::SHIFT)
+            {
+                code = KeyCode::Char(char.to_ascii_uppercase());
+            }
+        }
+        // shift+tab is sometimes sent as backtab with shift
+        if code == KeyCode::BackTab {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == code && self.modifiers == modifiers
+    }
+    /// Display name, like "Ctrl + T"
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            name += "Ctrl + ";
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            name += "Alt + ";
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            name += "Shift + ";
+        }
+        match self.code {
+            KeyCode::Char(' ') => name += "Space",
+            KeyCode::Char(char) if char.is_ascii_uppercase() => {
+                name += &format!("Shift + {char}");
+            }
+            KeyCode::Char(char) => name += &char.to_uppercase().to_string(),
+            KeyCode::BackTab => name += "Shift + Tab",
+            other => name += &other.to_string(),
+        }
+        name
+    }
+}
+
+/// Which key does what
+#[derive(Clone, PartialEq, Debug)]
+pub struct Keybinds {
+    pub bindings: Vec<(KeyBinding, Action)>,
+}
+impl Default for Keybinds {
+    fn default() -> Self {
+        use Action::*;
+        use KeyBinding as K;
+        Self {
+            bindings: vec![
+                (K::plain(KeyCode::Down), ScrollDown),
+                (K::plain(KeyCode::Up), ScrollUp),
+                (K::new(KeyCode::Left, KeyModifiers::SHIFT), ScrollLeft),
+                (K::new(KeyCode::Right, KeyModifiers::SHIFT), ScrollRight),
+                (K::plain(KeyCode::PageDown), PageDown),
+                (K::plain(KeyCode::PageUp), PageUp),
+                (K::plain(KeyCode::Home), ScrollTop),
+                (K::plain(KeyCode::End), ScrollBottom),
+                (K::plain(KeyCode::Right), NextInteractable),
+                (K::plain(KeyCode::Left), PreviousInteractable),
+                (K::plain(KeyCode::Enter), Follow),
+                (K::new(KeyCode::Enter, KeyModifiers::CONTROL), FollowNewTab),
+                (K::new(KeyCode::Left, KeyModifiers::CONTROL), Back),
+                (K::new(KeyCode::Right, KeyModifiers::CONTROL), Forward),
+                (K::ctrl('r'), Reload),
+                (K::ctrl('t'), NewTab),
+                (K::ctrl('w'), CloseTab),
+                (K::plain(KeyCode::Tab), NextTab),
+                (K::plain(KeyCode::BackTab), PreviousTab),
+                (K::ctrl('l'), OpenUrlBar),
+                (K::ctrl('h'), History),
+                (K::ctrl('d'), Bookmark),
+                (K::char('R'), ReaderMode),
+                (K::ctrl('u'), ViewSource),
+                (K::char('/'), Find),
+                (K::ctrl('f'), Find),
+                (K::char('n'), FindNext),
+                (K::char('N'), FindPrevious),
+                (K::plain(KeyCode::Esc), Cancel),
+                (K::char('f'), HintFollow),
+                (K::char('F'), HintFollowNewTab),
+                (K::char('y'), HintCopyUrl),
+                (K::ctrl('e'), Network),
+                (K::plain(KeyCode::F(12)), DebugInfo),
+                (K::ctrl('s'), SaveLogin),
+                (K::ctrl('b'), ToggleBlocking),
+                (K::char('q'), Quit),
+            ],
+        }
+    }
+}
+impl Keybinds {
+    /// The default bindings, plus vi-style movement keys
+    pub fn vi() -> Self {
+        use Action::*;
+        use KeyBinding as K;
+        let mut keybinds = Self::default();
+        let vi_bindings = [
+            (K::char('j'), ScrollDown),
+            (K::char('k'), ScrollUp),
+            (K::char('h'), ScrollLeft),
+            (K::char('l'), ScrollRight),
+            (K::ctrl('d'), PageDown),
+            (K::ctrl('u'), PageUp),
+            (K::char('g'), ScrollTop),
+            (K::char('G'), ScrollBottom),
+            (K::char('H'), Back),
+            (K::char('L'), Forward),
+            (K::char('r'), Reload),
+            (K::char('t'), NewTab),
+            (K::char('x'), CloseTab),
+            (K::char('K'), NextTab),
+            (K::char('J'), PreviousTab),
+            (K::char('o'), OpenUrlBar),
+            (K::char('m'), Bookmark),
+            (K::char('U'), ViewSource),
+        ];
+        keybinds
+            .bindings
+            .retain(|f| !vi_bindings.iter().any(|(key, _)| *key == f.0));
+        keybinds.bindings.extend(vi_bindings);
+        keybinds
+    }
+    /// Parses a key binding config file.
+    ///
+    /// Each line is either `preset = default|vi`, or an action name followed by the keys bound to it,
+    /// like `close_tab = ctrl+w x`. Binding an action replaces the preset's keys for it,
+    /// and binding it to `none` unbinds it. Lines starting with `#` are comments.
+    pub fn parse(text: &str) -> Self {
+        let mut keybinds = Self::default();
+        let mut overrides: Vec<(Action, Vec<KeyBinding>)> = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (name, value) = (name.trim(), value.trim());
+            if name == "preset" {
+                keybinds = match value {
+                    "vi" => Self::vi(),
+                    _ => Self::default(),
+                };
+                continue;
+            }
+            let Some((action, ..)) = ACTIONS.iter().find(|f| f.1 == name) else {
+                continue;
+            };
+            let keys = value
+                .split_whitespace()
+                .filter(|f| *f != "none")
+                .filter_map(KeyBinding::parse)
+                .collect();
+            overrides.push((*action, keys));
+        }
+        for (action, keys) in overrides {
+            keybinds.bindings.retain(|f| f.1 != action);
+            // a key can only do one thing
+            keybinds.bindings.retain(|f| !keys.contains(&f.0));
+            keybinds
+                .bindings
+                .extend(keys.into_iter().map(|key| (key, action)));
+        }
+        keybinds
+    }
+    pub fn get(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings.iter().find(|f| f.0.matches(key)).map(|f| f.1)
+    }
+    pub fn keys_for(&self, action: Action) -> impl Iterator<Item = &KeyBinding> {
+        self.bindings
+            .iter()
+            .filter(move |f| f.1 == action)
+            .map(|f| &f.0)
+    }
+    /// Generates the list of shortcuts shown on the home page, as HTML
+    pub fn shortcut_list_html(&self) -> String {
+        let mut html = String::new();
+        for (action, _, description) in ACTIONS {
+            let keys: Vec<String> = self
+                .keys_for(*action)
+                .map(|f| {
+                    format!(
+                        "<span class=\"highlighted\">{}</span>",
+                        crate::parsing::sanitize(&f.name())
+                    )
+                })
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
+            html += &format!("<li>{} - {description}</li>\n", keys.join(" or "));
+        }
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::keybinds::{ACTIONS, Action, KeyBinding, Keybinds};
+
+    #[test]
+    fn test_parse_keybinds() {
+        assert_eq!(
+            KeyBinding::parse("ctrl+shift+left"),
+            Some(KeyBinding {
+                code: KeyCode::Left,
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            })
+        );
+        assert_eq!(KeyBinding::parse("shift+g"), KeyBinding::parse("G"));
+        assert_eq!(KeyBinding::parse("ctrl+nope+x"), None);
+
+        let keybinds = Keybinds::parse("preset = vi\n# comment\nquit = ctrl+q\nclose_tab = q");
+        let key = |code, modifiers| keybinds.get(&KeyEvent::new(code, modifiers));
+        assert_eq!(
+            key(KeyCode::Char('j'), KeyModifiers::NONE),
+            Some(Action::ScrollDown)
+        );
+        assert_eq!(
+            key(KeyCode::Char('q'), KeyModifiers::NONE),
+            Some(Action::CloseTab)
+        );
+        assert_eq!(
+            key(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Some(Action::Quit)
+        );
+        assert_eq!(key(KeyCode::Char('x'), KeyModifiers::NONE), None);
+        assert_eq!(
+            key(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            Some(Action::PageDown)
+        );
+        assert_eq!(
+            key(KeyCode::Char('N'), KeyModifiers::SHIFT),
+            Some(Action::FindPrevious)
+        );
+    }
+
+    #[test]
+    fn test_vi_keeps_every_action() {
+        let (default, vi) = (Keybinds::default(), Keybinds::vi());
+        for (action, name, _) in ACTIONS {
+            if default.keys_for(*action).next().is_some() {
+                assert!(
+                    vi.keys_for(*action).next().is_some(),
+                    "{name} has no vi key"
+                );
+            }
+        }
+    }
+}
@@ -7,29 +7,68 @@ use crossterm::{
 use reqwest::{Client, Method, Url};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::Debug,
     io::{self, Stdout, Write, stdout},
+    path::PathBuf,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::task::JoinHandle;
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+use autofill::*;
+use blocker::*;
+use bookmarks::*;
 use buffer::*;
 use config::*;
 use consts::*;
 use element::*;
+use file_picker::*;
+use find::*;
+use hints::*;
+use history::*;
+use inspector::*;
+use keybinds::*;
+use network::*;
 use parsing::*;
+use reader::*;
+use search::*;
+use select::*;
+use session::*;
+use source::*;
+use submission::*;
+use suggestions::*;
+use textarea::*;
 use utils::*;
+use validation::*;
 
+mod autofill;
+mod blocker;
+mod bookmarks;
 mod buffer;
 mod config;
 mod consts;
 mod css;
 mod element;
+mod file_picker;
+mod find;
+mod hints;
+mod history;
+mod inspector;
+mod keybinds;
+mod network;
 mod parsing;
+mod reader;
+mod search;
+mod select;
+mod session;
+mod source;
+mod submission;
+mod suggestions;
+mod textarea;
 mod utils;
+mod validation;
 
 #[derive(Clone)]
 struct CachedDraw {
@@ -38,6 +77,99 @@ struct CachedDraw {
     interactables: Vec<Interactable>,
     content_height: u16,
     forms: Vec<Form>,
+    /// Boxes of elements with `overflow` set, indexed by the clip index of draw calls
+    clips: Vec<ClipRect>,
+    /// Boxes of drawn elements, sorted by Y
+    boxes: Vec<ElementBox>,
+    /// The first interactable with `autofocus` set
+    autofocus: Option<usize>,
+    /// Interactable of the field that failed validation when submitting, and why
+    invalid: Option<(usize, String)>,
+    /// The element tree this was drawn from
+    root: Option<Arc<Element>>,
+}
+impl CachedDraw {
+    /// Takes the values filled into the forms of an earlier draw, if it was drawn from the same elements
+    fn keep_values(&mut self, old: &CachedDraw) {
+        if let (Some(root), Some(old_root)) = (&self.root, &old.root)
+            && Arc::ptr_eq(root, old_root)
+        {
+            for (form, old) in self.forms.iter_mut().zip(old.forms.iter()) {
+                form.keep_values(old);
+            }
+        }
+    }
+    /// Finds the element starting closest above the top of the view, preferring the innermost one
+    fn scroll_anchor(&self, scroll_y: u16) -> Option<ScrollAnchor> {
+        let top = scroll_y + 3;
+        let index = self.boxes.partition_point(|f| f.y / LH <= top);
+        let element_box = self.boxes.get(index.checked_sub(1)?)?;
+        Some(ScrollAnchor {
+            element: element_box.id,
+            offset: top - element_box.y / LH,
+        })
+    }
+    /// The scroll that puts an anchor where it was in the view, if the element is still drawn
+    fn anchored_scroll(&self, anchor: &ScrollAnchor) -> Option<u16> {
+        let element_box = self.element_box(anchor.element)?;
+        Some((element_box.y / LH + anchor.offset).saturating_sub(3))
+    }
+    fn element_box(&self, id: u64) -> Option<&ElementBox> {
+        self.boxes.iter().find(|f| f.id == id)
+    }
+    /// Converts all clip rects to screen space, intersecting each with those of its ancestors.
+    ///
+    /// Returns [None] for clips that are entirely empty.
+    fn screen_clips(
+        &self,
+        scroll_y: u16,
+        scroll_x: &HashMap<usize, u16>,
+    ) -> Vec<Option<ScreenClip>> {
+        let mut screen_clips: Vec<Option<ScreenClip>> = Vec::with_capacity(self.clips.len());
+        for (index, clip) in self.clips.iter().enumerate() {
+            // clips are always registered before their children, so the parent is already converted
+            let parent = clip.info.parent.map(|p| screen_clips[p]);
+            if let Some(None) = parent {
+                screen_clips.push(None);
+                continue;
+            }
+            let parent = parent.flatten();
+            let parent_shift = parent.map(|p| p.shift_x).unwrap_or_default();
+            let width = actualize_actual(clip.width, &self.unknown_sized_elements) / EM;
+            let height = actualize_actual(clip.height, &self.unknown_sized_elements) / LH;
+            let x0 = (clip.x / EM) as i32 - parent_shift as i32;
+            let y0 = (clip.y / LH) as i32 - scroll_y as i32;
+            let mut screen_clip = ScreenClip {
+                x0,
+                y0,
+                x1: x0 + width as i32,
+                y1: y0 + height as i32,
+                shift_x: parent_shift + scroll_x.get(&index).cloned().unwrap_or_default(),
+                ellipsis: clip.info.ellipsis,
+            };
+            if let Some(parent) = parent {
+                screen_clip.x0 = screen_clip.x0.max(parent.x0);
+                screen_clip.y0 = screen_clip.y0.max(parent.y0);
+                screen_clip.x1 = screen_clip.x1.min(parent.x1);
+                screen_clip.y1 = screen_clip.y1.min(parent.y1);
+            }
+            if screen_clip.x0 >= screen_clip.x1 || screen_clip.y0 >= screen_clip.y1 {
+                screen_clips.push(None);
+            } else {
+                screen_clips.push(Some(screen_clip));
+            }
+        }
+        screen_clips
+    }
+}
+
+/// Element the view was scrolled to, so it can be scrolled to again after the page is redrawn
+#[derive(Clone, Copy, Debug)]
+struct ScrollAnchor {
+    /// Anchor ID of the element, see [anchor_id]
+    element: u64,
+    /// Rows between the top of the element and the top of the view
+    offset: u16,
 }
 
 #[derive(Default, Clone)]
@@ -58,8 +190,61 @@ struct Webpage {
     has_been_scrolled: bool,
     /// The current height of the page
     page_height: Option<u16>,
+    /// Horizontal scroll of `overflow: auto` elements, by clip index
+    scroll_x: HashMap<usize, u16>,
+    /// Active find-in-page query and its matches
+    find: Option<FindState>,
+    /// Requests made for this page, in the order they finished
+    requests: Vec<NetworkRequest>,
+    /// Whether this is a placeholder, shown while the actual page is fetched
+    loading: bool,
+    /// Whether this is a placeholder of a restored session, which is fetched once it's shown
+    restored: bool,
+    /// How many assets of this page couldn't be fetched
+    failed_fetches: usize,
+    /// How many requests of this page were blocked by filter lists
+    blocked_requests: usize,
+    /// What was in view before the page was last redrawn, see [Webpage::save_scroll_anchor]
+    scroll_anchor: Option<ScrollAnchor>,
+    /// Whether this is the reader mode of the page before it
+    reader: bool,
+    /// The HTML the page was parsed from
+    source: Option<Arc<str>>,
 }
 impl Webpage {
+    /// Placeholder page, shown while `url` is fetched
+    fn loading(url: Url) -> Self {
+        let mut page = parse_html(include_str!("loading.html")).unwrap();
+        page.url = Some(url);
+        page.loading = true;
+        page
+    }
+    /// Shown instead of a page that filter lists kept from being fetched
+    fn blocked(url: Url) -> Self {
+        let html = include_str!("blocked.html").replace("{{URL}}", &sanitize(url.as_str()));
+        let mut page = parse_html(&html).unwrap();
+        page.url = Some(url.clone());
+        page.block_request(RequestKind::Document, url);
+        page
+    }
+    /// Records a request that filter lists kept from being made
+    fn block_request(&mut self, kind: RequestKind, url: Url) {
+        let mut request = NetworkRequest::new(kind, Method::GET, url);
+        request.finish(ResponseInfo {
+            error: Some(String::from(BLOCKED_ERROR)),
+            ..Default::default()
+        });
+        self.requests.push(request);
+        self.blocked_requests += 1;
+    }
+    /// Placeholder page of a restored session
+    fn restored(url: Url, scroll_y: u16) -> Self {
+        let mut page = Self::loading(url);
+        page.restored = true;
+        page.scroll_y = scroll_y;
+        page.has_been_scrolled = scroll_y > 0;
+        page
+    }
     fn get_title(&self) -> String {
         if let Some(title) = self.title.clone() {
             return title;
@@ -69,6 +254,113 @@ impl Webpage {
         }
         String::from("unknown")
     }
+    /// Shows the highlighted source of the page at `url`, where `url` is a `view-source:` URL
+    fn view_source(url: Url, source: &str) -> Option<Self> {
+        let inner = view_source_target(&url);
+        let html = include_str!("view_source.html")
+            .replace(
+                "{{TITLE}}",
+                &sanitize(inner.as_ref().map_or("", |f| f.as_str())),
+            )
+            .replace("{{LINES}}", &source_to_html(source, inner.as_ref()));
+        let mut page = parse_html(&html)?;
+        page.url = Some(url);
+        // the source of the source view isn't useful
+        page.source = None;
+        Some(page)
+    }
+    /// Extracts the article of this page, and shows it with the reader mode stylesheet
+    fn reader_mode(&self) -> Option<Self> {
+        let content = extract_article(self.root.as_ref()?)?;
+        let title = self.title.clone().unwrap_or_default();
+        let heading = if content.contains("<h1>") || title.trim().is_empty() {
+            String::new()
+        } else {
+            format!("<h1>{}</h1>", sanitize(title.trim()))
+        };
+        let source = self
+            .url
+            .as_ref()
+            .and_then(|f| f.host_str())
+            .unwrap_or_default();
+        let html = include_str!("reader.html")
+            .replace("{{TITLE}}", &sanitize(&title))
+            .replace("{{SOURCE}}", &sanitize(source))
+            .replace("{{HEADING}}", &heading)
+            .replace("{{CONTENT}}", &content);
+        let mut page = parse_html(&html)?;
+        page.url = self.url.clone();
+        page.reader = true;
+        Some(page)
+    }
+    /// Remembers which element is at the top of the view, so the same content stays in view once the page is drawn again.
+    ///
+    /// Pages scrolled to the very top stay there.
+    fn save_scroll_anchor(&mut self) {
+        // keep the anchor of a draw that hasn't been replaced yet
+        let Some(draw) = &self.cached_draw else {
+            return;
+        };
+        self.scroll_anchor = if self.scroll_y > 0 {
+            draw.scroll_anchor(self.scroll_y)
+        } else {
+            None
+        };
+    }
+    /// Removes the draw of the page, so it's drawn again when shown
+    fn uncache_draw(&mut self) {
+        self.save_scroll_anchor();
+        self.cached_draw = None;
+    }
+    /// Scrolls such that the selected find match is visible
+    fn scroll_to_find_match(&mut self, screen_height: u16) {
+        let Some(m) = self.find.as_ref().and_then(|f| f.current_match()) else {
+            return;
+        };
+        let row = m.y / LH;
+        if row < self.scroll_y + 3 || row >= self.scroll_y + screen_height {
+            // place the match a third of the way down the screen
+            self.scroll_y = row.saturating_sub(3 + screen_height.saturating_sub(3) / 3);
+            self.has_been_scrolled = true;
+        }
+    }
+    /// Scrolls the innermost scrollable element at the given screen position horizontally.
+    /// If there is none there, the topmost one on screen is scrolled instead.
+    ///
+    /// Returns whether anything was scrolled.
+    fn scroll_horizontally(&mut self, column: u16, row: u16, amount: i32) -> bool {
+        let Some(cached) = &self.cached_draw else {
+            return false;
+        };
+        let screen_clips = cached.screen_clips(self.scroll_y, &self.scroll_x);
+        let scrollable = |index: &usize| {
+            cached.clips[*index].info.scrollable && screen_clips[*index].is_some_and(|c| c.y0 >= 3)
+        };
+        let Some(index) = (0..screen_clips.len())
+            .rev()
+            .filter(scrollable)
+            .find(|i| {
+                screen_clips[*i]
+                    .unwrap()
+                    .contains(column as i32, row as i32)
+            })
+            .or_else(|| {
+                (0..screen_clips.len())
+                    .filter(scrollable)
+                    .min_by_key(|i| screen_clips[*i].unwrap().y0)
+            })
+        else {
+            return false;
+        };
+        let clip = &cached.clips[index];
+        let visible_width = actualize_actual(clip.width, &cached.unknown_sized_elements) / EM;
+        let max_scroll = (clip.info.content_width / EM).saturating_sub(visible_width);
+        let scroll = self.scroll_x.entry(index).or_default();
+        let new = (*scroll as i32 + amount).clamp(0, max_scroll as i32) as u16;
+        let changed = new != *scroll;
+        *scroll = new;
+        changed
+    }
 }
 struct Tab {
     history: Vec<Webpage>,
@@ -159,6 +451,43 @@ enum Display {
     Block,
     None,
 }
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum WhiteSpace {
+    Normal,
+    NoWrap,
+    Pre,
+    PreWrap,
+    PreLine,
+}
+impl WhiteSpace {
+    /// Whether runs of spaces and tabs should be kept as is
+    fn preserves_spaces(self) -> bool {
+        matches!(self, Self::Pre | Self::PreWrap)
+    }
+    /// Whether newlines in the source should break the line
+    fn preserves_newlines(self) -> bool {
+        matches!(self, Self::Pre | Self::PreWrap | Self::PreLine)
+    }
+    /// Whether text should wrap when it reaches the edge of its parent
+    fn wraps(self) -> bool {
+        matches!(self, Self::Normal | Self::PreWrap | Self::PreLine)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Overflow {
+    Visible,
+    Hidden,
+    /// Also used for `overflow: scroll`
+    Auto,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TextOverflow {
+    Clip,
+    Ellipsis,
+}
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum ActualMeasurement {
     Pixels(u16),
@@ -223,7 +552,7 @@ impl<T> NonInheritedField<T> {
 }
 use NonInheritedField::*;
 
-use crate::css::{MediaThemeSelector, parse_stylesheet};
+use crate::css::{MediaThemeSelector, parse_stylesheet, parse_user_stylesheet};
 
 #[derive(Clone, Copy, PartialEq)]
 enum TextPrefix {
@@ -239,10 +568,13 @@ struct ElementDrawContext {
     display: NonInheritedField<Display>,
     bold: bool,
     italics: bool,
-    respect_whitespace: bool,
+    white_space: Option<WhiteSpace>,
     width: NonInheritedField<Measurement>,
+    max_width: NonInheritedField<Measurement>,
     height: NonInheritedField<Measurement>,
     text_prefix: Option<TextPrefix>,
+    overflow: NonInheritedField<Overflow>,
+    text_overflow: NonInheritedField<TextOverflow>,
 }
 static DEFAULT_DRAW_CTX: ElementDrawContext = ElementDrawContext {
     text_align: None,
@@ -251,10 +583,13 @@ static DEFAULT_DRAW_CTX: ElementDrawContext = ElementDrawContext {
     display: Unset,
     bold: false,
     italics: false,
-    respect_whitespace: false,
+    white_space: None,
     width: Unset,
+    max_width: Unset,
     height: Unset,
     text_prefix: None,
+    overflow: Unset,
+    text_overflow: Unset,
 };
 impl ElementDrawContext {
     /// Merges this context with another, exclusively copying inherited fields
@@ -264,7 +599,7 @@ impl ElementDrawContext {
         self.text_prefix = other.text_prefix.or(self.text_prefix);
         self.bold |= other.bold;
         self.italics |= other.italics;
-        self.respect_whitespace |= other.respect_whitespace;
+        self.white_space = other.white_space.or(self.white_space);
     }
     /// Merges this context with another, copying all unset fields
     fn merge_all(&mut self, other: &ElementDrawContext) {
@@ -272,7 +607,10 @@ impl ElementDrawContext {
         self.display = other.display.set_or(self.display);
         self.height = other.height.set_or(self.height);
         self.width = other.width.set_or(self.width);
+        self.max_width = other.max_width.set_or(self.max_width);
         self.background_color = other.background_color.set_or(self.background_color);
+        self.overflow = other.overflow.set_or(self.overflow);
+        self.text_overflow = other.text_overflow.set_or(self.text_overflow);
     }
 }
 
@@ -286,6 +624,8 @@ enum StyleTargetType {
     Id(String, Option<String>),
     /// Target only active if theme matches
     Theme(MediaThemeSelector),
+    /// Target links that have been visited, with `:visited` (Optional selector it is attached to)
+    Visited(Option<Box<StyleTargetType>>),
 }
 
 impl StyleTargetType {
@@ -299,6 +639,9 @@ impl StyleTargetType {
                 info.id.as_ref().is_some_and(|i| i == id)
                     && ty.as_ref().is_none_or(|ty| ty == info.type_name)
             }
+            StyleTargetType::Visited(ty) => {
+                info.visited && ty.as_ref().is_none_or(|ty| ty.matches_one(info))
+            }
             StyleTargetType::Theme(_) => panic!("should be handled elsewhere!"),
         }
     }
@@ -314,15 +657,22 @@ struct ElementTargetInfo {
     type_name: &'static str,
     id: Option<String>,
     classes: Vec<String>,
+    /// Whether this is a link to a page in history
+    visited: bool,
 }
 impl StyleTarget {
     fn matches(&self, info: &[ElementTargetInfo], is_dark: bool) -> bool {
         let mut info = info.iter().rev();
         let mut types = self.types.iter().rev();
 
-        // the first (last) type should always be the media theme selector
-        let StyleTargetType::Theme(media_theme_selector) = types.next().unwrap() else {
-            panic!("media theme selector not present!");
+        // the first (last) type should be the media theme selector,
+        // unless the target was constructed outside of a stylesheet
+        let media_theme_selector = if let Some(StyleTargetType::Theme(selector)) = self.types.last()
+        {
+            types.next();
+            *selector
+        } else {
+            MediaThemeSelector::Unset
         };
         match media_theme_selector {
             MediaThemeSelector::Unset => {}
@@ -359,23 +709,50 @@ impl StyleTarget {
     }
 }
 
-fn refresh_style(page: &mut Webpage, assets: &HashMap<Url, DataEntry>) {
+fn refresh_style(
+    page: &mut Webpage,
+    assets: &HashMap<Url, DataEntry>,
+    user_stylesheet: &str,
+    blocker: &Blocker,
+) {
     let mut global_style = Vec::new();
     if let Some(root) = &page.root {
         let mut all_styles = String::new();
         get_all_styles(root, &mut all_styles, page.url.as_ref(), assets);
         parse_stylesheet(&all_styles, &mut global_style, MediaThemeSelector::Unset);
     }
+    // the user stylesheet is parsed last, so that its rules take precedence over the page's
+    let host = page.url.as_ref().and_then(|url| url.host_str());
+    parse_user_stylesheet(user_stylesheet, host, &mut global_style);
+    // and elements hidden by filter lists stay hidden
+    if let Some(root) = &page.root {
+        let css = blocker.cosmetic_css(root, page.url.as_ref());
+        parse_stylesheet(&css, &mut global_style, MediaThemeSelector::Unset);
+    }
     page.global_style = global_style;
 }
 
 #[derive(PartialEq, Clone)]
 enum DrawCall {
-    /// X, Y, W, H, Image Source Link
-    Image(u16, u16, ActualMeasurement, ActualMeasurement, Url),
-    /// X, Y, W, H, Color
-    Rect(u16, u16, ActualMeasurement, ActualMeasurement, style::Color),
-    /// X, Y, Text, DrawContext, Parent Width, Parent Interactable
+    /// X, Y, W, H, Image Source Link, Clip Index
+    Image(
+        u16,
+        u16,
+        ActualMeasurement,
+        ActualMeasurement,
+        Url,
+        Option<usize>,
+    ),
+    /// X, Y, W, H, Color, Clip Index
+    Rect(
+        u16,
+        u16,
+        ActualMeasurement,
+        ActualMeasurement,
+        style::Color,
+        Option<usize>,
+    ),
+    /// X, Y, Text, DrawContext, Parent Width, Parent Interactable, Clip Index
     Text(
         u16,
         u16,
@@ -383,26 +760,41 @@ enum DrawCall {
         ElementDrawContext,
         ActualMeasurement,
         Option<usize>,
+        Option<usize>,
     ),
-    /// X, Y, W, H, Interactable Index, Placeholder Text
+    /// X, Y, W, H, Interactable Index (None if disabled), Placeholder Text, Clip Index
     DrawInput(
         u16,
         u16,
         ActualMeasurement,
         ActualMeasurement,
-        usize,
+        Option<usize>,
         String,
+        Option<usize>,
     ),
     ClearColor(style::Color),
+    /// X, Y, W, H, Clip Index
+    ///
+    /// Marks the box of an element with `overflow` set. These are taken out of the draw calls once layout is done, see [CachedDraw::clips]
+    Clip(u16, u16, ActualMeasurement, ActualMeasurement, usize),
 }
 impl DrawCall {
+    fn clip(&self) -> Option<usize> {
+        match self {
+            DrawCall::Image(.., clip)
+            | DrawCall::Rect(.., clip)
+            | DrawCall::Text(.., clip)
+            | DrawCall::DrawInput(.., clip) => *clip,
+            DrawCall::ClearColor(_) | DrawCall::Clip(..) => None,
+        }
+    }
     fn order(&self) -> u8 {
         match self {
-            DrawCall::ClearColor(_) => 0,
-            DrawCall::Rect(_, _, _, _, _) => 1,
-            DrawCall::Image(_, _, _, _, _) => 2,
-            DrawCall::DrawInput(_, _, _, _, _, _) => 3,
-            DrawCall::Text(_, _, _, _, _, _) => 4,
+            DrawCall::ClearColor(_) | DrawCall::Clip(..) => 0,
+            DrawCall::Rect(..) => 1,
+            DrawCall::Image(..) => 2,
+            DrawCall::DrawInput(..) => 3,
+            DrawCall::Text(..) => 4,
         }
     }
 }
@@ -410,32 +802,423 @@ impl Debug for DrawCall {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DrawCall::ClearColor(color) => f.write_str(&format!("Clear({color:?})")),
-            DrawCall::DrawInput(x, y, w, h, _, _) => {
+            DrawCall::DrawInput(x, y, w, h, ..) => {
                 f.write_str(&format!("Input({x},{y},{w:?},{h:?})"))
             }
-            DrawCall::Image(x, y, w, h, source) => {
+            DrawCall::Image(x, y, w, h, source, _) => {
                 f.write_str(&format!("Image({x},{y},{w:?},{h:?},{source:?})"))
             }
-            DrawCall::Rect(x, y, w, h, c) => {
+            DrawCall::Rect(x, y, w, h, c, _) => {
                 f.write_str(&format!("Rect({x},{y},{w:?},{h:?},{c:?})"))
             }
-            DrawCall::Text(x, y, text, _, _, _) => f.write_str(&format!("Text({x},{y},'{text}')")),
+            DrawCall::Text(x, y, text, ..) => f.write_str(&format!("Text({x},{y},'{text}')")),
+            DrawCall::Clip(x, y, w, h, index) => {
+                f.write_str(&format!("Clip({x},{y},{w:?},{h:?},{index})"))
+            }
+        }
+    }
+}
+
+/// Info about an element with `overflow` set, that is known before the element has been positioned
+#[derive(Clone, Copy)]
+struct ClipInfo {
+    /// The clip of the closest clipping ancestor
+    parent: Option<usize>,
+    /// Whether the content can be scrolled horizontally (`overflow: auto/scroll`)
+    scrollable: bool,
+    /// Whether text cut off by this clip should end with an ellipsis
+    ellipsis: bool,
+    /// Width of the content inside the clip, in pixels
+    content_width: u16,
+}
+
+/// Box of an element with `overflow` set. Anything drawn inside it is cut off at its edges.
+#[derive(Clone, Copy)]
+struct ClipRect {
+    x: u16,
+    y: u16,
+    width: ActualMeasurement,
+    height: ActualMeasurement,
+    info: ClipInfo,
+}
+
+/// A [ClipRect] converted to screen space. Edges are exclusive at the end.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct ScreenClip {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+    /// How many columns content inside this clip is moved to the left by horizontal scrolling
+    shift_x: u16,
+    ellipsis: bool,
+}
+impl ScreenClip {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x0..self.x1).contains(&x) && (self.y0..self.y1).contains(&y)
+    }
+}
+
+/// A checkbox or radio button
+#[derive(Clone, Debug, PartialEq)]
+struct Checkable {
+    name: String,
+    value: String,
+    radio: bool,
+    checked: bool,
+    required: bool,
+}
+impl Checkable {
+    /// What the input is drawn as
+    fn marker(&self) -> &'static str {
+        match (self.radio, self.checked) {
+            (false, false) => "[ ]",
+            (false, true) => "[x]",
+            (true, false) => "( )",
+            (true, true) => "(•)",
         }
     }
 }
 
+/// Whether a form on a page may be sent to an action. toad:// actions change settings and files,
+/// so only toad:// pages may send forms to them
+fn toad_action_allowed(page: Option<&Url>, action: &Url) -> bool {
+    action.scheme() != "toad" || page.is_some_and(|f| f.scheme() == "toad")
+}
+
+/// The origin logins are saved for, if the page has one
+fn autofill_origin(url: Option<&Url>) -> Option<String> {
+    let origin = url?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// A submit button
+#[derive(Clone, Debug, PartialEq)]
+struct Submitter {
+    name: String,
+    value: String,
+    /// `formaction`, overriding the action of the form
+    action: Option<String>,
+    /// `formmethod`
+    method: Option<Method>,
+    /// `formenctype`
+    enctype: Option<String>,
+    /// `formnovalidate`
+    novalidate: bool,
+}
+
+/// A field of a form
+#[derive(Clone, Debug, PartialEq)]
+enum FormField {
+    /// Text field, by index
+    Text(usize),
+    /// Checkbox or radio button, by index
+    Check(usize),
+    /// Select, by index
+    Select(usize),
+    /// File input, by index
+    File(usize),
+    /// Submit button, by index
+    Submit(usize),
+}
+
+/// A text field of a form, or a hidden input
+#[derive(Clone, Default, Debug)]
+struct TextField {
+    name: String,
+    value: String,
+    /// Constraints of the field, or none for hidden inputs
+    constraints: Option<Constraints>,
+}
+
 #[derive(Clone, Default)]
 struct Form {
     action: String,
     method: Method,
-    text_fields: HashMap<String, String>,
+    enctype: String,
+    /// Whether the fields are sent without being validated
+    novalidate: bool,
+    /// Text fields, in the order they appear. Fields sharing a name are each sent
+    text_fields: Vec<TextField>,
+    checkables: Vec<Checkable>,
+    selects: Vec<Select>,
+    files: Vec<FileInput>,
+    submitters: Vec<Submitter>,
+    /// Every field, in the order they appear
+    fields: Vec<FormField>,
+}
+impl Form {
+    fn constraints(&self, field: usize) -> Option<&Constraints> {
+        self.text_fields
+            .get(field)
+            .and_then(|f| f.constraints.as_ref())
+    }
+    /// The value of the first text field with a name
+    fn text(&self, name: &str) -> Option<&str> {
+        self.text_fields
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| f.value.as_str())
+    }
+    /// Checks the constraints of every field, returning the first that isn't met and why
+    fn validate(&self) -> Result<(), (FormField, String)> {
+        for field in self.fields.iter() {
+            let message = match field {
+                FormField::Text(index) => {
+                    let field = &self.text_fields[*index];
+                    let constraints = field.constraints.as_ref();
+                    constraints.and_then(|f| f.check(&field.value).err())
+                }
+                FormField::Check(index) => {
+                    let checkable = &self.checkables[*index];
+                    if !checkable.required || checkable.checked {
+                        None
+                    } else if !checkable.radio {
+                        Some(String::from("Check this box to continue."))
+                    } else if !self
+                        .checkables
+                        .iter()
+                        .any(|f| f.radio && f.name == checkable.name && f.checked)
+                    {
+                        Some(String::from("Select one of these options."))
+                    } else {
+                        None
+                    }
+                }
+                FormField::Select(index) => self.selects[*index]
+                    .is_missing()
+                    .then(|| String::from("Select an item in the list.")),
+                FormField::File(index) => self.files[*index]
+                    .is_missing()
+                    .then(|| String::from("Select a file.")),
+                FormField::Submit(_) => None,
+            };
+            if let Some(message) = message {
+                return Err((field.clone(), message));
+            }
+        }
+        Ok(())
+    }
+    /// Takes the values filled into the fields of an earlier draw of the same form
+    fn keep_values(&mut self, old: &Form) {
+        for (field, old) in self.text_fields.iter_mut().zip(old.text_fields.iter()) {
+            if field.name == old.name {
+                field.value = old.value.clone();
+            }
+        }
+        for (checkable, old) in self.checkables.iter_mut().zip(old.checkables.iter()) {
+            if checkable.name == old.name {
+                checkable.checked = old.checked;
+            }
+        }
+        for (select, old) in self.selects.iter_mut().zip(old.selects.iter()) {
+            if select.name == old.name && select.options.len() == old.options.len() {
+                select.options = old.options.clone();
+            }
+        }
+        for (input, old) in self.files.iter_mut().zip(old.files.iter()) {
+            if input.name == old.name {
+                input.files = old.files.clone();
+            }
+        }
+    }
+    /// Adds a text field with its initial value, returning its index
+    fn add_text_field(
+        &mut self,
+        name: String,
+        value: String,
+        constraints: Option<Constraints>,
+    ) -> usize {
+        self.text_fields.push(TextField {
+            name,
+            value,
+            constraints,
+        });
+        self.fields
+            .push(FormField::Text(self.text_fields.len() - 1));
+        self.text_fields.len() - 1
+    }
+    /// Adds a checkbox or radio button, returning its index
+    fn add_checkable(&mut self, checkable: Checkable) -> usize {
+        if checkable.radio && checkable.checked {
+            self.uncheck_group(&checkable.name);
+        }
+        self.checkables.push(checkable);
+        self.fields
+            .push(FormField::Check(self.checkables.len() - 1));
+        self.checkables.len() - 1
+    }
+    fn add_select(&mut self, select: Select) -> usize {
+        self.selects.push(select);
+        self.fields.push(FormField::Select(self.selects.len() - 1));
+        self.selects.len() - 1
+    }
+    fn add_file_input(&mut self, input: FileInput) -> usize {
+        self.files.push(input);
+        self.fields.push(FormField::File(self.files.len() - 1));
+        self.files.len() - 1
+    }
+    fn add_submitter(&mut self, submitter: Submitter) -> usize {
+        self.submitters.push(submitter);
+        self.fields
+            .push(FormField::Submit(self.submitters.len() - 1));
+        self.submitters.len() - 1
+    }
+    fn uncheck_group(&mut self, name: &str) {
+        for other in self.checkables.iter_mut() {
+            if other.radio && other.name == name {
+                other.checked = false;
+            }
+        }
+    }
+    /// Toggles a checkbox, or checks a radio button and unchecks the rest of its group
+    fn toggle(&mut self, index: usize) {
+        let Some(checkable) = self.checkables.get(index) else {
+            return;
+        };
+        if checkable.radio {
+            // radio buttons without a name aren't part of any group
+            if !checkable.name.is_empty() {
+                let name = checkable.name.clone();
+                self.uncheck_group(&name);
+            }
+            self.checkables[index].checked = true;
+        } else {
+            self.checkables[index].checked ^= true;
+        }
+    }
+    /// The names and values this form submits, in order, including those of the button it's submitted with
+    fn data(&self, submitter: Option<usize>) -> Vec<(String, FormValue)> {
+        let mut data = Vec::new();
+        for field in self.fields.iter() {
+            match field {
+                FormField::Text(index) => {
+                    let field = &self.text_fields[*index];
+                    data.push((field.name.clone(), FormValue::Text(field.value.clone())));
+                }
+                FormField::Check(index) => {
+                    let checkable = &self.checkables[*index];
+                    if checkable.checked && !checkable.name.is_empty() {
+                        data.push((
+                            checkable.name.clone(),
+                            FormValue::Text(checkable.value.clone()),
+                        ));
+                    }
+                }
+                FormField::Select(index) => data.extend(
+                    self.selects[*index]
+                        .data()
+                        .into_iter()
+                        .map(|(name, value)| (name, FormValue::Text(value))),
+                ),
+                FormField::File(index) => data.extend(self.files[*index].data()),
+                FormField::Submit(index) => {
+                    let button = &self.submitters[*index];
+                    if submitter == Some(*index) && !button.name.is_empty() {
+                        data.push((button.name.clone(), FormValue::Text(button.value.clone())));
+                    }
+                }
+            }
+        }
+        data
+    }
+    /// Whether the form can be sent by pressing enter in a text field without a submit button
+    fn submits_implicitly(&self) -> bool {
+        // only forms with a single text field can
+        let text_fields = self
+            .text_fields
+            .iter()
+            .filter_map(|f| f.constraints.as_ref())
+            .filter(|constraints| constraints.kind != "textarea")
+            .count();
+        text_fields == 1
+    }
+    fn has_password(&self) -> bool {
+        self.text_fields
+            .iter()
+            .any(|f| f.constraints.as_ref().is_some_and(Constraints::masked))
+    }
+    /// The filled in text fields of a form with a password, to be saved for autofill
+    fn login(&self, origin: String) -> Option<AutofillRecord> {
+        let mut fields = Vec::new();
+        let mut user = String::new();
+        let mut has_password = false;
+        for field in self.text_fields.iter() {
+            let Some(constraints) = &field.constraints else {
+                continue;
+            };
+            if constraints.kind == "textarea" || field.value.is_empty() {
+                continue;
+            }
+            if constraints.masked() {
+                has_password = true;
+            } else if user.is_empty() {
+                user = field.value.clone();
+            }
+            fields.push((field.name.clone(), field.value.clone()));
+        }
+        has_password.then(|| AutofillRecord::new(origin, user, fields))
+    }
+    /// Whether the submission should skip validation
+    fn skips_validation(&self, submitter: Option<usize>) -> bool {
+        self.novalidate
+            || submitter
+                .and_then(|f| self.submitters.get(f))
+                .is_some_and(|f| f.novalidate)
+    }
+    /// The URL the form is sent to, before the entries are added
+    fn action_url(&self, submitter: Option<usize>, base: Option<&Url>) -> Option<Url> {
+        let submitter = submitter.and_then(|f| self.submitters.get(f));
+        let action = submitter
+            .and_then(|f| f.action.as_ref())
+            .unwrap_or(&self.action);
+        Url::options().base_url(base).parse(action).ok()
+    }
+    fn submission(&self, submitter: Option<usize>, url: Url) -> FormSubmission {
+        let button = submitter.and_then(|f| self.submitters.get(f));
+        let method = button
+            .and_then(|f| f.method.clone())
+            .unwrap_or(self.method.clone());
+        let enctype = button
+            .and_then(|f| f.enctype.as_ref())
+            .unwrap_or(&self.enctype);
+        FormSubmission::new(method, url, Encoding::parse(enctype), self.data(submitter))
+    }
 }
 
 #[derive(Clone, PartialEq)]
 enum Interactable {
     Link(String),
-    InputText(usize, String, u16, Option<(u16, u16)>),
-    InputSubmit(usize),
+    /// Form index, text field index, width, screen position
+    InputText(usize, usize, u16, Option<(u16, u16)>),
+    /// Form index, submit button index
+    InputSubmit(usize, usize),
+    /// Form index, checkable index
+    InputCheck(usize, usize),
+    /// Form index, select index
+    InputSelect(usize, usize),
+    /// Form index, file input index
+    InputFile(usize, usize),
+    /// Form index, text field index, columns and rows of text, screen position
+    InputTextArea(usize, usize, (u16, u16), Option<(u16, u16)>),
+}
+impl Interactable {
+    /// Whether this is the control of a field in a form
+    fn is_field(&self, form: usize, field: &FormField) -> bool {
+        match (self, field) {
+            (
+                Interactable::InputText(f, index, ..) | Interactable::InputTextArea(f, index, ..),
+                FormField::Text(other),
+            ) => *f == form && index == other,
+            (Interactable::InputCheck(f, index), FormField::Check(other))
+            | (Interactable::InputSelect(f, index), FormField::Select(other))
+            | (Interactable::InputFile(f, index), FormField::File(other))
+            | (Interactable::InputSubmit(f, index), FormField::Submit(other)) => {
+                *f == form && index == other
+            }
+            _ => false,
+        }
+    }
 }
 struct GlobalDrawContext<'a> {
     /// The global CSS stylesheet
@@ -445,9 +1228,15 @@ struct GlobalDrawContext<'a> {
     /// Keeps track of interactable elements
     interactables: Vec<Interactable>,
     forms: Vec<Form>,
+    /// The first interactable with `autofocus` set
+    autofocus: Option<usize>,
+    /// Elements with `overflow` set, see [ClipInfo]
+    clips: Vec<ClipInfo>,
     /// Known sizes of images
     cached_image_sizes: HashMap<Url, (u16, u16)>,
     base_url: &'a Option<Url>,
+    /// URLs in history, for `:visited`
+    visited: HashSet<String>,
     is_dark: bool,
     use_css: bool,
 }
@@ -469,6 +1258,7 @@ fn draw_page(
     settings: ToadSettings,
     url: Option<Url>,
     global_style: Vec<(StyleTarget, ElementDrawContext)>,
+    visited: HashSet<String>,
 ) -> (CachedDraw, Option<u16>) {
     let mut scroll_to = None;
     let (screen_width, screen_height) = screen_size;
@@ -478,8 +1268,11 @@ fn draw_page(
         global_style: &global_style,
         interactables: Vec::new(),
         forms: Vec::new(),
+        autofocus: None,
+        clips: Vec::new(),
         cached_image_sizes,
         base_url: &url,
+        visited,
         is_dark: settings.theme.is_dark,
         use_css: settings.css_enabled,
     };
@@ -496,6 +1289,36 @@ fn draw_page(
         scroll_to = Some(y / LH - 3);
     }
 
+    // take out the positions of clips, now that they are known
+    let mut clips: Vec<ClipRect> = global_ctx
+        .clips
+        .iter()
+        .map(|info| ClipRect {
+            x: 0,
+            y: 0,
+            width: ActualMeasurement::Pixels(0),
+            height: ActualMeasurement::Pixels(0),
+            info: *info,
+        })
+        .collect();
+    draw_data.draw_calls.retain(|call| {
+        if let DrawCall::Clip(x, y, w, h, index) = call {
+            clips[*index] = ClipRect {
+                x: *x,
+                y: *y,
+                width: *w,
+                height: *h,
+                info: global_ctx.clips[*index],
+            };
+            false
+        } else {
+            true
+        }
+    });
+
+    let mut boxes = draw_data.boxes;
+    boxes.sort_by_key(|f| f.y);
+
     // sort draw calls such that rect calls are drawn first
     draw_data.draw_calls.sort_by_key(|a| a.order());
     // reverse because vecs are LIFO
@@ -506,6 +1329,11 @@ fn draw_page(
         content_height: draw_data.content_height,
         interactables: global_ctx.interactables,
         forms: global_ctx.forms,
+        clips,
+        boxes,
+        autofocus: global_ctx.autofocus,
+        invalid: None,
+        root: Some(root),
     };
     (draws, scroll_to)
 }
@@ -520,20 +1348,6 @@ struct WebpageDebugInfo {
     redirect_to: Option<String>,
     element_count: usize,
 }
-impl Debug for WebpageDebugInfo {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut log = String::new();
-        for item in self.info_log.iter() {
-            log += &format!("-{:?}\n", item);
-        }
-        write!(
-            f,
-            "Info Log: \n\n{log}\n\nUnknown elements: {:?}\n\nRedirect to: {:?}\n\nElement count: {}",
-            self.unknown_elements, self.redirect_to, self.element_count
-        )
-    }
-}
-
 fn actualize_actual(
     a: ActualMeasurement,
     unknown_sized_elements: &Vec<Option<ActualMeasurement>>,
@@ -554,7 +1368,8 @@ fn actualize_actual(
     }
 }
 
-const DEBUG_PAGE: &str = include_str!("debug.html");
+const USER_STYLESHEET_TEMPLATE: &str = include_str!("user.css");
+const KEYBINDS_TEMPLATE: &str = include_str!("keys.conf");
 
 fn parse_base64_url(url: &Url) -> Option<Vec<u8>> {
     if url.scheme() == "data"
@@ -567,58 +1382,106 @@ fn parse_base64_url(url: &Url) -> Option<Vec<u8>> {
     }
 }
 
-async fn get_data(url: Url, ty: DataType, client: Client) -> Option<DataEntry> {
+async fn get_data(url: Url, ty: DataType, client: Client) -> (Option<DataEntry>, ResponseInfo) {
     if let DataType::Image = ty
         && let Some(data) = parse_base64_url(&url)
     {
-        let image = image::load_from_memory(&data).ok()?;
-        return Some(DataEntry::Image(image));
+        let mut info = ResponseInfo::local(data.len());
+        let image = match image::load_from_memory(&data) {
+            Ok(image) => Some(DataEntry::Image(image)),
+            Err(e) => info.fail(format!("couldn't decode image: {e}")),
+        };
+        return (image, info);
     }
 
-    let resp = client.get(url).send().await.ok()?;
-    match ty {
-        DataType::Image => {
-            let bytes: Vec<u8> = resp.bytes().await.ok().map(|f| f.into())?;
-            let image = image::load_from_memory(&bytes).ok()?;
-            Some(DataEntry::Image(image))
-        }
-        DataType::PlainText => {
-            let text: String = resp.text().await.ok()?;
-            Some(DataEntry::PlainText(text))
-        }
-    }
+    let mut info = ResponseInfo::default();
+    let Some(resp) = send(client.get(url), &mut info).await else {
+        return (None, info);
+    };
+    let data = match ty {
+        DataType::Image => match read_bytes(resp, &mut info).await {
+            Some(bytes) => match image::load_from_memory(&bytes) {
+                Ok(image) => Some(DataEntry::Image(image)),
+                Err(e) => info.fail(format!("couldn't decode image: {e}")),
+            },
+            None => None,
+        },
+        DataType::PlainText => read_text(resp, &mut info).await.map(DataEntry::PlainText),
+    };
+    (data, info)
 }
 
-async fn get_page(client: Client, url: Url) -> Option<DataEntry> {
-    let response = client.get(url.clone()).send().await.ok()?;
-    let data = response.text().await.ok()?;
-    let mut page = parse_html(&data)?;
+/// Parses a fetched page, keeping track of whether it could be
+fn parse_fetched_page(
+    data: Option<String>,
+    url: Url,
+    info: &mut ResponseInfo,
+) -> Option<DataEntry> {
+    let Some(mut page) = parse_html(&data?) else {
+        return info.fail("couldn't parse page");
+    };
     page.url = Some(url);
     Some(DataEntry::Webpage(Box::new(page)))
 }
-async fn get_page_with_form(client: Client, url: Url, form: Form) -> Option<DataEntry> {
-    let Ok(response) = client
-        .request(form.method, url.clone())
-        .form(&form.text_fields)
-        .send()
-        .await
-    else {
-        return None;
+async fn get_page(client: Client, url: Url) -> (Option<DataEntry>, ResponseInfo) {
+    let mut info = ResponseInfo::default();
+    let data = match send(client.get(url.clone()), &mut info).await {
+        Some(response) => read_text(response, &mut info).await,
+        None => None,
     };
-    let Ok(data) = response.text().await else {
-        return None;
+    let page = parse_fetched_page(data, url, &mut info);
+    (page, info)
+}
+/// Fetches the page a `view-source:` URL points to, and shows its source
+async fn get_page_source(client: Client, url: Url) -> (Option<DataEntry>, ResponseInfo) {
+    let mut info = ResponseInfo::default();
+    let Some(inner) = view_source_target(&url) else {
+        return (info.fail("invalid view-source URL"), info);
     };
-    let mut page = parse_html(&data)?;
-    page.url = Some(url);
-    Some(DataEntry::Webpage(Box::new(page)))
+    let data = match send(client.get(inner), &mut info).await {
+        Some(response) => read_text(response, &mut info).await,
+        None => None,
+    };
+    let page = data
+        .and_then(|data| Webpage::view_source(url, &data))
+        .map(|page| DataEntry::Webpage(Box::new(page)));
+    (page, info)
+}
+/// The URL a `view-source:` URL shows the source of
+fn view_source_target(url: &Url) -> Option<Url> {
+    Url::parse(url.as_str().strip_prefix("view-source:")?).ok()
+}
+async fn get_page_with_form(
+    client: Client,
+    submission: FormSubmission,
+) -> (Option<DataEntry>, ResponseInfo) {
+    let mut info = ResponseInfo::default();
+    let request = match submission.request(&client) {
+        Ok(request) => request,
+        Err(e) => return (info.fail(e), info),
+    };
+    let data = match send(request, &mut info).await {
+        Some(response) => read_text(response, &mut info).await,
+        None => None,
+    };
+    let page = parse_fetched_page(data, submission.url, &mut info);
+    (page, info)
 }
 
 enum PageDrawFuture {
     Handle(JoinHandle<(CachedDraw, Option<u16>)>),
-    Immediate((CachedDraw, Option<u16>)),
+    Immediate(Box<(CachedDraw, Option<u16>)>),
 }
 
-type FetchFuture = JoinHandle<Option<DataEntry>>;
+type FetchFuture = JoinHandle<(Option<DataEntry>, ResponseInfo)>;
+
+/// A request in progress
+struct Fetch {
+    /// Identifier of the page the request was made for
+    page_id: usize,
+    request: NetworkRequest,
+    handle: FetchFuture,
+}
 
 #[derive(Default)]
 struct Toad {
@@ -626,16 +1489,50 @@ struct Toad {
     tab_index: usize,
     client: Client,
     fetched_assets: HashMap<Url, DataEntry>,
-    fetches: Vec<(usize, Url, FetchFuture)>,
+    fetches: Vec<Fetch>,
     draw_threads: HashMap<usize, Option<PageDrawFuture>>,
     current_page_id: usize,
     cached_resized_images: Vec<(Url, u16, u16, image::DynamicImage)>,
     prev_buffer: Option<Buffer>,
     current_input_box: Option<InputBox>,
+    /// Editor of the `<textarea>` being filled in
+    current_text_area: Option<TextArea>,
+    /// Rows of URL bar suggestions drawn last frame
+    suggestion_rows: u16,
+    hint_mode: Option<HintMode>,
+    /// Element inspector panel, open on the current page
+    inspector: Option<Inspector>,
+    /// Open option list of a `<select>`
+    select_popup: Option<SelectPopup>,
+    /// Open picker of an `<input type="file">`
+    file_picker: Option<FilePicker>,
+    /// Saved form values, once unlocked with the master passphrase
+    autofill: Option<AutofillStore>,
+    /// Whether unlocking autofill was cancelled, so it isn't asked again
+    autofill_declined: bool,
+    /// Login sent from the last form with a password, that can be saved for autofill
+    pending_login: Option<AutofillRecord>,
+    /// Filter lists, blocking requests and hiding elements
+    blocker: Blocker,
+    keybinds: Keybinds,
+    bookmarks: Bookmarks,
+    history: History,
+    /// Name of the session open tabs are saved to
+    session_name: String,
+    last_session_save: Option<Instant>,
+    /// Whether quit was pressed once, and needs to be pressed again to exit
+    quit_pending: bool,
+    /// Message shown in the status line for a moment, like "copied"
+    status_message: Option<(String, Instant)>,
     last_mouse_x: u16,
     last_mouse_y: u16,
     dragging_scrollbar: bool,
     settings: ToadSettings,
+    /// Contents of the user stylesheet
+    user_stylesheet: String,
+    /// Modification time of the user stylesheet when it was last loaded
+    user_stylesheet_modified: Option<SystemTime>,
+    last_user_stylesheet_check: Option<Instant>,
 }
 impl Toad {
     fn new() -> Result<Self, reqwest::Error> {
@@ -648,37 +1545,85 @@ impl Toad {
         Ok(Self {
             client,
             settings: load_settings(),
+            bookmarks: load_bookmarks(),
+            history: load_history(),
+            blocker: load_blocker(),
             ..Default::default()
         })
     }
+    /// Starts a request for the page with `page_id`, which is logged to the page once it finishes
+    fn fetch(
+        &mut self,
+        page_id: usize,
+        kind: RequestKind,
+        method: Method,
+        url: Url,
+        future: impl Future<Output = (Option<DataEntry>, ResponseInfo)> + Send + 'static,
+    ) {
+        self.fetches.push(Fetch {
+            page_id,
+            request: NetworkRequest::new(kind, method, url),
+            handle: tokio::spawn(future),
+        });
+    }
     async fn handle_new_page(&mut self, page: &mut Webpage) {
         let url = page.url.as_ref().cloned();
         let options = Url::options().base_url(url.as_ref());
         if let Some(redirect) = &page.debug_info.redirect_to
             && let Ok(url) = options.parse(redirect)
         {
-            let handle = tokio::spawn(get_page(self.client.clone(), url.clone()));
-            self.fetches
-                .push((self.current_page_id, url.clone(), handle));
+            if self
+                .blocker
+                .should_block(&url, page.url.as_ref(), RequestKind::Redirect)
+            {
+                page.block_request(RequestKind::Redirect, url);
+            } else {
+                let future = get_page(self.client.clone(), url.clone());
+                self.fetch(
+                    self.current_page_id,
+                    RequestKind::Redirect,
+                    Method::GET,
+                    url,
+                    future,
+                );
+            }
         }
 
-        refresh_style(page, &self.fetched_assets);
-        if let Some(url) = &url {
-            self.settings.history.push_back(url.to_string());
-            if self.settings.history.len() >= 100 {
-                self.settings.history.pop_front();
-            }
-            write_settings(&self.settings);
+        refresh_style(
+            page,
+            &self.fetched_assets,
+            &self.user_stylesheet,
+            &self.blocker,
+        );
+        if let Some(url) = &url
+            && !page.loading
+            && !page.reader
+            && !matches!(url.scheme(), "toad" | "view-source")
+        {
+            let visit = self.history.visit(
+                url.as_str(),
+                &page.title.clone().unwrap_or_default(),
+                unix_now(),
+            );
+            append_history_visit(&visit);
         }
         page.indentifier = self.current_page_id;
         self.current_page_id += 1;
-        for (ty, source) in page.debug_info.fetch_queue.drain(..) {
+        for (ty, source) in std::mem::take(&mut page.debug_info.fetch_queue) {
             let Ok(url) = options.parse(&source) else {
                 continue;
             };
             if !self.fetched_assets.contains_key(&url) {
-                let handle = tokio::spawn(get_data(url.clone(), ty, self.client.clone()));
-                self.fetches.push((page.indentifier, url, handle));
+                let kind = match ty {
+                    DataType::Image => RequestKind::Image,
+                    DataType::PlainText => RequestKind::Stylesheet,
+                };
+                if self.blocker.should_block(&url, page.url.as_ref(), kind) {
+                    page.block_request(kind, url);
+                    continue;
+                }
+                let future = get_data(url.clone(), ty, self.client.clone());
+                self.fetch(page.indentifier, kind, Method::GET, url, future);
             }
         }
         self.draw_threads
@@ -692,23 +1637,33 @@ impl Toad {
 
         let url = page.url.clone();
         let global_style = page.global_style.clone();
+        let visited = self.history.visited_urls();
         if let Some(root) = &page.root {
             let arc = Arc::clone(root);
             if page.debug_info.element_count > 100 {
                 let handle = tokio::task::spawn_blocking(move || {
-                    draw_page(arc, cached_image_size, size, settings, url, global_style)
+                    draw_page(
+                        arc,
+                        cached_image_size,
+                        size,
+                        settings,
+                        url,
+                        global_style,
+                        visited,
+                    )
                 });
 
                 Some(PageDrawFuture::Handle(handle))
             } else {
-                Some(PageDrawFuture::Immediate(draw_page(
+                Some(PageDrawFuture::Immediate(Box::new(draw_page(
                     arc,
                     cached_image_size,
                     size,
                     settings,
                     url,
                     global_style,
-                )))
+                    visited,
+                ))))
             }
         } else {
             None
@@ -733,10 +1688,29 @@ impl Toad {
     }
     fn get_url_bar_autocompletions(&self) -> Vec<String> {
         let mut vec = vec![String::from("https://"), String::from("toad://")];
-        vec.append(&mut self.settings.history.clone().into());
-        vec.sort_by_key(String::len);
+        // bookmarks are suggested before history
+        let mut bookmarks: Vec<String> =
+            self.bookmarks.items.iter().map(|f| f.url.clone()).collect();
+        bookmarks.sort_by_key(String::len);
+        let mut history = self.history.ranked_urls(unix_now());
+        vec.append(&mut bookmarks);
+        vec.append(&mut history);
         vec
     }
+    /// Open tabs, bookmarks and history, for the dropdown of the URL bar
+    fn get_url_bar_suggestions(&self) -> Vec<Suggestion> {
+        let tabs = self
+            .tabs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, f)| {
+                let page = f.page();
+                let url = page.url.as_ref()?.to_string();
+                Some((index, url, page.title.clone().unwrap_or_default()))
+            })
+            .collect();
+        collect_suggestions(tabs, &self.bookmarks, &self.history, unix_now())
+    }
     async fn interact(
         &mut self,
         stdout: &Stdout,
@@ -755,11 +1729,23 @@ impl Toad {
                 let Ok(url) = options.parse(path) else {
                     return Ok(());
                 };
-                let handle = tokio::spawn(get_page(self.client.clone(), url.clone()));
-                self.fetches
-                    .push((self.current_page_id, url.clone(), handle));
-                let mut page = parse_html(include_str!("loading.html")).unwrap();
-                page.url = Some(url);
+                let page =
+                    if self
+                        .blocker
+                        .should_block(&url, tab.url.as_ref(), RequestKind::Document)
+                    {
+                        Webpage::blocked(url)
+                    } else {
+                        let future = get_page(self.client.clone(), url.clone());
+                        self.fetch(
+                            self.current_page_id,
+                            RequestKind::Document,
+                            Method::GET,
+                            url.clone(),
+                            future,
+                        );
+                        Webpage::loading(url)
+                    };
                 if control_held {
                     self.open_page_new_tab(page).await;
                 } else {
@@ -768,55 +1754,499 @@ impl Toad {
 
                 self.draw(stdout, screen_size)?;
             }
-            Interactable::InputText(index, name, width, pos) => {
+            Interactable::InputText(index, field, width, pos) => {
                 let Some(cached) = &mut tab.cached_draw else {
                     return Ok(());
                 };
                 let Some((x, y)) = pos else {
                     return Ok(());
                 };
-                self.current_input_box = Some(InputBox::new(
-                    x + 1,
-                    y + 1,
-                    *width,
-                    InputBoxSubmitTarget::SetFormTextField(*index, name.clone()),
-                    cached.forms[*index].text_fields.get(name).cloned(),
-                    Vec::new(),
-                ));
+                let form = &cached.forms[*index];
+                let name = &form.text_fields[*field].name;
+                let constraints = form.constraints(*field).cloned().unwrap_or_default();
+                if constraints.readonly {
+                    self.status_message =
+                        Some((String::from("this field is read-only"), Instant::now()));
+                    self.draw(stdout, screen_size)?;
+                    return Ok(());
+                }
+                let origin = autofill_origin(tab.url.as_ref());
+                // logins are filled in password fields, and the other fields of their form
+                if (constraints.masked() || form.has_password())
+                    && origin.is_some()
+                    && self.autofill.is_none()
+                    && !self.autofill_declined
+                    && autofill_path().is_some_and(|f| f.exists())
+                {
+                    self.ask_passphrase(UnlockReason::Fill, screen_size);
+                    self.draw(stdout, screen_size)?;
+                    return Ok(());
+                }
+                let fills = match (&self.autofill, &origin) {
+                    (Some(store), Some(origin)) => store
+                        .autofill
+                        .fills(origin, name)
+                        .into_iter()
+                        .map(|index| {
+                            let record = &store.autofill.records[index];
+                            Suggestion {
+                                url: record.get(name).unwrap_or_default().to_string(),
+                                title: record.user.clone(),
+                                kind: SuggestionKind::Autofill(index),
+                                frecency: 0,
+                            }
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                self.current_input_box = Some(
+                    InputBox::new(
+                        x + 1,
+                        y + 1,
+                        *width,
+                        InputBoxSubmitTarget::SetFormTextField(*index, *field),
+                        Some(form.text_fields[*field].value.clone()),
+                        Vec::new(),
+                    )
+                    .masked(constraints.masked())
+                    .with_max_length(constraints.max_length)
+                    .with_fills(fills),
+                );
                 self.prev_buffer = None;
                 self.draw(stdout, screen_size)?;
             }
-            Interactable::InputSubmit(index) => {
-                let Some(mut cached) = tab.cached_draw.take() else {
+            Interactable::InputTextArea(index, field, (width, height), pos) => {
+                let Some(cached) = &mut tab.cached_draw else {
                     return Ok(());
                 };
-
-                let options = Url::options().base_url(tab.url.as_ref());
-                let a = cached.forms.remove(*index);
-                let Ok(url) = options.parse(&a.action) else {
+                let Some((x, y)) = pos else {
                     return Ok(());
                 };
-
-                if self.handle_toad_settings(&url) {
+                let form = &cached.forms[*index];
+                let constraints = form.constraints(*field).cloned().unwrap_or_default();
+                if constraints.readonly {
+                    self.status_message =
+                        Some((String::from("this field is read-only"), Instant::now()));
                     self.draw(stdout, screen_size)?;
                     return Ok(());
                 }
-
-                let handle = tokio::spawn(get_page_with_form(self.client.clone(), url.clone(), a));
-                self.fetches
-                    .push((self.current_page_id, url.clone(), handle));
-                let mut page = parse_html(include_str!("loading.html")).unwrap();
-                page.url = Some(url);
-                self.open_page(page, self.tab_index).await;
+                self.current_text_area = Some(
+                    TextArea::new(
+                        x + 1,
+                        y + 1,
+                        *width,
+                        *height,
+                        *index,
+                        *field,
+                        form.text_fields[*field].value.clone(),
+                    )
+                    .with_max_length(constraints.max_length),
+                );
+                self.prev_buffer = None;
                 self.draw(stdout, screen_size)?;
             }
-        }
-
-        Ok(())
-    }
-    fn handle_toad_settings(&mut self, url: &Url) -> bool {
-        if url.scheme() == "toad" {
-            if let Some(mut segments) = url.path_segments()
+            Interactable::InputCheck(form, index) => {
+                let (form, index) = (*form, *index);
+                if let Some(cached) = &mut tab.cached_draw {
+                    cached.forms[form].toggle(index);
+                    cached.invalid = None;
+                }
+                self.draw(stdout, screen_size)?;
+            }
+            Interactable::InputSelect(form, index) => {
+                let (form, index) = (*form, *index);
+                // the list opens below where the select is on screen
+                let position = tab.tab_index.and_then(|interactable| {
+                    let buffer = self.prev_buffer.as_ref()?;
+                    buffer
+                        .interactable_positions(3)
+                        .into_iter()
+                        .find(|f| f.0 == interactable)
+                        .map(|f| (f.1, f.2))
+                });
+                if let Some((x, y)) = position
+                    && let Some(cached) = &tab.cached_draw
+                    && let Some(select) = cached.forms[form].selects.get(index)
+                {
+                    self.select_popup =
+                        Some(SelectPopup::new(tab.indentifier, form, index, select, x, y));
+                }
+                self.draw(stdout, screen_size)?;
+            }
+            Interactable::InputFile(form, index) => {
+                if let Some(cached) = &tab.cached_draw
+                    && let Some(input) = cached.forms[*form].files.get(*index)
+                {
+                    self.file_picker = Some(FilePicker::new(tab.indentifier, *form, *index, input));
+                }
+                self.draw(stdout, screen_size)?;
+            }
+            Interactable::InputSubmit(form, submitter) => {
+                let (form, submitter) = (*form, *submitter);
+                self.submit_form(form, Some(submitter), stdout, screen_size)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+    /// Enters link hint mode, labeling every interactable on screen
+    fn start_hint_mode(&mut self, action: HintAction) {
+        if let Some(buffer) = &self.prev_buffer {
+            let hint_mode = HintMode::new(action, buffer, 3);
+            if !hint_mode.hints.is_empty() {
+                self.hint_mode = Some(hint_mode);
+            }
+        }
+    }
+    /// Passes a key or click to the open select list
+    fn handle_select_input(&mut self, key: Option<event::KeyEvent>, click: (u16, u16)) {
+        let Some(mut popup) = self.select_popup.take() else {
+            return;
+        };
+        let Some(page) = self.tabs.get_mut(self.tab_index) else {
+            return;
+        };
+        if page.indentifier != popup.page_id {
+            return;
+        }
+        let Some(select) = page
+            .cached_draw
+            .as_mut()
+            .and_then(|f| f.forms.get_mut(popup.form))
+            .and_then(|f| f.selects.get_mut(popup.select))
+        else {
+            return;
+        };
+        let input = match key {
+            Some(key) => popup.on_key(key, select),
+            None => popup.on_click(click.0, click.1, select),
+        };
+        if let Some(cached) = &mut page.cached_draw {
+            cached.invalid = None;
+        }
+        if let SelectInput::Pending = input {
+            self.select_popup = Some(popup);
+        }
+    }
+    /// Passes a key or click to the open file picker, setting the files of its input once chosen
+    fn handle_file_picker_input(&mut self, key: Option<event::KeyEvent>, click: (u16, u16)) {
+        let Some(mut picker) = self.file_picker.take() else {
+            return;
+        };
+        let input = match key {
+            Some(key) => picker.on_key(key),
+            None => picker.on_click(click.0, click.1),
+        };
+        match input {
+            FilePickerInput::Pending => self.file_picker = Some(picker),
+            FilePickerInput::Cancel => {}
+            FilePickerInput::Choose(files) => {
+                if let Some(page) = self.tabs.get_mut(self.tab_index)
+                    && page.indentifier == picker.page_id
+                    && let Some(cached) = &mut page.cached_draw
+                    && let Some(input) = cached
+                        .forms
+                        .get_mut(picker.form)
+                        .and_then(|f| f.files.get_mut(picker.input))
+                {
+                    input.files = files;
+                    cached.invalid = None;
+                }
+            }
+        }
+    }
+    /// Passes a key to the inspector if it's open, returning whether it was used
+    fn handle_inspector_key(&mut self, key: event::KeyEvent, screen_size: (u16, u16)) -> bool {
+        let Some(inspector) = &mut self.inspector else {
+            return false;
+        };
+        match inspector.on_key(key) {
+            InspectorInput::Ignored => return false,
+            InspectorInput::Close => self.inspector = None,
+            InspectorInput::Changed => {
+                // scroll the selected element into view
+                if let Some(id) = inspector.selected_id()
+                    && let Some(page) = self.tabs.get_mut(self.tab_index)
+                    && let Some(cached) = &page.cached_draw
+                    && let Some(element_box) = cached.element_box(id)
+                {
+                    let y = element_box.y / LH;
+                    if y < page.scroll_y + 3 || y >= page.scroll_y + screen_size.1.saturating_sub(1)
+                    {
+                        page.scroll_y = y.saturating_sub(3);
+                    }
+                }
+            }
+        }
+        true
+    }
+    async fn handle_hint_key(
+        &mut self,
+        key: event::KeyEvent,
+        stdout: &Stdout,
+        screen_size: (u16, u16),
+    ) -> io::Result<()> {
+        let Some(hint_mode) = &mut self.hint_mode else {
+            return Ok(());
+        };
+        let input = match key.code {
+            event::KeyCode::Char(char) => hint_mode.on_char(char),
+            event::KeyCode::Backspace => {
+                hint_mode.on_backspace();
+                HintInput::Pending
+            }
+            _ => HintInput::Cancel,
+        };
+        match input {
+            HintInput::Pending => {}
+            HintInput::Cancel => {
+                self.hint_mode = None;
+            }
+            HintInput::Activate(index) => {
+                let action = self.hint_mode.take().unwrap().action;
+                let Some(page) = self.tabs.get_mut(self.tab_index) else {
+                    return Ok(());
+                };
+                // the page may have been redrawn with fewer interactables since the hints were shown
+                let Some(interactable) = page
+                    .cached_draw
+                    .as_ref()
+                    .and_then(|f| f.interactables.get(index))
+                    .cloned()
+                else {
+                    return self.draw(stdout, screen_size);
+                };
+                page.tab_index = Some(index);
+                page.hovered_interactable = Some(interactable.clone());
+                match action {
+                    HintAction::Follow => self.interact(stdout, false, screen_size).await?,
+                    HintAction::FollowNewTab => self.interact(stdout, true, screen_size).await?,
+                    HintAction::CopyUrl => {
+                        if let Interactable::Link(path) = &interactable
+                            && let Ok(url) = Url::options().base_url(page.url.as_ref()).parse(path)
+                        {
+                            copy_to_clipboard(stdout, url.as_str())?;
+                            self.show_status("copied");
+                        }
+                    }
+                }
+            }
+        }
+        self.draw(stdout, screen_size)
+    }
+    /// Sends a form of the current page, with the submit button it was sent with
+    async fn submit_form(
+        &mut self,
+        form: usize,
+        submitter: Option<usize>,
+        stdout: &Stdout,
+        screen_size: (u16, u16),
+    ) -> io::Result<()> {
+        let Some(tab) = self.tabs.get_mut(self.tab_index) else {
+            return Ok(());
+        };
+        // the form isn't sent until every field is valid
+        if let Some(cached) = &mut tab.cached_draw
+            && let Some(a) = cached.forms.get(form)
+            && !a.skips_validation(submitter)
+            && let Err((field, message)) = a.validate()
+        {
+            let invalid = cached
+                .interactables
+                .iter()
+                .position(|f| f.is_field(form, &field));
+            cached.invalid = invalid.map(|f| (f, message.clone()));
+            tab.tab_index = invalid.or(tab.tab_index);
+            self.status_message = Some((message, Instant::now()));
+            self.draw(stdout, screen_size)?;
+            return Ok(());
+        }
+        // refuse before the cached draw is taken so the page stays drawn
+        if let Some(cached) = &tab.cached_draw
+            && let Some(a) = cached.forms.get(form)
+            && let Some(url) = a.action_url(submitter, tab.url.as_ref())
+            && !toad_action_allowed(tab.url.as_ref(), &url)
+        {
+            self.show_status("only toad pages can send forms to toad://");
+            self.draw(stdout, screen_size)?;
+            return Ok(());
+        }
+        let Some(mut cached) = tab.cached_draw.take() else {
+            return Ok(());
+        };
+        if form >= cached.forms.len() {
+            return Ok(());
+        }
+        let a = cached.forms.remove(form);
+        let Some(url) = a.action_url(submitter, tab.url.as_ref()) else {
+            return Ok(());
+        };
+        let origin = autofill_origin(tab.url.as_ref());
+
+        if self.handle_toad_settings(&url, &a).await {
+            self.draw(stdout, screen_size)?;
+            return Ok(());
+        }
+
+        // offer to save logins that aren't saved yet
+        if let Some(login) = origin.and_then(|f| a.login(f))
+            && !self
+                .autofill
+                .as_ref()
+                .is_some_and(|f| f.autofill.contains(&login))
+        {
+            let key = self.keybinds.keys_for(Action::SaveLogin).next();
+            if let Some(key) = key.map(KeyBinding::name) {
+                self.show_status(&format!(
+                    "press {key} to save the login for {}",
+                    login.origin
+                ));
+            }
+            self.pending_login = Some(login);
+        }
+
+        let submission = a.submission(submitter, url);
+        let (method, url) = (submission.method.clone(), submission.url.clone());
+        let future = get_page_with_form(self.client.clone(), submission);
+        self.fetch(
+            self.current_page_id,
+            RequestKind::Form,
+            method,
+            url.clone(),
+            future,
+        );
+        let page = Webpage::loading(url);
+        self.open_page(page, self.tab_index).await;
+        self.draw(stdout, screen_size)
+    }
+    /// Opens a prompt for the master passphrase of autofill
+    fn ask_passphrase(&mut self, reason: UnlockReason, screen_size: (u16, u16)) {
+        self.open_passphrase_box(InputBoxSubmitTarget::UnlockAutofill(reason), screen_size);
+        self.show_status(if autofill_path().is_some_and(|f| f.exists()) {
+            "enter the master passphrase to unlock autofill"
+        } else {
+            "choose a master passphrase for autofill"
+        });
+    }
+    fn open_passphrase_box(&mut self, target: InputBoxSubmitTarget, screen_size: (u16, u16)) {
+        self.current_input_box = Some(
+            InputBox::new(
+                4 * 3,
+                1,
+                screen_size.0.saturating_sub(4 * 3 * 2),
+                target,
+                None,
+                Vec::new(),
+            )
+            .masked(true),
+        );
+    }
+    /// Unlocks autofill with a passphrase, or asks for it again with why it failed
+    fn unlock_autofill(
+        &mut self,
+        passphrase: &str,
+        reason: UnlockReason,
+        screen_size: (u16, u16),
+    ) -> bool {
+        match load_autofill(passphrase) {
+            Ok(store) => {
+                self.autofill = Some(store);
+                true
+            }
+            Err(error) => {
+                self.ask_passphrase(reason, screen_size);
+                self.show_status(&format!("{error}, try again"));
+                false
+            }
+        }
+    }
+    /// Goes on with what autofill was unlocked for, or without autofill if it wasn't
+    async fn finish_unlock(
+        &mut self,
+        reason: UnlockReason,
+        stdout: &Stdout,
+        screen_size: (u16, u16),
+    ) -> io::Result<()> {
+        match reason {
+            // go on to fill in the field
+            UnlockReason::Fill => self.interact(stdout, false, screen_size).await,
+            UnlockReason::Save => {
+                if self.autofill.is_some() {
+                    self.save_login(screen_size);
+                }
+                self.draw(stdout, screen_size)
+            }
+        }
+    }
+    /// Stops or resumes blocking content on the site of the current page
+    fn toggle_blocking(&mut self) {
+        let Some(page) = self.tabs.get_mut(self.tab_index) else {
+            return;
+        };
+        let Some(host) = page.url.as_ref().and_then(|f| f.host_str()) else {
+            self.show_status("nothing to block on this page");
+            return;
+        };
+        let blocking = self.blocker.toggle_allowlist(host);
+        write_allowlist(&self.blocker);
+        // fetch what was blocked, now that it's allowed
+        let mut unblocked = Vec::new();
+        if !blocking {
+            page.requests.retain(|f| {
+                let blocked = f.response.error.as_deref() == Some(BLOCKED_ERROR);
+                if blocked && f.kind != RequestKind::Redirect {
+                    unblocked.push((f.kind, f.url.clone()));
+                }
+                !blocked
+            });
+            page.blocked_requests = 0;
+        }
+        let page_id = page.indentifier;
+        for (kind, url) in unblocked {
+            let ty = match kind {
+                RequestKind::Image => DataType::Image,
+                _ => DataType::PlainText,
+            };
+            let future = get_data(url.clone(), ty, self.client.clone());
+            self.fetch(page_id, kind, Method::GET, url, future);
+        }
+        self.show_status(if blocking {
+            "blocking content on this site"
+        } else {
+            "stopped blocking content on this site"
+        });
+        // restyle, so cosmetic rules are applied or removed
+        if let Some(page) = self.tabs.get_mut(self.tab_index) {
+            refresh_style(
+                page,
+                &self.fetched_assets,
+                &self.user_stylesheet,
+                &self.blocker,
+            );
+        }
+        self.refresh_page(self.tab_index);
+    }
+    /// Saves the login last sent from a form, asking for the master passphrase first if autofill is locked
+    fn save_login(&mut self, screen_size: (u16, u16)) {
+        let Some(login) = self.pending_login.take() else {
+            self.show_status("no login to save");
+            return;
+        };
+        let Some(store) = &mut self.autofill else {
+            self.pending_login = Some(login);
+            self.ask_passphrase(UnlockReason::Save, screen_size);
+            return;
+        };
+        store.autofill.save(login);
+        match write_autofill(store) {
+            Ok(()) => self.show_status("login saved"),
+            Err(e) => self.show_status(e),
+        }
+    }
+    async fn handle_toad_settings(&mut self, url: &Url, form: &Form) -> bool {
+        if url.scheme() == "toad" {
+            if let Some(mut segments) = url.path_segments()
                 && let Some(last) = segments.next_back()
             {
                 match last {
@@ -838,6 +2268,172 @@ impl Toad {
                     "theme_dark" => {
                         self.settings.theme = &THEMES[1];
                     }
+                    "rename_bookmark" | "delete_bookmark" => {
+                        let Some(index) = url
+                            .query_pairs()
+                            .find(|f| f.0 == "id")
+                            .and_then(|f| f.1.parse().ok())
+                        else {
+                            return true;
+                        };
+                        if last == "delete_bookmark" {
+                            self.bookmarks.remove(index);
+                        } else {
+                            let field = |name| form.text(name).unwrap_or_default();
+                            self.bookmarks
+                                .rename(index, field("title"), field("folder"));
+                        }
+                        write_bookmarks(&self.bookmarks);
+                        self.regenerate_toad_page().await;
+                        return true;
+                    }
+                    "open_session" | "delete_session" => {
+                        if let Some((_, name)) = url.query_pairs().find(|f| f.0 == "name") {
+                            if last == "open_session" {
+                                self.switch_session(&name).await;
+                            } else if name != self.session_name {
+                                delete_session(&name);
+                                self.regenerate_toad_page().await;
+                            }
+                        }
+                        return true;
+                    }
+                    "save_session" => {
+                        let name = form
+                            .text("name")
+                            .map(sanitize_session_name)
+                            .unwrap_or_default();
+                        if !name.is_empty() {
+                            self.session_name = name.clone();
+                            self.save_session();
+                            self.settings.session = name;
+                            write_settings(&self.settings);
+                            self.regenerate_toad_page().await;
+                        }
+                        return true;
+                    }
+                    "enable_session_restore" | "disable_session_restore" => {
+                        self.settings.restore_session = last == "enable_session_restore";
+                        write_settings(&self.settings);
+                        self.regenerate_toad_page().await;
+                        return true;
+                    }
+                    "set_search_engine" => {
+                        if let Some(template) = form.text("template")
+                            && template.contains("%s")
+                            && !template.contains(char::is_whitespace)
+                        {
+                            self.settings.search_engine = template.trim().to_string();
+                            write_settings(&self.settings);
+                            self.regenerate_toad_page().await;
+                        }
+                        return true;
+                    }
+                    "add_keyword" => {
+                        let field = |name| {
+                            form.text(name)
+                                .map(|f| f.trim().to_string())
+                                .unwrap_or_default()
+                        };
+                        let (keyword, template) = (field("keyword"), field("template"));
+                        if !keyword.is_empty()
+                            && !keyword.contains(char::is_whitespace)
+                            && template.contains("%s")
+                            && !template.contains(char::is_whitespace)
+                        {
+                            self.settings.search_keywords.retain(|f| f.0 != keyword);
+                            self.settings.search_keywords.push((keyword, template));
+                            write_settings(&self.settings);
+                            self.regenerate_toad_page().await;
+                        }
+                        return true;
+                    }
+                    "remove_keyword" => {
+                        if let Some((_, keyword)) = url.query_pairs().find(|f| f.0 == "keyword") {
+                            self.settings.search_keywords.retain(|f| f.0 != keyword);
+                            write_settings(&self.settings);
+                            self.regenerate_toad_page().await;
+                        }
+                        return true;
+                    }
+                    "search_history" => {
+                        let mut history_url = Url::parse("toad://history").unwrap();
+                        if let Some(query) = form.text("q")
+                            && !query.trim().is_empty()
+                        {
+                            history_url.query_pairs_mut().append_pair("q", query.trim());
+                        }
+                        self.set_url(history_url).await;
+                        return true;
+                    }
+                    "clear_history" => {
+                        let now = unix_now();
+                        let since = match url.query_pairs().find(|f| f.0 == "range") {
+                            Some((_, range)) if range == "hour" => now.saturating_sub(3600),
+                            Some((_, range)) if range == "day" => now.saturating_sub(86400),
+                            Some((_, range)) if range == "week" => now.saturating_sub(7 * 86400),
+                            Some((_, range)) if range == "all" => 0,
+                            _ => return true,
+                        };
+                        self.history.clear_since(since);
+                        write_history(&self.history);
+                        self.regenerate_toad_page().await;
+                        return true;
+                    }
+                    "import_bookmarks" | "export_bookmarks" => {
+                        let name = form.text("path").map(str::trim);
+                        let Some(path) = bookmarks_file_path(name.unwrap_or_default()) else {
+                            let message = match data_dir() {
+                                Some(dir) => {
+                                    format!("bookmark files must be in {}", dir.to_string_lossy())
+                                }
+                                None => {
+                                    String::from("there's no data directory for bookmark files")
+                                }
+                            };
+                            self.show_status(&message);
+                            return true;
+                        };
+                        let shown = path.to_string_lossy().into_owned();
+                        if last == "import_bookmarks" {
+                            match std::fs::read_to_string(&path) {
+                                Ok(text) => {
+                                    let count = self.bookmarks.import_netscape(&text);
+                                    write_bookmarks(&self.bookmarks);
+                                    self.regenerate_toad_page().await;
+                                    self.show_status(&format!("imported {count} bookmarks"));
+                                }
+                                Err(e) => self.show_status(&format!("couldn't read {shown}: {e}")),
+                            }
+                        } else {
+                            match std::fs::write(&path, self.bookmarks.to_netscape()) {
+                                Ok(()) => self.show_status(&format!("exported to {shown}")),
+                                Err(e) => self.show_status(&format!("couldn't write {shown}: {e}")),
+                            }
+                        }
+                        return true;
+                    }
+                    "edit_keybinds" => {
+                        if let Some(path) = keybinds_path() {
+                            if !path.exists() {
+                                let _ = std::fs::write(&path, KEYBINDS_TEMPLATE);
+                            }
+                            let _ = edit_externally(&path);
+                            self.reload_keybinds();
+                            self.prev_buffer = None;
+                        }
+                        return true;
+                    }
+                    "edit_user_stylesheet" => {
+                        if let Some(path) = user_stylesheet_path() {
+                            if !path.exists() {
+                                let _ = std::fs::write(&path, USER_STYLESHEET_TEMPLATE);
+                            }
+                            let _ = edit_externally(&path);
+                            self.prev_buffer = None;
+                        }
+                        return true;
+                    }
                     _ => return false,
                 }
                 self.uncache_all_pages();
@@ -848,46 +2444,262 @@ impl Toad {
         }
         false
     }
-    async fn set_url(&mut self, url: Url) {
+    /// Generates toad:// pages whose contents change, like history and bookmarks
+    fn generate_toad_page(&self, url: &Url) -> Option<Webpage> {
+        if url.scheme() != "toad" {
+            return None;
+        }
+        let html = match url.host_str()? {
+            "history" => {
+                let query = url
+                    .query_pairs()
+                    .find(|f| f.0 == "q")
+                    .map(|f| f.1.to_string())
+                    .unwrap_or_default();
+                let query_attribute = if query.is_empty() {
+                    String::new()
+                } else {
+                    format!(" value=\"{}\"", sanitize(&query))
+                };
+                include_str!("history.html")
+                    .replace("{{QUERY}}", &query_attribute)
+                    .replace("{{ITEMS}}", &self.history.to_html(&query))
+            }
+            "bookmarks" => {
+                let data_dir = data_dir()
+                    .map(|f| f.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                include_str!("bookmarks.html")
+                    .replace("{{ITEMS}}", &self.bookmarks.to_html())
+                    .replace("{{DEFAULT_PATH}}", BOOKMARKS_EXPORT_FILENAME)
+                    .replace("{{DATA_DIR}}", &sanitize(&data_dir))
+            }
+            "settings" => {
+                let path = |f: Option<PathBuf>| {
+                    f.map(|f| f.to_string_lossy().to_string())
+                        .unwrap_or(String::from("unavailable"))
+                };
+                include_str!("settings.html")
+                    .replace(
+                        "{{USER_STYLESHEET}}",
+                        &sanitize(&path(user_stylesheet_path())),
+                    )
+                    .replace("{{KEYBINDS}}", &sanitize(&path(keybinds_path())))
+                    .replace("{{SEARCH_ENGINE}}", &sanitize(&self.settings.search_engine))
+                    .replace(
+                        "{{KEYWORDS}}",
+                        &keywords_to_html(&self.settings.search_keywords),
+                    )
+            }
+            "network" => {
+                let page_id = url
+                    .query_pairs()
+                    .find(|f| f.0 == "page")
+                    .and_then(|f| f.1.parse::<usize>().ok());
+                let page = match page_id {
+                    Some(id) => self.tabs.find_identifier(id),
+                    None => self.tabs.get(self.tab_index),
+                };
+                let (name, items) = match page {
+                    Some(page) => {
+                        let mut requests = page.requests.clone();
+                        requests.extend(
+                            self.fetches
+                                .iter()
+                                .filter(|f| f.page_id == page.indentifier)
+                                .map(|f| f.request.clone()),
+                        );
+                        let name = page
+                            .url
+                            .as_ref()
+                            .map(|f| f.to_string())
+                            .unwrap_or(String::from("this page"));
+                        (name, requests_to_html(&requests))
+                    }
+                    None => (
+                        String::from("a closed page"),
+                        String::from("<p>the page is no longer open.</p>"),
+                    ),
+                };
+                include_str!("network.html")
+                    .replace("{{PAGE}}", &sanitize(&name))
+                    .replace("{{ITEMS}}", &items)
+            }
+            "sessions" => {
+                let restore = if self.settings.restore_session {
+                    "on"
+                } else {
+                    "off"
+                };
+                include_str!("sessions.html")
+                    .replace("{{ITEMS}}", &sessions_to_html(&self.session_name))
+                    .replace("{{CURRENT}}", &sanitize(&self.session_name))
+                    .replace("{{RESTORE}}", restore)
+            }
+            _ => return None,
+        };
+        let mut page = parse_html(&html)?;
+        page.url = Some(url.clone());
+        Some(page)
+    }
+    /// Regenerates the current page if it is generated, like after a bookmark was deleted on toad://bookmarks
+    async fn regenerate_toad_page(&mut self) {
+        let Some(url) = self.tabs.get(self.tab_index).and_then(|f| f.url.clone()) else {
+            return;
+        };
+        if let Some(mut page) = self.generate_toad_page(&url) {
+            self.handle_new_page(&mut page).await;
+            *self.tabs.tabs[self.tab_index].page_mut() = page;
+        }
+    }
+    /// Gets the page of a URL, starting to fetch it if it isn't a toad:// page
+    fn load_url(&mut self, url: Url) -> Webpage {
         let mut u = url.clone();
         u.set_fragment(None);
-        let page = if let Some(page) = self.fetched_assets.get(&u)
+        if let Some(page) = self.fetched_assets.get(&u)
             && let DataEntry::Webpage(page) = page
         {
             let mut page = (**page).clone();
             page.url = Some(url);
             page
-        } else if url == Url::parse("toad://history").unwrap() {
-            let mut items_text = String::new();
-            for item in self.settings.history.iter().rev() {
-                items_text += &format!("<a href=\"{0}\">{0}</a><br>", sanitize(item));
-            }
-            let mut page =
-                parse_html(&include_str!("history.html").replace("{{ITEMS}}", &items_text))
-                    .unwrap();
-            page.url = Some(url);
+        } else if let Some(page) = self.generate_toad_page(&url) {
             page
+        } else if url.scheme() == "view-source" {
+            // use the source of an open page if there is one, so what's shown is what was rendered
+            let target = view_source_target(&url);
+            let open = self
+                .tabs
+                .iter()
+                .map(Tab::page)
+                .find(|f| f.url.is_some() && f.url == target && f.source.is_some())
+                .and_then(|f| f.source.clone());
+            if let Some(page) = open.and_then(|f| Webpage::view_source(url.clone(), &f)) {
+                page
+            } else {
+                let future = get_page_source(self.client.clone(), url.clone());
+                self.fetch(
+                    self.current_page_id,
+                    RequestKind::Document,
+                    Method::GET,
+                    url.clone(),
+                    future,
+                );
+                Webpage::loading(url)
+            }
+        } else if self.blocker.should_block(
+            &url,
+            self.tabs.get(self.tab_index).and_then(|f| f.url.as_ref()),
+            RequestKind::Document,
+        ) {
+            Webpage::blocked(url)
         } else {
-            let handle = tokio::spawn(get_page(self.client.clone(), url.clone()));
-            self.fetches
-                .push((self.current_page_id, url.clone(), handle));
-            let mut page = parse_html(include_str!("loading.html")).unwrap();
-            page.url = Some(url);
-            page
-        };
+            let future = get_page(self.client.clone(), url.clone());
+            self.fetch(
+                self.current_page_id,
+                RequestKind::Document,
+                Method::GET,
+                url.clone(),
+                future,
+            );
+            Webpage::loading(url)
+        }
+    }
+    async fn set_url(&mut self, url: Url) {
+        let page = self.load_url(url);
         self.open_page(page, self.tab_index).await;
     }
+    /// The open tabs, as a [Session]
+    fn current_session(&self) -> Session {
+        let to_session_page = |page: &Webpage| {
+            Some(SessionPage {
+                url: page.url.as_ref()?.to_string(),
+                scroll_y: page.scroll_y,
+            })
+        };
+        let tabs = self
+            .tabs
+            .iter()
+            .map(|tab| SessionTab {
+                history: tab.history.iter().filter_map(to_session_page).collect(),
+                future: tab.future.iter().filter_map(to_session_page).collect(),
+            })
+            .filter(|f| !f.history.is_empty())
+            .collect();
+        Session {
+            tabs,
+            tab_index: self.tab_index,
+        }
+    }
+    fn save_session(&mut self) {
+        write_session(&self.session_name, &self.current_session());
+        self.last_session_save = Some(Instant::now());
+    }
+    /// Replaces all open tabs with those of a session. The pages are fetched once they are shown.
+    fn restore_session(&mut self, session: Session) {
+        let to_page = |page: &SessionPage| {
+            Url::parse(&page.url)
+                .ok()
+                .map(|url| Webpage::restored(url, page.scroll_y))
+        };
+        self.tabs.tabs = session
+            .tabs
+            .iter()
+            .map(|tab| Tab {
+                history: tab.history.iter().filter_map(to_page).collect(),
+                future: tab.future.iter().filter_map(to_page).collect(),
+            })
+            .filter(|f| !f.history.is_empty())
+            .collect();
+        self.tab_index = session.tab_index.min(self.tabs.len().saturating_sub(1));
+        self.prev_buffer = None;
+    }
+    /// Switches to another named session, saving the current one first
+    async fn switch_session(&mut self, name: &str) {
+        let name = sanitize_session_name(name);
+        let Some(session) = load_session(&name) else {
+            return;
+        };
+        self.save_session();
+        self.restore_session(session);
+        self.session_name = name.clone();
+        self.settings.session = name;
+        write_settings(&self.settings);
+        if self.tabs.is_empty() {
+            self.set_url(Url::parse("toad://home").unwrap()).await;
+        }
+    }
+    /// Fetches the current page, if it's a placeholder of a restored session
+    async fn load_restored_page(&mut self) {
+        let Some(page) = self.tabs.get(self.tab_index) else {
+            return;
+        };
+        if !page.restored {
+            return;
+        }
+        let Some(url) = page.url.clone() else {
+            return;
+        };
+        let (scroll_y, has_been_scrolled) = (page.scroll_y, page.has_been_scrolled);
+        let mut page = self.load_url(url);
+        page.scroll_y = scroll_y;
+        page.has_been_scrolled = has_been_scrolled;
+        self.handle_new_page(&mut page).await;
+        *self.tabs.tabs[self.tab_index].page_mut() = page;
+    }
     async fn handle_input_box_state(
         &mut self,
         mut stdout: &Stdout,
         screen_size: (u16, u16),
     ) -> io::Result<()> {
         let input_box = self.current_input_box.as_mut().unwrap();
+        let pressed_enter = matches!(input_box.state, InputBoxState::Submitted);
         let was_submitted = match &input_box.state {
             InputBoxState::Submitted => true,
             InputBoxState::Cancelled => matches!(
                 input_box.on_submit,
                 InputBoxSubmitTarget::SetFormTextField(_, _)
+                    | InputBoxSubmitTarget::UnlockAutofill(_)
+                    | InputBoxSubmitTarget::ConfirmAutofill(_, _)
             ),
             _ => false,
         };
@@ -897,7 +2709,30 @@ impl Toad {
             self.prev_buffer = None;
             match input_box.on_submit {
                 InputBoxSubmitTarget::ChangeAddress | InputBoxSubmitTarget::OpenNewTab => {
-                    if let Some(url) = parse_url_user_input(&input_box.text) {
+                    if let Some(Suggestion {
+                        kind: SuggestionKind::Tab(index),
+                        ..
+                    }) = input_box.chosen_suggestion
+                        && index < self.tabs.len()
+                    {
+                        // switch to the already open tab instead of loading the page again
+                        if let InputBoxSubmitTarget::OpenNewTab = input_box.on_submit
+                            && index != self.tab_index
+                        {
+                            self.tabs.remove(self.tab_index);
+                            self.tab_index = if index > self.tab_index {
+                                index - 1
+                            } else {
+                                index
+                            };
+                        } else {
+                            self.tab_index = index;
+                        }
+                    } else if let Some(url) = resolve_user_input(
+                        &input_box.text,
+                        &self.settings.search_engine,
+                        &self.settings.search_keywords,
+                    ) {
                         self.set_url(url).await;
                     } else if let InputBoxSubmitTarget::OpenNewTab = input_box.on_submit {
                         self.tabs.remove(self.tab_index);
@@ -905,21 +2740,98 @@ impl Toad {
                     }
                     self.draw(stdout, screen_size)?;
                 }
-                InputBoxSubmitTarget::SetFormTextField(index, name) => {
+                InputBoxSubmitTarget::SetFormTextField(index, field) => {
+                    let mut implicit_submission = None;
                     if let Some(tab) = self.tabs.get_mut(self.tab_index)
                         && let Some(cached) = &mut tab.cached_draw
                     {
-                        cached.forms[index]
-                            .text_fields
-                            .insert(name.clone(), input_box.text);
+                        let form = &mut cached.forms[index];
+                        form.text_fields[field].value = input_box.text;
+                        cached.invalid = None;
+                        // a chosen fill fills in the rest of the login too
+                        if let Some(Suggestion {
+                            kind: SuggestionKind::Autofill(record),
+                            ..
+                        }) = input_box.chosen_suggestion
+                            && let Some(record) = self
+                                .autofill
+                                .as_ref()
+                                .and_then(|f| f.autofill.records.get(record))
+                        {
+                            for field in form.text_fields.iter_mut() {
+                                let readonly =
+                                    field.constraints.as_ref().is_none_or(|f| f.readonly);
+                                if let Some(value) = record.get(&field.name)
+                                    && !readonly
+                                {
+                                    field.value = value.to_string();
+                                }
+                            }
+                        }
+                        // pressing enter sends the form with its first submit button,
+                        // or without any if it has a single text field
+                        if pressed_enter {
+                            let submitter = form.fields.iter().find_map(|f| match f {
+                                FormField::Submit(index) => Some(*index),
+                                _ => None,
+                            });
+                            if submitter.is_some() || form.submits_implicitly() {
+                                implicit_submission = Some(submitter);
+                            }
+                        }
                     };
+                    if let Some(submitter) = implicit_submission {
+                        self.submit_form(index, submitter, stdout, screen_size)
+                            .await?;
+                        return Ok(());
+                    }
                     self.draw(stdout, screen_size)?;
                 }
+                InputBoxSubmitTarget::FindInPage => {
+                    if let Some(tab) = self.tabs.get_mut(self.tab_index) {
+                        tab.scroll_to_find_match(screen_size.1);
+                    }
+                    self.draw(stdout, screen_size)?;
+                }
+                InputBoxSubmitTarget::UnlockAutofill(reason) => {
+                    let exists = autofill_path().is_some_and(|f| f.exists());
+                    if !pressed_enter {
+                        self.autofill_declined = true;
+                    } else if !exists && !input_box.text.is_empty() {
+                        // a new passphrase is typed twice, so a typo can't lock the store
+                        self.open_passphrase_box(
+                            InputBoxSubmitTarget::ConfirmAutofill(reason, input_box.text),
+                            screen_size,
+                        );
+                        self.show_status("enter the passphrase again to confirm it");
+                        return self.draw(stdout, screen_size);
+                    } else if !self.unlock_autofill(&input_box.text, reason, screen_size) {
+                        return self.draw(stdout, screen_size);
+                    }
+                    self.finish_unlock(reason, stdout, screen_size).await?;
+                }
+                InputBoxSubmitTarget::ConfirmAutofill(reason, passphrase) => {
+                    if !pressed_enter {
+                        self.autofill_declined = true;
+                    } else if input_box.text != passphrase {
+                        self.ask_passphrase(reason, screen_size);
+                        self.show_status("the passphrases don't match, try again");
+                        return self.draw(stdout, screen_size);
+                    } else if !self.unlock_autofill(&passphrase, reason, screen_size) {
+                        return self.draw(stdout, screen_size);
+                    }
+                    self.finish_unlock(reason, stdout, screen_size).await?;
+                }
             }
         } else {
             match &input_box.state {
                 InputBoxState::Cancelled => {
-                    self.current_input_box.take().unwrap();
+                    let input_box = self.current_input_box.take().unwrap();
+                    if let InputBoxSubmitTarget::FindInPage = input_box.on_submit
+                        && let Some(tab) = self.tabs.get_mut(self.tab_index)
+                    {
+                        tab.find = None;
+                    }
                     queue!(stdout, cursor::Hide)?;
                     self.prev_buffer = None;
                     self.draw(stdout, screen_size)?;
@@ -931,20 +2843,167 @@ impl Toad {
         }
         Ok(())
     }
+    /// Inserts pasted text into the text area or input box being edited
+    async fn handle_paste(
+        &mut self,
+        text: &str,
+        stdout: &Stdout,
+        screen_size: (u16, u16),
+    ) -> io::Result<()> {
+        if let Some(text_area) = &mut self.current_text_area {
+            text_area.paste(text);
+            self.draw(stdout, screen_size)?;
+        } else if let Some(input_box) = &mut self.current_input_box {
+            // input boxes are a single line, so newlines are left out
+            for char in text.chars().filter(|f| !f.is_control()) {
+                input_box.on_event(event::KeyEvent::from(event::KeyCode::Char(char)));
+            }
+            if let InputBoxSubmitTarget::FindInPage = input_box.on_submit {
+                self.update_find(screen_size, false, false);
+            }
+            self.handle_input_box_state(stdout, screen_size).await?;
+        }
+        Ok(())
+    }
+    /// Saves the text area's text to its form once editing is done, or opens it in the user's editor
+    fn handle_text_area_state(
+        &mut self,
+        mut stdout: &Stdout,
+        screen_size: (u16, u16),
+    ) -> io::Result<()> {
+        let Some(text_area) = &mut self.current_text_area else {
+            return Ok(());
+        };
+        match text_area.state {
+            TextAreaState::Active => {}
+            TextAreaState::OpenEditor => {
+                if let Ok(path) = create_private_temp_file(&text_area.text) {
+                    let _ = edit_externally(&path);
+                    if let Ok(text) = std::fs::read_to_string(&path) {
+                        text_area.text = String::new();
+                        text_area.paste(text.strip_suffix('\n').unwrap_or(&text));
+                    }
+                    let _ = std::fs::remove_file(&path);
+                }
+                text_area.state = TextAreaState::Active;
+                self.prev_buffer = None;
+            }
+            TextAreaState::Done => {
+                let text_area = self.current_text_area.take().unwrap();
+                if let Some(tab) = self.tabs.get_mut(self.tab_index)
+                    && let Some(cached) = &mut tab.cached_draw
+                    && let Some(form) = cached.forms.get_mut(text_area.form)
+                {
+                    if let Some(field) = form.text_fields.get_mut(text_area.field) {
+                        field.value = text_area.text;
+                    }
+                    cached.invalid = None;
+                }
+                queue!(stdout, cursor::Hide)?;
+                self.prev_buffer = None;
+            }
+        }
+        self.draw(stdout, screen_size)
+    }
+    /// Loads the key bindings config, and regenerates the home page's shortcut list from it
+    fn reload_keybinds(&mut self) {
+        self.keybinds = load_keybinds();
+        let home =
+            include_str!("home.html").replace("{{SHORTCUTS}}", &self.keybinds.shortcut_list_html());
+        self.fetched_assets.insert(
+            Url::parse("toad://home").unwrap(),
+            DataEntry::Webpage(Box::new(parse_html(&home).unwrap())),
+        );
+    }
+    /// Opens the find-in-page search bar, in place of the URL bar
+    fn open_find_bar(&mut self, screen_size: (u16, u16)) {
+        let Some(tab) = self.tabs.get(self.tab_index) else {
+            return;
+        };
+        let query = tab.find.as_ref().map(|f| f.query.clone());
+        self.current_input_box = Some(InputBox::new(
+            4 * 3,
+            1,
+            screen_size.0.saturating_sub(4 * 3 * 2 + FIND_COUNTER_WIDTH),
+            InputBoxSubmitTarget::FindInPage,
+            query,
+            Vec::new(),
+        ));
+    }
+    /// Searches the current page with the text of the find bar, optionally toggling case sensitivity or regex mode.
+    fn update_find(&mut self, screen_size: (u16, u16), toggle_case: bool, toggle_regex: bool) {
+        let Some(input_box) = &self.current_input_box else {
+            return;
+        };
+        let Some(tab) = self.tabs.get_mut(self.tab_index) else {
+            return;
+        };
+        let (mut case_sensitive, mut regex) = tab
+            .find
+            .as_ref()
+            .map(|f| (f.case_sensitive, f.regex))
+            .unwrap_or_default();
+        case_sensitive ^= toggle_case;
+        regex ^= toggle_regex;
+        if tab.find.as_ref().is_some_and(|f| {
+            f.query == input_box.text && f.case_sensitive == case_sensitive && f.regex == regex
+        }) {
+            return;
+        }
+        let mut find = FindState::new(input_box.text.clone(), case_sensitive, regex);
+        if let Some(cached) = &tab.cached_draw {
+            find.search(&cached.calls);
+        }
+        find.select_from_row(tab.scroll_y + 3);
+        tab.find = Some(find);
+        tab.scroll_to_find_match(screen_size.1);
+    }
     fn refresh_page(&mut self, tab_index: usize) {
         if let Some(page) = self.tabs.get(tab_index) {
             self.prev_buffer = None;
             let draw = self.draw_page(page);
             self.draw_threads.insert(page.indentifier, draw);
             let page = self.tabs.get_mut(tab_index).unwrap();
-            page.scroll_y = 0;
-            refresh_style(page, &self.fetched_assets);
+            refresh_style(
+                page,
+                &self.fetched_assets,
+                &self.user_stylesheet,
+                &self.blocker,
+            );
         }
     }
+    /// Reloads the user stylesheet if it has been changed since it was last loaded,
+    /// and restyles all pages if so.
+    ///
+    /// Returns whether it was reloaded.
+    fn reload_user_stylesheet(&mut self) -> bool {
+        self.last_user_stylesheet_check = Some(Instant::now());
+        let Some(path) = user_stylesheet_path() else {
+            return false;
+        };
+        let modified = std::fs::metadata(&path).and_then(|f| f.modified()).ok();
+        if modified == self.user_stylesheet_modified {
+            return false;
+        }
+        self.user_stylesheet_modified = modified;
+        self.user_stylesheet = std::fs::read_to_string(path).unwrap_or_default();
+        for tab in self.tabs.tabs.iter_mut() {
+            for page in tab.future.iter_mut().chain(tab.history.iter_mut()) {
+                refresh_style(
+                    page,
+                    &self.fetched_assets,
+                    &self.user_stylesheet,
+                    &self.blocker,
+                );
+                page.uncache_draw();
+            }
+        }
+        true
+    }
     fn uncache_all_pages(&mut self) {
         for tab in self.tabs.tabs.iter_mut() {
             for page in tab.future.iter_mut().chain(tab.history.iter_mut()) {
-                page.cached_draw = None;
+                page.uncache_draw();
             }
         }
     }
@@ -953,18 +3012,53 @@ impl Toad {
         let mut running = true;
         let mut stdout = stdout();
         terminal::enable_raw_mode()?;
-        queue!(stdout, cursor::Hide, event::EnableMouseCapture)?;
+        queue!(
+            stdout,
+            cursor::Hide,
+            event::EnableMouseCapture,
+            event::EnableBracketedPaste
+        )?;
         let mut screen_size = terminal::size()?;
+        self.load_restored_page().await;
         self.draw(&stdout, screen_size)?;
         while running {
+            self.load_restored_page().await;
+            if self
+                .last_session_save
+                .is_none_or(|f| f.elapsed() >= Duration::from_secs(SESSION_SAVE_INTERVAL))
+            {
+                self.save_session();
+            }
+            if self
+                .last_user_stylesheet_check
+                .is_none_or(|f| f.elapsed() >= Duration::from_secs(1))
+                && self.reload_user_stylesheet()
+            {
+                self.prev_buffer = None;
+                self.draw(&stdout, screen_size)?;
+            }
+            if self
+                .status_message
+                .as_ref()
+                .is_some_and(|f| f.1.elapsed() >= Duration::from_secs(STATUS_MESSAGE_DURATION))
+            {
+                self.status_message = None;
+                self.draw(&stdout, screen_size)?;
+            }
             let new_screen_size = terminal::size()?;
             if new_screen_size != screen_size {
                 screen_size = new_screen_size;
+                // reflow pages to the new width
+                self.uncache_all_pages();
                 self.prev_buffer = None;
                 self.draw(&stdout, screen_size)?;
             }
             if event::poll(Duration::from_millis(10))? {
                 let event = event::read()?;
+                if let event::Event::Paste(text) = &event {
+                    self.handle_paste(text, &stdout, screen_size).await?;
+                    continue;
+                }
                 if !event.is_key_press() {
                     if let event::Event::Mouse(mouse_event) = event {
                         let Some(page) = self.tabs.get_mut(self.tab_index) else {
@@ -979,11 +3073,41 @@ impl Toad {
                         (self.last_mouse_x, self.last_mouse_y) =
                             (mouse_event.column, mouse_event.row);
 
-                        if self.current_input_box.is_some() {
+                        if let Some(text_area) = &mut self.current_text_area {
+                            // clicking outside of the text area finishes editing
+                            if let event::MouseEventKind::Down(_) = mouse_event.kind {
+                                if !text_area.click(mouse_event.column, mouse_event.row) {
+                                    text_area.state = TextAreaState::Done;
+                                }
+                                self.handle_text_area_state(&stdout, screen_size)?;
+                            }
+                        } else if self.select_popup.is_some() {
+                            if let event::MouseEventKind::Down(_) = mouse_event.kind {
+                                self.handle_select_input(
+                                    None,
+                                    (mouse_event.column, mouse_event.row),
+                                );
+                                self.draw(&stdout, screen_size)?;
+                            }
+                        } else if self.file_picker.is_some() {
+                            if let event::MouseEventKind::Down(_) = mouse_event.kind {
+                                self.handle_file_picker_input(
+                                    None,
+                                    (mouse_event.column, mouse_event.row),
+                                );
+                                self.draw(&stdout, screen_size)?;
+                            }
+                        } else if self.current_input_box.is_some() {
                             if let event::MouseEventKind::Down(_) = mouse_event.kind
                                 && let Some(input_box) = &mut self.current_input_box
                             {
-                                input_box.state = InputBoxState::Cancelled;
+                                if let Some(index) =
+                                    input_box.suggestion_at(mouse_event.column, mouse_event.row)
+                                {
+                                    input_box.choose_suggestion(index);
+                                } else {
+                                    input_box.state = InputBoxState::Cancelled;
+                                }
                                 self.handle_input_box_state(&stdout, screen_size).await?;
                             }
                         } else {
@@ -1016,7 +3140,33 @@ impl Toad {
                                 page.hovered_interactable = None;
                                 page.tab_index = None;
                             }
+                            let shift_held =
+                                mouse_event.modifiers.contains(event::KeyModifiers::SHIFT);
                             match mouse_event.kind {
+                                event::MouseEventKind::ScrollRight
+                                | event::MouseEventKind::ScrollDown
+                                    if shift_held
+                                        || mouse_event.kind
+                                            == event::MouseEventKind::ScrollRight =>
+                                {
+                                    needs_redraw |= page.scroll_horizontally(
+                                        mouse_event.column,
+                                        mouse_event.row,
+                                        4,
+                                    );
+                                }
+                                event::MouseEventKind::ScrollLeft
+                                | event::MouseEventKind::ScrollUp
+                                    if shift_held
+                                        || mouse_event.kind
+                                            == event::MouseEventKind::ScrollLeft =>
+                                {
+                                    needs_redraw |= page.scroll_horizontally(
+                                        mouse_event.column,
+                                        mouse_event.row,
+                                        -4,
+                                    );
+                                }
                                 event::MouseEventKind::ScrollDown => {
                                     page.scroll_y += 1;
                                     needs_redraw = true;
@@ -1121,14 +3271,17 @@ impl Toad {
                                         {
                                             // click url bar
 
-                                            self.current_input_box = Some(InputBox::new(
-                                                4 * 3,
-                                                1,
-                                                screen_size.0 - 4 * 3 * 2,
-                                                InputBoxSubmitTarget::ChangeAddress,
-                                                page.url.clone().map(|f| f.to_string()),
-                                                self.get_url_bar_autocompletions(),
-                                            ));
+                                            self.current_input_box = Some(
+                                                InputBox::new(
+                                                    4 * 3,
+                                                    1,
+                                                    screen_size.0 - 4 * 3 * 2,
+                                                    InputBoxSubmitTarget::ChangeAddress,
+                                                    page.url.clone().map(|f| f.to_string()),
+                                                    self.get_url_bar_autocompletions(),
+                                                )
+                                                .with_suggestions(self.get_url_bar_suggestions()),
+                                            );
                                             needs_redraw = true;
                                         } else if mouse_event.column <= 2 {
                                             self.tabs.tabs[self.tab_index].backwards();
@@ -1158,84 +3311,145 @@ impl Toad {
                     continue;
                 };
                 if let Some(input_box) = &mut self.current_input_box {
-                    input_box.on_event(key);
+                    let is_find = matches!(input_box.on_submit, InputBoxSubmitTarget::FindInPage);
+                    if is_find
+                        && key.modifiers.contains(event::KeyModifiers::ALT)
+                        && let event::KeyCode::Char(char @ ('c' | 'r')) = key.code
+                    {
+                        // alt+c toggles case sensitivity, alt+r toggles regex
+                        self.update_find(screen_size, char == 'c', char == 'r');
+                    } else {
+                        input_box.on_event(key);
+                        if is_find {
+                            self.update_find(screen_size, false, false);
+                        }
+                    }
                     self.handle_input_box_state(&stdout, screen_size).await?;
+                } else if let Some(text_area) = &mut self.current_text_area {
+                    text_area.on_event(key);
+                    self.handle_text_area_state(&stdout, screen_size)?;
+                } else if self.hint_mode.is_some() {
+                    self.handle_hint_key(key, &stdout, screen_size).await?;
+                } else if self.select_popup.is_some() {
+                    self.handle_select_input(Some(key), (0, 0));
+                    self.draw(&stdout, screen_size)?;
+                } else if self.file_picker.is_some() {
+                    self.handle_file_picker_input(Some(key), (0, 0));
+                    self.draw(&stdout, screen_size)?;
+                } else if self.handle_inspector_key(key, screen_size) {
+                    self.draw(&stdout, screen_size)?;
                 } else {
-                    match key.code {
-                        event::KeyCode::Enter => {
-                            self.interact(
-                                &stdout,
-                                key.modifiers.contains(event::KeyModifiers::CONTROL),
-                                screen_size,
-                            )
-                            .await?;
+                    // quitting has to be confirmed by pressing the key again
+                    let quit_pending = std::mem::take(&mut self.quit_pending);
+                    match self.keybinds.get(&key) {
+                        Some(Action::Quit) => {
+                            if quit_pending {
+                                running = false;
+                            } else {
+                                self.quit_pending = true;
+                            }
                         }
-                        event::KeyCode::F(12) => {
-                            if let Some(tab) = self.tabs.get(self.tab_index) {
-                                let debug = tab.debug_info.clone();
-                                let _page_text = sanitize(
-                                    &tab.root
-                                        .as_ref()
-                                        .map(|f| f.print_recursive(0))
-                                        .unwrap_or(String::new()),
-                                );
-                                let mut s = String::new();
-                                for (item, _) in tab.global_style.iter() {
-                                    s += &format!("{:?}", item);
-                                    s += "\n\n"
-                                }
-                                let html = DEBUG_PAGE
-                                .replace("{DEBUGINFO}", &sanitize(&format!("{:?}", debug)))
-                                .replace("{STYLE_TARGETS}", &sanitize(&s))
-                               // .replace("{PAGE}", &page_text);
-                               ;
-                                if let Some(page) = parse_html(&html) {
-                                    self.open_page_new_tab(page).await;
-                                    self.draw(&stdout, screen_size)?;
-                                }
+                        Some(Action::Follow) => {
+                            self.interact(&stdout, false, screen_size).await?;
+                        }
+                        Some(Action::FollowNewTab) => {
+                            self.interact(&stdout, true, screen_size).await?;
+                        }
+                        Some(Action::Cancel) => {
+                            if let Some(tab) = self.tabs.get_mut(self.tab_index)
+                                && tab.find.take().is_some()
+                            {
+                                self.draw(&stdout, screen_size)?;
                             }
                         }
-                        event::KeyCode::Tab => {
+                        Some(Action::DebugInfo) => {
+                            if self.inspector.take().is_none()
+                                && let Some(tab) = self.tabs.get(self.tab_index)
+                                && let Some(root) = &tab.root
+                            {
+                                self.inspector = Some(Inspector::new(Arc::clone(root)));
+                            }
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::NextTab) => {
                             self.tab_index += 1;
                             if self.tab_index >= self.tabs.len() {
                                 self.tab_index = 0;
                             }
                             self.draw(&stdout, screen_size)?;
                         }
-                        event::KeyCode::Down => {
+                        Some(Action::PreviousTab) => {
+                            self.tab_index = self
+                                .tab_index
+                                .checked_sub(1)
+                                .unwrap_or(self.tabs.len().saturating_sub(1));
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::ScrollDown) => {
                             if let Some(tab) = self.tabs.get_mut(self.tab_index) {
                                 tab.has_been_scrolled = true;
                                 tab.scroll_y += 1;
                                 self.draw(&stdout, screen_size)?;
                             }
                         }
-                        event::KeyCode::Up => {
+                        Some(Action::ScrollUp) => {
                             if let Some(tab) = self.tabs.get_mut(self.tab_index) {
                                 tab.has_been_scrolled = true;
                                 tab.scroll_y = tab.scroll_y.saturating_sub(1);
                                 self.draw(&stdout, screen_size)?;
                             }
                         }
-                        event::KeyCode::Right => {
-                            if key.modifiers.contains(event::KeyModifiers::CONTROL) {
-                                self.tabs.tabs[self.tab_index].forwards();
+                        Some(Action::ScrollTop) => {
+                            if let Some(tab) = self.tabs.get_mut(self.tab_index) {
+                                tab.has_been_scrolled = true;
+                                tab.scroll_y = 0;
+                                self.draw(&stdout, screen_size)?;
+                            }
+                        }
+                        Some(Action::ScrollBottom) => {
+                            if let Some(tab) = self.tabs.get_mut(self.tab_index)
+                                && let Some(content_height) = tab.page_height
+                            {
+                                tab.has_been_scrolled = true;
+                                tab.scroll_y =
+                                    (content_height / LH).saturating_sub(screen_size.1 - 3);
                                 self.draw(&stdout, screen_size)?;
-                            } else if let Some(tab) = self.tabs.get_mut(self.tab_index) {
-                                tab.tab_index = Some(tab.tab_index.map(|i| i + 1).unwrap_or(0));
+                            }
+                        }
+                        Some(action @ (Action::ScrollLeft | Action::ScrollRight)) => {
+                            let amount = if action == Action::ScrollLeft { -4 } else { 4 };
+                            if let Some(tab) = self.tabs.get_mut(self.tab_index)
+                                && tab.scroll_horizontally(
+                                    self.last_mouse_x,
+                                    self.last_mouse_y,
+                                    amount,
+                                )
+                            {
                                 self.draw(&stdout, screen_size)?;
                             }
                         }
-                        event::KeyCode::Left => {
-                            if key.modifiers.contains(event::KeyModifiers::CONTROL) {
-                                self.tabs.tabs[self.tab_index].backwards();
+                        Some(Action::NextInteractable) => {
+                            if let Some(tab) = self.tabs.get_mut(self.tab_index) {
+                                tab.tab_index = Some(tab.tab_index.map(|i| i + 1).unwrap_or(0));
                                 self.draw(&stdout, screen_size)?;
-                            } else if let Some(tab) = self.tabs.get_mut(self.tab_index) {
+                            }
+                        }
+                        Some(Action::PreviousInteractable) => {
+                            if let Some(tab) = self.tabs.get_mut(self.tab_index) {
                                 tab.tab_index =
                                     Some(tab.tab_index.map(|i| i.saturating_sub(1)).unwrap_or(0));
                                 self.draw(&stdout, screen_size)?;
                             }
                         }
-                        event::KeyCode::PageDown => {
+                        Some(Action::Back) => {
+                            self.tabs.tabs[self.tab_index].backwards();
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::Forward) => {
+                            self.tabs.tabs[self.tab_index].forwards();
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::PageDown) => {
                             let (_, screen_height) = screen_size;
                             if let Some(tab) = self.tabs.get_mut(self.tab_index) {
                                 tab.has_been_scrolled = true;
@@ -1243,7 +3457,7 @@ impl Toad {
                                 self.draw(&stdout, screen_size)?;
                             }
                         }
-                        event::KeyCode::PageUp => {
+                        Some(Action::PageUp) => {
                             let (_, screen_height) = screen_size;
                             if let Some(tab) = self.tabs.get_mut(self.tab_index) {
                                 tab.has_been_scrolled = true;
@@ -1251,55 +3465,161 @@ impl Toad {
                                 self.draw(&stdout, screen_size)?;
                             }
                         }
-                        event::KeyCode::Char(char) => {
-                            let control = key.modifiers.contains(event::KeyModifiers::CONTROL);
-                            if char == 'q' {
-                                running = false;
-                            } else if char == 'r' && control {
-                                self.refresh_page(self.tab_index);
+                        Some(Action::Find) => {
+                            self.open_find_bar(screen_size);
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(action @ (Action::FindNext | Action::FindPrevious)) => {
+                            if let Some(tab) = self.tabs.get_mut(self.tab_index)
+                                && let Some(find) = &mut tab.find
+                            {
+                                if action == Action::FindNext {
+                                    find.next();
+                                } else {
+                                    find.previous();
+                                }
+                                tab.scroll_to_find_match(screen_size.1);
                                 self.draw(&stdout, screen_size)?;
-                            } else if char == 'h' && control {
-                                self.open_page_new_tab(
-                                    parse_html(include_str!("blank.html")).unwrap(),
-                                )
+                            }
+                        }
+                        Some(Action::HintFollow) => {
+                            self.start_hint_mode(HintAction::Follow);
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::HintFollowNewTab) => {
+                            self.start_hint_mode(HintAction::FollowNewTab);
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::HintCopyUrl) => {
+                            self.start_hint_mode(HintAction::CopyUrl);
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::Reload) => {
+                            self.refresh_page(self.tab_index);
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::History) => {
+                            self.open_page_new_tab(parse_html(include_str!("blank.html")).unwrap())
                                 .await;
-                                self.set_url(Url::parse("toad://history").unwrap()).await;
+                            self.set_url(Url::parse("toad://history").unwrap()).await;
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::SaveLogin) => {
+                            self.save_login(screen_size);
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::ToggleBlocking) => {
+                            self.toggle_blocking();
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::Bookmark) => {
+                            if let Some(tab) = self.tabs.get(self.tab_index)
+                                && let Some(url) = &tab.url
+                            {
+                                let added =
+                                    self.bookmarks.toggle(url.as_str(), tab.get_title().trim());
+                                write_bookmarks(&self.bookmarks);
+                                self.show_status(if added {
+                                    "bookmarked"
+                                } else {
+                                    "bookmark removed"
+                                });
                                 self.draw(&stdout, screen_size)?;
-                            } else if char == 'w' && control {
-                                if self.tab_index < self.tabs.len() {
-                                    self.tabs.remove(self.tab_index);
-                                    self.tab_index = self.tab_index.saturating_sub(1);
-                                    if self.tabs.is_empty() {
-                                        break;
-                                    }
-                                    self.draw(&stdout, screen_size)?;
-                                }
-                            } else if char == 't' && control {
-                                let url = Url::parse("toad://home").unwrap();
-                                let page = if let Some(page) = self.fetched_assets.get(&url)
-                                    && let DataEntry::Webpage(page) = page
-                                {
-                                    let mut page = (**page).clone();
-                                    page.url = Some(url);
-                                    page
+                            }
+                        }
+                        Some(Action::ReaderMode) => {
+                            if let Some(page) = self.tabs.get(self.tab_index) {
+                                if page.reader {
+                                    self.tabs.tabs[self.tab_index].backwards();
+                                } else if let Some(reader) = page.reader_mode() {
+                                    self.open_page(reader, self.tab_index).await;
                                 } else {
-                                    panic!()
-                                };
-
+                                    self.show_status("no article found");
+                                }
+                                self.draw(&stdout, screen_size)?;
+                            }
+                        }
+                        Some(Action::Network) => {
+                            if let Some(tab) = self.tabs.get(self.tab_index)
+                                && let Ok(url) =
+                                    Url::parse(&format!("toad://network?page={}", tab.indentifier))
+                                && let Some(page) = self.generate_toad_page(&url)
+                            {
                                 self.open_page_new_tab(page).await;
-                                self.current_input_box = Some(InputBox::new(
+                                self.draw(&stdout, screen_size)?;
+                            }
+                        }
+                        Some(Action::ViewSource) => {
+                            if let Some(url) =
+                                self.tabs.get(self.tab_index).and_then(|f| f.url.clone())
+                            {
+                                if let Some(target) = view_source_target(&url) {
+                                    self.set_url(target).await;
+                                } else if let Ok(url) = Url::parse(&format!("view-source:{url}")) {
+                                    self.set_url(url).await;
+                                }
+                                self.draw(&stdout, screen_size)?;
+                            }
+                        }
+                        Some(Action::CloseTab) if self.tab_index < self.tabs.len() => {
+                            self.tabs.remove(self.tab_index);
+                            self.tab_index = self.tab_index.saturating_sub(1);
+                            if self.tabs.is_empty() {
+                                break;
+                            }
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::OpenUrlBar) => {
+                            let url = self
+                                .tabs
+                                .get(self.tab_index)
+                                .and_then(|f| f.url.clone())
+                                .map(|f| f.to_string());
+                            self.current_input_box = Some(
+                                InputBox::new(
+                                    4 * 3,
+                                    1,
+                                    screen_size.0 - 4 * 3 * 2,
+                                    InputBoxSubmitTarget::ChangeAddress,
+                                    url,
+                                    self.get_url_bar_autocompletions(),
+                                )
+                                .with_suggestions(self.get_url_bar_suggestions()),
+                            );
+                            self.draw(&stdout, screen_size)?;
+                        }
+                        Some(Action::NewTab) => {
+                            let url = Url::parse("toad://home").unwrap();
+                            let page = if let Some(page) = self.fetched_assets.get(&url)
+                                && let DataEntry::Webpage(page) = page
+                            {
+                                let mut page = (**page).clone();
+                                page.url = Some(url);
+                                page
+                            } else {
+                                panic!()
+                            };
+
+                            self.open_page_new_tab(page).await;
+                            self.current_input_box = Some(
+                                InputBox::new(
                                     4 * 3,
                                     1,
                                     screen_size.0 - 4 * 3 * 2,
                                     InputBoxSubmitTarget::OpenNewTab,
                                     None,
                                     self.get_url_bar_autocompletions(),
-                                ));
-                                self.draw(&stdout, screen_size)?;
-                            }
+                                )
+                                .with_suggestions(self.get_url_bar_suggestions()),
+                            );
+                            self.draw(&stdout, screen_size)?;
                         }
                         _ => {}
                     }
+                    if quit_pending != self.quit_pending {
+                        // show or clear the quit prompt
+                        self.draw(&stdout, screen_size)?;
+                    }
                 }
             }
             // update fetch queue
@@ -1310,31 +3630,42 @@ impl Toad {
             let mut unhandled_pages = Vec::new();
             let mut undrawn_pages = Vec::new();
 
-            for (index, (page_id, url, handle)) in self.fetches.iter_mut().enumerate() {
-                if handle.is_finished() {
-                    let Ok(polled) = tokio::join!(handle).0 else {
+            for (index, fetch) in self.fetches.iter_mut().enumerate() {
+                if fetch.handle.is_finished() {
+                    let Ok((polled, response)) = tokio::join!(&mut fetch.handle).0 else {
                         continue;
                     };
                     death_queue.push(index);
+                    let page_id = fetch.page_id;
+                    let url = fetch.request.url.clone();
+                    let mut request = fetch.request.clone();
+                    request.finish(response);
+                    if let Some(page) = self.tabs.find_identifier_mut(page_id) {
+                        page.requests.push(request);
+                    }
                     let Some(data) = polled else {
-                        if let Some(page) = self.tabs.find_identifier_mut(*page_id) {
-                            page.debug_info
-                                .info_log
-                                .push(format!("Failed to get data of {url}"));
+                        if let Some(page) = self.tabs.find_identifier_mut(page_id) {
+                            page.failed_fetches += 1;
+                            any_changed = true;
                         }
                         continue;
                     };
                     any_changed = true;
                     if let DataEntry::Webpage(webpage) = data {
-                        unhandled_pages.push((*page_id, webpage));
+                        unhandled_pages.push((page_id, webpage));
                     } else {
                         let is_stylesheet = matches!(data, DataEntry::PlainText(_));
-                        self.fetched_assets.insert(url.clone(), data);
+                        self.fetched_assets.insert(url, data);
 
                         // refresh page with this page_id
-                        if let Some(page) = self.tabs.find_identifier_mut(*page_id) {
+                        if let Some(page) = self.tabs.find_identifier_mut(page_id) {
                             if is_stylesheet {
-                                refresh_style(page, &self.fetched_assets);
+                                refresh_style(
+                                    page,
+                                    &self.fetched_assets,
+                                    &self.user_stylesheet,
+                                    &self.blocker,
+                                );
                             }
                             undrawn_pages.push(page.indentifier);
                         }
@@ -1361,16 +3692,35 @@ impl Toad {
                         let PageDrawFuture::Immediate(h) = handle.take().unwrap() else {
                             panic!()
                         };
-                        Some(h)
+                        Some(*h)
                     }
                 };
-                if let Some((draw, scroll_to)) = result
+                if let Some((mut draw, scroll_to)) = result
                     && let Some(page) = self.tabs.find_identifier_mut(*page_id)
                 {
+                    page.save_scroll_anchor();
                     if let Some(scroll) = scroll_to
                         && !page.has_been_scrolled
                     {
                         page.scroll_y = scroll;
+                    } else if let Some(anchor) = page.scroll_anchor.take()
+                        && let Some(scroll) = draw.anchored_scroll(&anchor)
+                    {
+                        page.scroll_y = scroll;
+                    }
+                    if let Some(find) = &mut page.find {
+                        find.search(&draw.calls);
+                    }
+                    // focus the autofocused field, unless the user has already moved around the page
+                    if draw.autofocus.is_some()
+                        && page.tab_index.is_none()
+                        && !page.has_been_scrolled
+                    {
+                        page.tab_index = draw.autofocus;
+                    }
+                    // a redraw of the same page keeps what has been filled in
+                    if let Some(old) = &page.cached_draw {
+                        draw.keep_values(old);
                     }
                     page.cached_draw = Some(draw);
                     any_changed = true;
@@ -1393,6 +3743,13 @@ impl Toad {
             for (id, mut page) in unhandled_pages.into_iter() {
                 self.handle_new_page(&mut page).await;
                 if let Some(p) = self.tabs.find_identifier_mut(id) {
+                    // keep the scroll of a restored page
+                    if p.loading && p.has_been_scrolled {
+                        page.scroll_y = p.scroll_y;
+                        page.has_been_scrolled = true;
+                    }
+                    // the page's own request was logged to the page it replaces
+                    page.requests.splice(0..0, p.requests.drain(..));
                     *p = *page;
                 }
             }
@@ -1402,6 +3759,7 @@ impl Toad {
                 self.draw(&stdout, screen_size)?;
             }
         }
+        self.save_session();
         terminal::disable_raw_mode()?;
 
         // clean up styling and move cursor to bottom of screen
@@ -1409,15 +3767,29 @@ impl Toad {
             stdout,
             cursor::Show,
             cursor::MoveTo(0, screen_size.1 - 3),
-            event::DisableMouseCapture
+            event::DisableMouseCapture,
+            event::DisableBracketedPaste
         )?;
         Ok(())
     }
     fn draw(&mut self, mut stdout: &Stdout, screen_size: (u16, u16)) -> io::Result<()> {
+        // the suggestion dropdown is drawn over the page, so rows it no longer covers have to be redrawn
+        let suggestion_rows = self
+            .current_input_box
+            .as_ref()
+            .map(InputBox::suggestion_rows)
+            .unwrap_or_default();
+        if suggestion_rows < self.suggestion_rows {
+            self.prev_buffer = None;
+        }
+        self.suggestion_rows = suggestion_rows;
         self.draw_current_page(stdout, screen_size)?;
         if let Some(input_box) = &self.current_input_box {
             input_box.draw(stdout)?;
         }
+        if let Some(text_area) = &self.current_text_area {
+            text_area.draw(stdout)?;
+        }
         stdout.flush()
     }
     fn draw_topbar(&self, buffer: &mut Buffer, screen_size: (u16, u16)) {
@@ -1492,6 +3864,15 @@ impl Toad {
                 text = text[..max_width].to_string();
             }
             buffer.draw_str(4 * 3, 1, &text, &DEFAULT_DRAW_CTX, None);
+            if self.bookmarks.get(url.as_str()).is_some() {
+                buffer.draw_str(
+                    screen_width as u16 - 4 * 3 - 2,
+                    1,
+                    "★",
+                    &DEFAULT_DRAW_CTX,
+                    None,
+                );
+            }
         }
 
         if self.last_mouse_y == 1 {
@@ -1513,9 +3894,103 @@ impl Toad {
                 );
             }
         }
+        if let Some(Some(find)) = self.tabs.get(self.tab_index).map(|f| &f.find) {
+            let text = find.counter_text();
+            let x = (screen_width as u16 - 4 * 3).saturating_sub(text.width() as u16 + 1);
+            buffer.draw_rect(
+                x - 1,
+                1,
+                text.width() as u16 + 2,
+                1,
+                self.settings.theme.interactive_color,
+            );
+            buffer.draw_str(x, 1, &text, &DEFAULT_DRAW_CTX, None);
+        }
+        if self.quit_pending {
+            let key = self
+                .keybinds
+                .keys_for(Action::Quit)
+                .next()
+                .map(|f| f.name())
+                .unwrap_or_default();
+            buffer.draw_rect(
+                4 * 3,
+                1,
+                screen_width as u16 - 4 * 3 * 2,
+                1,
+                self.settings.theme.background_color,
+            );
+            buffer.draw_str(
+                4 * 3,
+                1,
+                &format!("press {key} again to quit"),
+                &DEFAULT_DRAW_CTX,
+                None,
+            );
+        }
         buffer.draw_str(0, 1, "[←][→] [↻] ", &DEFAULT_DRAW_CTX, None);
         buffer.draw_str(screen_width as u16 - 4, 1, "[≡]", &DEFAULT_DRAW_CTX, None);
     }
+    /// Shows a message in the status line for [STATUS_MESSAGE_DURATION] seconds
+    fn show_status(&mut self, message: &str) {
+        self.status_message = Some((message.to_string(), Instant::now()));
+    }
+    /// Draws the status line at the bottom of the screen, if there's anything to show.
+    ///
+    /// Shows the status message or where the hovered element leads on the left, and the page's asset fetches on the right.
+    fn draw_status_line(&self, buffer: &mut Buffer, screen_size: (u16, u16)) {
+        let Some(page) = self.tabs.get(self.tab_index) else {
+            return;
+        };
+        let left = if let Some((message, _)) = &self.status_message {
+            message.clone()
+        } else {
+            let base = Url::options().base_url(page.url.as_ref());
+            match &page.hovered_interactable {
+                Some(Interactable::Link(path)) => base
+                    .parse(path)
+                    .map(|f| f.to_string())
+                    .unwrap_or(path.clone()),
+                Some(Interactable::InputSubmit(form, submitter)) => page
+                    .cached_draw
+                    .as_ref()
+                    .and_then(|f| f.forms.get(*form))
+                    .and_then(|form| {
+                        let url = form.action_url(Some(*submitter), page.url.as_ref())?;
+                        let submission = form.submission(Some(*submitter), url);
+                        Some(format!("{} {}", submission.method, submission.url))
+                    })
+                    .unwrap_or_default(),
+                _ => String::new(),
+            }
+        };
+        let pending = self
+            .fetches
+            .iter()
+            .filter(|f| f.page_id == page.indentifier)
+            .count();
+        let mut right = Vec::new();
+        if pending > 0 {
+            right.push(format!("{pending} loading"));
+        }
+        if page.failed_fetches > 0 {
+            right.push(format!("{} failed", page.failed_fetches));
+        }
+        if page.blocked_requests > 0 {
+            right.push(format!("{} blocked", page.blocked_requests));
+        }
+        let right = right.join(", ");
+        if left.is_empty() && right.is_empty() {
+            return;
+        }
+        let (screen_width, screen_height) = screen_size;
+        let y = screen_height - 1;
+        buffer.draw_rect(0, y, screen_width, 1, self.settings.theme.ui_color);
+        let right_x = screen_width.saturating_sub(right.width() as u16 + 1);
+        let left = truncate_to_width(&left, right_x.saturating_sub(2) as usize);
+        buffer.draw_str(1, y, &left, &DEFAULT_DRAW_CTX, None);
+        buffer.draw_str(right_x, y, &right, &DEFAULT_DRAW_CTX, None);
+    }
     fn generate_cached_image_sizes(&self) -> HashMap<Url, (u16, u16)> {
         if !self.settings.images_enabled {
             return HashMap::new();
@@ -1539,6 +4014,14 @@ impl Toad {
         let (screen_width, screen_height) = screen_size;
         let mut send_to_draw_queue = false;
 
+        // the inspector follows the page of the current tab
+        if let Some(inspector) = &self.inspector
+            && let Some(root) = &page.root
+            && !Arc::ptr_eq(&inspector.root, root)
+        {
+            self.inspector = Some(Inspector::new(Arc::clone(root)));
+        }
+
         let mut draws = if let Some(calls) = &page.cached_draw {
             calls.clone()
         } else {
@@ -1558,25 +4041,48 @@ impl Toad {
                 interactables: Vec::new(),
                 content_height: 0,
                 forms: Vec::new(),
+                clips: Vec::new(),
+                boxes: Vec::new(),
+                autofocus: None,
+                invalid: None,
+                root: None,
             }
         };
 
         page.hovered_interactable = None;
         let mut buffer = Buffer::empty(screen_width, screen_height, self.settings.theme);
+        let screen_clips = draws.screen_clips(page.scroll_y, &page.scroll_x);
 
         while let Some(call) = draws.calls.pop() {
+            let shift_x = match call.clip().map(|index| screen_clips[index]) {
+                // entirely clipped away
+                Some(None) => continue,
+                Some(Some(clip)) => {
+                    buffer.set_clip(Some(clip));
+                    clip.shift_x
+                }
+                None => {
+                    buffer.set_clip(None);
+                    0
+                }
+            };
             match call {
                 DrawCall::ClearColor(color) => {
                     buffer.clear_color(color);
                 }
-                DrawCall::Rect(x, y, w, h, color) => {
+                DrawCall::Clip(..) => {}
+                DrawCall::Rect(x, y, w, h, color, _) => {
                     let x = x / EM;
                     let mut y = y / LH;
 
                     let w = actualize_actual(w, &draws.unknown_sized_elements);
                     let h = actualize_actual(h, &draws.unknown_sized_elements);
-                    let w = w / EM;
+                    let mut w = w / EM;
                     let mut h = h / LH;
+                    if x < shift_x {
+                        w = w.saturating_sub(shift_x - x);
+                    }
+                    let x = x.saturating_sub(shift_x);
                     let bottom_out = y < page.scroll_y;
 
                     if bottom_out && y + h < page.scroll_y {
@@ -1594,14 +4100,14 @@ impl Toad {
 
                     buffer.draw_rect(x, y, w, h, color);
                 }
-                DrawCall::Image(x, y, w, h, url) => {
+                DrawCall::Image(x, y, w, h, url, _) => {
                     if !self.settings.images_enabled {
                         continue;
                     }
                     let Some(DataEntry::Image(image)) = self.fetched_assets.get(&url) else {
                         continue;
                     };
-                    let x = x / EM;
+                    let x = (x / EM).saturating_sub(shift_x);
                     let mut y = y / LH;
 
                     let w = actualize_actual(w, &draws.unknown_sized_elements);
@@ -1655,8 +4161,8 @@ impl Toad {
                         );
                     }
                 }
-                DrawCall::DrawInput(x, y, w, h, interactable_index, mut placeholder_text) => {
-                    let x = x / EM;
+                DrawCall::DrawInput(x, y, w, h, interactable_index, mut placeholder_text, _) => {
+                    let x = (x / EM).saturating_sub(shift_x);
                     let mut y = y / LH;
 
                     let w = actualize_actual(w, &draws.unknown_sized_elements);
@@ -1681,44 +4187,94 @@ impl Toad {
                     }
                     let y = y.saturating_sub(page.scroll_y);
 
-                    let hovered = page.tab_index.is_some_and(|f| f == interactable_index);
-                    let interactable = &draws.interactables[interactable_index];
-                    let (form, name) = match interactable {
-                        Interactable::InputText(form, text, width, _) => {
-                            let new =
-                                Interactable::InputText(*form, text.clone(), *width, Some((x, y)));
-                            page.cached_draw.as_mut().unwrap().interactables[interactable_index] =
-                                new;
+                    let hovered = interactable_index.is_some_and(|f| page.tab_index == Some(f));
+                    let interactable = interactable_index.map(|f| &draws.interactables[f]);
+                    if let Some(interactable_index) = interactable_index {
+                        let interactable = &draws.interactables[interactable_index];
+                        let (form, field) = match interactable {
+                            Interactable::InputText(form, field, width, _) => {
+                                let new =
+                                    Interactable::InputText(*form, *field, *width, Some((x, y)));
+                                page.cached_draw.as_mut().unwrap().interactables
+                                    [interactable_index] = new;
+
+                                (form, Some(*field))
+                            }
+                            Interactable::InputTextArea(form, field, size, _) => {
+                                let new =
+                                    Interactable::InputTextArea(*form, *field, *size, Some((x, y)));
+                                page.cached_draw.as_mut().unwrap().interactables
+                                    [interactable_index] = new;
 
-                            (form, text.clone())
+                                (form, Some(*field))
+                            }
+                            Interactable::InputSubmit(form, _) => (form, None),
+                            _ => {
+                                panic!()
+                            }
+                        };
+                        let form = &draws.forms[*form];
+                        if hovered {
+                            page.hovered_interactable = Some(interactable.clone());
                         }
-                        Interactable::InputSubmit(form) => (form, String::from("Submit Button")),
-                        _ => {
-                            panic!()
+                        if let Some(field) = field.and_then(|f| form.text_fields.get(f)) {
+                            placeholder_text =
+                                if field.constraints.as_ref().is_some_and(Constraints::masked) {
+                                    mask(&field.value)
+                                } else {
+                                    field.value.clone()
+                                };
                         }
-                    };
-                    let form = &draws.forms[*form];
-                    if hovered {
-                        page.hovered_interactable = Some(interactable.clone());
-                    }
-                    if let Some(value) = form.text_fields.get(&name) {
-                        placeholder_text = value.clone();
                     }
 
+                    // text areas show every row of their text, other inputs a single line.
+                    // disabled inputs are laid out with their rows already split
+                    let lines = match interactable {
+                        Some(Interactable::InputTextArea(..)) => {
+                            wrap_text(&placeholder_text, w.saturating_sub(3))
+                        }
+                        Some(_) => vec![placeholder_text],
+                        None => placeholder_text.split('\n').map(String::from).collect(),
+                    };
                     for i in 0..h {
+                        let line = (i + image_row_offset)
+                            .checked_sub(1)
+                            .and_then(|f| lines.get(f as usize));
                         buffer.draw_input_box(
                             x,
                             y + i,
                             i + image_row_offset,
                             w,
                             h + image_row_offset,
-                            &placeholder_text,
+                            line.map(String::as_str).unwrap_or_default(),
                             hovered,
                             interactable_index,
                         );
                     }
                 }
-                DrawCall::Text(x, y, text, mut ctx, parent_width, parent_interactable) => {
+                DrawCall::Text(x, y, mut text, mut ctx, parent_width, parent_interactable, _) => {
+                    // checkboxes are drawn as they currently are, not as they were laid out
+                    if let Some(interactable) = parent_interactable
+                        && let Interactable::InputCheck(form, index) =
+                            draws.interactables[interactable]
+                        && let Some(checkable) = draws.forms[form].checkables.get(index)
+                    {
+                        text = String::from(checkable.marker());
+                    }
+                    if let Some(interactable) = parent_interactable
+                        && let Interactable::InputSelect(form, index) =
+                            draws.interactables[interactable]
+                        && let Some(select) = draws.forms[form].selects.get(index)
+                    {
+                        text = select.display_text();
+                    }
+                    if let Some(interactable) = parent_interactable
+                        && let Interactable::InputFile(form, index) =
+                            draws.interactables[interactable]
+                        && let Some(input) = draws.forms[form].files.get(index)
+                    {
+                        text = input.display_text();
+                    }
                     if let Some(interactable) = parent_interactable
                         && let Some(tab_amt) = page.tab_index
                         && tab_amt == interactable
@@ -1726,6 +4282,11 @@ impl Toad {
                         page.hovered_interactable = Some(draws.interactables[interactable].clone());
                         ctx.background_color = Specified(self.settings.theme.interactive_color);
                     }
+                    let highlights = page
+                        .find
+                        .as_ref()
+                        .map(|find| find.highlights(x, y, &text))
+                        .unwrap_or_default();
                     let x = x / EM;
                     let y = y / LH;
                     let width = actualize_actual(parent_width, &draws.unknown_sized_elements) / EM;
@@ -1739,14 +4300,53 @@ impl Toad {
                         Some(TextAlignment::Right) if width > text_len => width - text_len,
                         _ => 0,
                     };
-                    let x = x + offset_x;
+                    let mut x = x + offset_x;
+
+                    // cut off the start of text that has been scrolled out to the left
+                    let mut hidden_chars = 0;
+                    if x < shift_x {
+                        let mut hidden = shift_x - x;
+                        let mut chars = text.chars();
+                        while hidden > 0
+                            && let Some(char) = chars.next()
+                        {
+                            hidden = hidden.saturating_sub(char.width().unwrap_or_default() as u16);
+                            hidden_chars += 1;
+                        }
+                        text = chars.collect();
+                        x = shift_x;
+                    }
+                    let x = x - shift_x;
 
                     if let Some(y) = y.checked_sub(page.scroll_y) {
                         buffer.draw_str(x, y, &text, &ctx, parent_interactable);
+
+                        // highlight find matches
+                        for (start, len, is_current) in highlights {
+                            if start + len <= hidden_chars {
+                                continue;
+                            }
+                            let visible_start = start.saturating_sub(hidden_chars);
+                            let visible_len = start + len - hidden_chars - visible_start;
+                            let offset: String = text.chars().take(visible_start).collect();
+                            let matched: String =
+                                text.chars().skip(visible_start).take(visible_len).collect();
+                            buffer.highlight(
+                                x + offset.width() as u16,
+                                y,
+                                matched.width() as u16,
+                                if is_current {
+                                    FIND_CURRENT_COLOR
+                                } else {
+                                    FIND_MATCH_COLOR
+                                },
+                            );
+                        }
                     }
                 }
             }
         }
+        buffer.set_clip(None);
         if draws.content_height / LH > screen_height {
             // draw scrollbar
             let page_height = screen_height - 3;
@@ -1763,7 +4363,55 @@ impl Toad {
         }
         page.page_height = Some(draws.content_height);
 
+        if let Some(inspector) = &mut self.inspector {
+            inspector.highlight(&mut buffer, &draws, page.scroll_y, screen_height - 1);
+            let inspection = inspector.inspect(
+                &page.global_style,
+                &page.url,
+                &self.history.visited_urls(),
+                self.settings.theme.is_dark,
+                self.settings.css_enabled,
+            );
+            inspector.draw(
+                &mut buffer,
+                screen_size,
+                &draws,
+                &inspection,
+                self.settings.theme,
+            );
+        }
+        // the reason the form couldn't be submitted is shown under the invalid field
+        if let Some((interactable, message)) = &draws.invalid
+            && let Some((x, y)) = buffer.interactable_bottom_left(*interactable)
+            && y < screen_height - 1
+        {
+            let message = format!(" {message} ");
+            buffer.draw_str(x, y, &message, &INVALID_FIELD_DRAW_CTX, None);
+        }
+        if let Some(popup) = &mut self.select_popup {
+            match draws
+                .forms
+                .get(popup.form)
+                .and_then(|f| f.selects.get(popup.select))
+            {
+                Some(select) if popup.page_id == page.indentifier => {
+                    popup.draw(&mut buffer, select, screen_size, self.settings.theme);
+                }
+                _ => self.select_popup = None,
+            }
+        }
+        if let Some(picker) = &mut self.file_picker {
+            if picker.page_id == page.indentifier {
+                picker.draw(&mut buffer, screen_size, self.settings.theme);
+            } else {
+                self.file_picker = None;
+            }
+        }
+        if let Some(hint_mode) = &self.hint_mode {
+            hint_mode.draw(&mut buffer);
+        }
         self.draw_topbar(&mut buffer, screen_size);
+        self.draw_status_line(&mut buffer, screen_size);
 
         queue!(stdout, cursor::MoveTo(0, 0))?;
         buffer.render(&mut stdout, self.prev_buffer.as_ref(), 0, 0)?;
@@ -1787,15 +4435,19 @@ async fn main() -> io::Result<()> {
         Url::parse("toad://toad.png").unwrap(),
         DataEntry::Image(image::load_from_memory(include_bytes!("toad.png")).unwrap()),
     );
-    toad.fetched_assets.insert(
-        Url::parse("toad://home").unwrap(),
-        DataEntry::Webpage(Box::new(parse_html(include_str!("home.html")).unwrap())),
-    );
-    toad.fetched_assets.insert(
-        Url::parse("toad://settings").unwrap(),
-        DataEntry::Webpage(Box::new(parse_html(include_str!("settings.html")).unwrap())),
-    );
-    toad.set_url(Url::parse("toad://home").unwrap()).await;
+    toad.reload_keybinds();
+    toad.reload_user_stylesheet();
+    if toad.settings.restore_session {
+        toad.session_name = toad.settings.session.clone();
+        if let Some(session) = load_session(&toad.session_name) {
+            toad.restore_session(session);
+        }
+    } else {
+        toad.session_name = String::from(LAST_SESSION_NAME);
+    }
+    if toad.tabs.is_empty() {
+        toad.set_url(Url::parse("toad://home").unwrap()).await;
+    }
     toad.run().await
 }
 
@@ -1803,7 +4455,10 @@ async fn main() -> io::Result<()> {
 mod tests {
     use reqwest::{Client, Url};
 
-    use crate::{DataEntry, DataType, get_data};
+    use crate::{
+        BLOCKED_ERROR, DataEntry, DataType, DrawCall, FormField, FormValue, Toad, ToadSettings,
+        bookmarks_file_path, draw_page, get_data, parse_html, toad_action_allowed,
+    };
 
     #[tokio::test]
     async fn test_base64_urls() {
@@ -1813,7 +4468,10 @@ mod tests {
         AAAAAQAAAAWgBAABAAAAlAAAAAAAAAACAAEAAgAEAAAAUjk4AAIABwAEAAAAMDEwMAAAAABMz8BIJY/XoAAAABdJREFUGFdjZPh/4f+lywz/a14y/L8AADvICKjr7H/4
         AAAAAElFTkSuQmCC";
         let url = Url::parse(b64).unwrap();
-        let DataEntry::Image(resp) = get_data(url, DataType::Image, Client::new()).await.unwrap()
+        let DataEntry::Image(resp) = get_data(url, DataType::Image, Client::new())
+            .await
+            .0
+            .unwrap()
         else {
             panic!()
         };
@@ -1823,4 +4481,294 @@ mod tests {
             [0, 255, 208, 255, 209, 163, 255, 124, 233, 0, 255, 208]
         );
     }
+
+    #[test]
+    fn test_overflow_clip() {
+        let page = parse_html(&format!(
+            "<html><body><pre style=\"text-overflow: ellipsis\">{}</pre></body></html>",
+            "a".repeat(200)
+        ))
+        .unwrap();
+        let (draw, _) = draw_page(
+            page.root.unwrap(),
+            Default::default(),
+            (80, 24),
+            ToadSettings::default(),
+            None,
+            Vec::new(),
+            Default::default(),
+        );
+        // the pre should not wrap, and be clipped to the width of the screen
+        assert_eq!(draw.clips.len(), 1);
+        let clip = &draw.clips[0];
+        assert!(clip.info.scrollable && clip.info.ellipsis);
+        assert_eq!(clip.width.get_pixels(), Some(80 * crate::EM));
+        assert_eq!(clip.info.content_width, 200 * crate::EM);
+        let lines = draw
+            .calls
+            .iter()
+            .filter(|call| matches!(call, DrawCall::Text(.., Some(0))))
+            .count();
+        assert_eq!(lines, 1);
+    }
+    #[test]
+    fn test_max_width() {
+        let page = parse_html(&format!(
+            "<html><body><div style=\"max-width: 20em\"><p>{}</p></div></body></html>",
+            "word ".repeat(40)
+        ))
+        .unwrap();
+        let (draw, _) = draw_page(
+            page.root.unwrap(),
+            Default::default(),
+            (80, 24),
+            ToadSettings::default(),
+            None,
+            Vec::new(),
+            Default::default(),
+        );
+        // the text wraps within the max width instead of the screen
+        let lines: Vec<&String> = draw
+            .calls
+            .iter()
+            .filter_map(|call| match call {
+                DrawCall::Text(_, _, text, ..) => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|f| f.trim_end().len() <= 20));
+    }
+    #[test]
+    fn test_form_inputs() {
+        let page = parse_html(
+            "<html><body><form action=\"/search\">\
+            <input type=\"hidden\" name=\"token\" value=\"abc\">\
+            <input type=\"checkbox\" name=\"safe\" checked>\
+            <input type=\"hidden\" name=\"token\" value=\"def\">\
+            <input type=\"checkbox\" name=\"tag\" value=\"a\">\
+            <input type=\"radio\" name=\"sort\" value=\"new\" checked>\
+            <input type=\"radio\" name=\"sort\" value=\"top\">\
+            </form><textarea>notes</textarea></body></html>",
+        )
+        .unwrap();
+        let (draw, _) = draw_page(
+            page.root.unwrap(),
+            Default::default(),
+            (80, 24),
+            ToadSettings::default(),
+            None,
+            Vec::new(),
+            Default::default(),
+        );
+        let mut form = draw.forms[0].clone();
+        assert_eq!(draw.interactables.len(), 4);
+        // a text area outside of a form is drawn, but can't be used
+        assert!(
+            draw.calls
+                .iter()
+                .any(|f| matches!(f, DrawCall::DrawInput(.., None, text, _) if text == "notes"))
+        );
+        let data = |form: &crate::Form| form.data(None);
+        let pair = |k: &str, v: &str| (String::from(k), FormValue::Text(String::from(v)));
+        assert_eq!(
+            data(&form),
+            [
+                pair("token", "abc"),
+                pair("safe", "on"),
+                pair("token", "def"),
+                pair("sort", "new")
+            ]
+        );
+
+        form.toggle(0);
+        form.toggle(1);
+        form.toggle(3);
+        assert_eq!(
+            data(&form),
+            [
+                pair("token", "abc"),
+                pair("token", "def"),
+                pair("tag", "a"),
+                pair("sort", "top")
+            ]
+        );
+        assert_eq!(form.checkables[2].marker(), "( )");
+        assert_eq!(form.checkables[3].marker(), "(•)");
+    }
+    #[test]
+    fn test_redraw_keeps_form_values() {
+        let page = parse_html(
+            "<html><body><form action=\"/search\">\
+            <input type=\"text\" name=\"q\">\
+            <input type=\"checkbox\" name=\"safe\">\
+            </form></body></html>",
+        )
+        .unwrap();
+        let draw = |root| {
+            draw_page(
+                root,
+                Default::default(),
+                (80, 24),
+                ToadSettings::default(),
+                None,
+                Vec::new(),
+                Default::default(),
+            )
+            .0
+        };
+        let root = page.root.unwrap();
+        let mut old = draw(root.clone());
+        old.forms[0].toggle(0);
+        old.forms[0].text_fields[0].value = String::from("toad");
+
+        let mut redrawn = draw(root.clone());
+        redrawn.keep_values(&old);
+        assert!(redrawn.forms[0].checkables[0].checked);
+        assert_eq!(redrawn.forms[0].text("q"), Some("toad"));
+
+        // a different page starts with its own values
+        let other = parse_html(
+            "<html><body><form action=\"/search\">\
+            <input type=\"text\" name=\"q\">\
+            <input type=\"checkbox\" name=\"safe\">\
+            </form></body></html>",
+        )
+        .unwrap();
+        let mut other = draw(other.root.unwrap());
+        other.keep_values(&old);
+        assert!(!other.forms[0].checkables[0].checked);
+    }
+    #[test]
+    fn test_form_validation() {
+        let page = parse_html(
+            "<html><body><form action=\"/login\">\
+            <input type=\"text\" name=\"user\" required autofocus>\
+            <input type=\"password\" name=\"pass\" value=\"hunter2\">\
+            <input type=\"text\" name=\"code\" disabled>\
+            <input type=\"checkbox\" name=\"terms\" required>\
+            <input type=\"submit\">\
+            </form></body></html>",
+        )
+        .unwrap();
+        let (draw, _) = draw_page(
+            page.root.unwrap(),
+            Default::default(),
+            (80, 24),
+            ToadSettings::default(),
+            None,
+            Vec::new(),
+            Default::default(),
+        );
+        // the disabled field can't be used
+        assert_eq!(draw.interactables.len(), 4);
+        assert_eq!(draw.autofocus, Some(0));
+        // the password's value isn't drawn
+        assert!(
+            draw.calls.iter().any(
+                |f| matches!(f, DrawCall::DrawInput(.., Some(1), text, _) if text == "•••••••")
+            )
+        );
+        assert!(
+            draw.calls
+                .iter()
+                .any(|f| matches!(f, DrawCall::DrawInput(.., None, _, _)))
+        );
+
+        let mut form = draw.forms[0].clone();
+        assert_eq!(
+            form.validate(),
+            Err((
+                FormField::Text(0),
+                String::from("Please fill in this field.")
+            ))
+        );
+        form.text_fields[0].value = String::from("toad");
+        assert_eq!(form.validate().map_err(|f| f.0), Err(FormField::Check(0)));
+        form.toggle(0);
+        assert_eq!(form.validate(), Ok(()));
+
+        // the filled in fields of the form are what autofill saves
+        let login = form.login(String::from("https://example.com")).unwrap();
+        assert_eq!(login.user, "toad");
+        assert_eq!(login.get("pass"), Some("hunter2"));
+    }
+    #[test]
+    fn test_scroll_anchor() {
+        let mut html = String::from("<html><body>");
+        for i in 0..40 {
+            html += &format!("<p>paragraph {i} {}</p>", "word ".repeat(30));
+        }
+        html += "</body></html>";
+        let page = parse_html(&html).unwrap();
+        let draw = |width| {
+            draw_page(
+                page.root.clone().unwrap(),
+                Default::default(),
+                (width, 24),
+                ToadSettings::default(),
+                None,
+                Vec::new(),
+                Default::default(),
+            )
+            .0
+        };
+        let (wide, narrow) = (draw(200), draw(60));
+        let anchor = wide.scroll_anchor(20).unwrap();
+        // the same element is still at the top after reflowing to a narrower width
+        let scroll = narrow.anchored_scroll(&anchor).unwrap();
+        assert!(scroll > 20);
+        assert_eq!(
+            narrow.scroll_anchor(scroll).unwrap().element,
+            anchor.element
+        );
+        assert_eq!(wide.anchored_scroll(&anchor), Some(20));
+    }
+    #[test]
+    fn test_toad_actions() {
+        let toad_page = Url::parse("toad://bookmarks").ok();
+        let web_page = Url::parse("https://example.com/").ok();
+        // actions changing settings or files, which web pages could otherwise send forms to
+        for action in [
+            "toad://bookmarks/rename_bookmark?id=0",
+            "toad://bookmarks/import_bookmarks",
+            "toad://bookmarks/export_bookmarks",
+            "toad://history/clear_history?range=all",
+            "toad://settings/open_session?name=work",
+            "toad://settings/delete_session?name=work",
+            "toad://settings/save_session",
+            "toad://settings/set_search_engine",
+            "toad://settings/add_keyword",
+            "toad://settings/remove_keyword?keyword=w",
+            "toad://settings/edit_keybinds",
+            "toad://settings/edit_user_stylesheet",
+        ] {
+            let action = Url::parse(action).unwrap();
+            assert!(toad_action_allowed(toad_page.as_ref(), &action));
+            assert!(!toad_action_allowed(web_page.as_ref(), &action));
+            assert!(!toad_action_allowed(None, &action));
+        }
+        let search = Url::parse("https://example.com/search").unwrap();
+        assert!(toad_action_allowed(web_page.as_ref(), &search));
+
+        // bookmark files can't be outside the data directory
+        assert!(bookmarks_file_path("").is_some());
+        assert!(bookmarks_file_path("backup/bookmarks.html").is_some());
+        assert!(bookmarks_file_path("../bookmarks.html").is_none());
+        assert!(bookmarks_file_path("/etc/passwd").is_none());
+    }
+    #[test]
+    fn test_blocked_page() {
+        let mut toad = Toad::default();
+        toad.blocker.add_list("||popups.example^$document");
+        let page = toad.load_url(Url::parse("https://popups.example/win").unwrap());
+        // the page isn't fetched, and the blocked request is logged to it
+        assert!(toad.fetches.is_empty());
+        assert_eq!(page.blocked_requests, 1);
+        assert_eq!(
+            page.requests[0].response.error.as_deref(),
+            Some(BLOCKED_ERROR)
+        );
+        assert_eq!(page.get_title().trim(), "Blocked");
+    }
 }
@@ -0,0 +1,234 @@
+use std::time::{Duration, Instant};
+
+use reqwest::{Method, RequestBuilder, Response, Url};
+
+use crate::parsing::sanitize;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RequestKind {
+    Document,
+    Stylesheet,
+    Image,
+    /// Page a `<meta http-equiv="refresh">` pointed to
+    Redirect,
+    Form,
+}
+impl RequestKind {
+    fn name(&self) -> &'static str {
+        match self {
+            RequestKind::Document => "document",
+            RequestKind::Stylesheet => "stylesheet",
+            RequestKind::Image => "image",
+            RequestKind::Redirect => "redirect",
+            RequestKind::Form => "form",
+        }
+    }
+}
+
+/// What came back from a request
+#[derive(Clone, Default, Debug)]
+pub struct ResponseInfo {
+    pub status: Option<u16>,
+    pub content_type: Option<String>,
+    /// Size of the body, in bytes
+    pub size: Option<usize>,
+    /// Where the request ended up, if it was redirected
+    pub final_url: Option<Url>,
+    pub error: Option<String>,
+}
+impl ResponseInfo {
+    /// Info of data that didn't have to be fetched, like `data:` URLs
+    pub fn local(size: usize) -> Self {
+        Self {
+            size: Some(size),
+            ..Default::default()
+        }
+    }
+    pub fn fail<T>(&mut self, error: impl ToString) -> Option<T> {
+        self.error = Some(error.to_string());
+        None
+    }
+}
+
+/// Sends a request, recording the status and headers of its response
+pub async fn send(request: RequestBuilder, info: &mut ResponseInfo) -> Option<Response> {
+    let (client, request) = request.build_split();
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => return info.fail(e),
+    };
+    let url = request.url().clone();
+    let response = match client.execute(request).await {
+        Ok(response) => response,
+        Err(e) => return info.fail(e),
+    };
+    info.status = Some(response.status().as_u16());
+    info.content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|f| f.to_str().ok())
+        .map(String::from);
+    if *response.url() != url {
+        info.final_url = Some(response.url().clone());
+    }
+    Some(response)
+}
+pub async fn read_bytes(response: Response, info: &mut ResponseInfo) -> Option<Vec<u8>> {
+    match response.bytes().await {
+        Ok(bytes) => {
+            info.size = Some(bytes.len());
+            Some(bytes.into())
+        }
+        Err(e) => info.fail(e),
+    }
+}
+pub async fn read_text(response: Response, info: &mut ResponseInfo) -> Option<String> {
+    match response.text().await {
+        Ok(text) => {
+            info.size = Some(text.len());
+            Some(text)
+        }
+        Err(e) => info.fail(e),
+    }
+}
+
+/// A request made for a page
+#[derive(Clone, Debug)]
+pub struct NetworkRequest {
+    pub kind: RequestKind,
+    pub method: Method,
+    pub url: Url,
+    pub started: Instant,
+    /// How long the request took, or [None] while it's in progress
+    pub duration: Option<Duration>,
+    pub response: ResponseInfo,
+}
+impl NetworkRequest {
+    pub fn new(kind: RequestKind, method: Method, url: Url) -> Self {
+        Self {
+            kind,
+            method,
+            url,
+            started: Instant::now(),
+            duration: None,
+            response: ResponseInfo::default(),
+        }
+    }
+    pub fn finish(&mut self, response: ResponseInfo) {
+        self.duration = Some(self.started.elapsed());
+        self.response = response;
+    }
+    pub fn failed(&self) -> bool {
+        self.response.error.is_some()
+            || self
+                .response
+                .status
+                .is_some_and(|status| !(200..400).contains(&status))
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f32 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f32 / (1024.0 * 1024.0))
+    }
+}
+/// Generates the request list of toad://network
+pub fn requests_to_html(requests: &[NetworkRequest]) -> String {
+    if requests.is_empty() {
+        return String::from("<p>no requests.</p>");
+    }
+    let failed = requests.iter().filter(|f| f.failed()).count();
+    let total: usize = requests.iter().filter_map(|f| f.response.size).sum();
+    let mut html = format!(
+        "<p class=\"summary\">{} requests, {failed} failed, {} transferred</p>",
+        requests.len(),
+        format_size(total)
+    );
+    for request in requests {
+        let status = match (request.response.status, request.duration) {
+            (_, None) => String::from("..."),
+            (Some(status), _) => status.to_string(),
+            (None, _) if request.response.error.is_some() => String::from("failed"),
+            (None, _) => String::from("ok"),
+        };
+        let class = if request.failed() { "failed" } else { "status" };
+        let mut details = vec![request.kind.name().to_string()];
+        if let Some(content_type) = &request.response.content_type {
+            details.push(content_type.clone());
+        }
+        if let Some(size) = request.response.size {
+            details.push(format_size(size));
+        }
+        if let Some(duration) = request.duration {
+            details.push(format!("{} ms", duration.as_millis()));
+        }
+        if let Some(final_url) = &request.response.final_url {
+            details.push(format!("redirected to {final_url}"));
+        }
+        html += &format!(
+            "<p><span class=\"{class}\">{status}</span> {} <a href=\"{}\">{}</a><br><span class=\"details\">{}</span>",
+            request.method,
+            sanitize(request.url.as_str()),
+            sanitize(request.url.as_str()),
+            sanitize(&details.join(", ")),
+        );
+        if let Some(error) = &request.response.error {
+            html += &format!("<br><span class=\"failed\">{}</span>", sanitize(error));
+        }
+        html += "</p>";
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Method, Url};
+
+    use crate::network::{NetworkRequest, RequestKind, ResponseInfo, requests_to_html};
+
+    #[test]
+    fn test_requests_to_html() {
+        let url = |f| Url::parse(f).unwrap();
+        let mut page = NetworkRequest::new(
+            RequestKind::Document,
+            Method::GET,
+            url("https://example.com/"),
+        );
+        page.finish(ResponseInfo {
+            status: Some(200),
+            content_type: Some(String::from("text/html")),
+            size: Some(2048),
+            ..Default::default()
+        });
+        let mut image = NetworkRequest::new(
+            RequestKind::Image,
+            Method::GET,
+            url("https://example.com/a.png"),
+        );
+        image.finish(ResponseInfo {
+            status: Some(404),
+            size: Some(10),
+            error: Some(String::from("couldn't decode image")),
+            ..Default::default()
+        });
+        let stylesheet = NetworkRequest::new(
+            RequestKind::Stylesheet,
+            Method::GET,
+            url("https://example.com/a.css"),
+        );
+        assert!(!page.failed());
+        assert!(image.failed());
+
+        let html = requests_to_html(&[page, image, stylesheet]);
+        assert!(html.contains("3 requests, 1 failed, 2.0 KB transferred"));
+        assert!(html.contains("<span class=\"status\">200</span> GET"));
+        assert!(html.contains("document, text/html, 2.0 KB"));
+        assert!(html.contains("<span class=\"failed\">404</span>"));
+        assert!(html.contains("couldn't decode image"));
+        assert!(html.contains("<span class=\"status\">...</span>"));
+    }
+}
@@ -122,6 +122,7 @@ pub fn parse_html(text: &str) -> Option<Webpage> {
     root.map(|root| Webpage {
         title,
         root: Some(Arc::new(root)),
+        source: Some(Arc::from(text)),
         debug_info,
         ..Default::default()
     })
@@ -174,6 +175,21 @@ fn handle_new_element(element: &Element, debug_info: &mut WebpageDebugInfo) {
     }
 }
 
+/// Elements whose closing tag can be left out, and the tags that end them when it is
+const IMPLIED_END_TAGS: &[(&str, &[&str])] = &[
+    ("option", &["option", "optgroup", "/optgroup", "/select"]),
+    ("optgroup", &["optgroup", "/select"]),
+];
+/// Whether the tag following a `<` in the (reversed) buffer is `tag`
+fn next_tag_is(buf: &[char], tag: &str) -> bool {
+    let mut chars = buf.iter().rev();
+    tag.chars()
+        .all(|char| chars.next().is_some_and(|f| f.eq_ignore_ascii_case(&char)))
+        && chars
+            .next()
+            .is_some_and(|f| f.is_whitespace() || matches!(f, '>' | '/'))
+}
+
 fn parse(
     buf: &mut Vec<char>,
     debug_info: &mut WebpageDebugInfo,
@@ -258,6 +274,14 @@ fn parse(
             }
             ParseState::WaitingForElement => {
                 if char == '<' {
+                    if let Some(parent) = &parent_tag_type
+                        && let Some((_, tags)) = IMPLIED_END_TAGS.iter().find(|f| f.0 == parent)
+                        && tags.iter().any(|tag| next_tag_is(buf, tag))
+                    {
+                        // this tag ends the parent, so leave it for the parent's parent
+                        buf.push('<');
+                        return elements;
+                    }
                     if next_is(buf, &'/') {
                         // this is where we return to parent,
                         // because we reached the parent element's closing tag
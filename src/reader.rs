@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::{element::Element, parsing::*};
+
+/// Elements that are never part of an article
+const REMOVED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "nav", "footer", "form", "button", "input", "select",
+    "textarea", "svg", "head", "title", "link", "meta",
+];
+/// Elements kept as they are in reader mode. Other elements are replaced by their contents
+const KEPT_ELEMENTS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "pre",
+    "code",
+    "samp",
+    "blockquote",
+    "ul",
+    "ol",
+    "li",
+    "dl",
+    "dt",
+    "dd",
+    "a",
+    "img",
+    "em",
+    "i",
+    "b",
+    "strong",
+    "q",
+    "cite",
+    "sup",
+    "small",
+    "figure",
+    "figcaption",
+    "table",
+    "tbody",
+    "tr",
+    "td",
+    "th",
+    "br",
+    "hr",
+];
+/// Elements whose text counts towards the score of their ancestors
+const SCORED_ELEMENTS: &[&str] = &["p", "pre", "td", "blockquote"];
+/// Block elements that can contain an article
+const CONTAINER_ELEMENTS: &[&str] = &["div", "section", "article", "main", "body", "td"];
+
+const POSITIVE_HINTS: &str = r"(?i)article|body|content|entry|main|page|post|text|blog|story|prose";
+const NEGATIVE_HINTS: &str = r"(?i)comment|footer|footnote|sidebar|sponsor|advert|share|social|related|menu|breadcrumb|cookie|popup|banner|widget|promo|navbar|masthead|hidden";
+
+/// Text of an element and all its descendants
+pub fn inner_text(element: &Element) -> String {
+    match &element.text {
+        Some(text) if element.ty.name == "node" => parse_special(text),
+        _ => element.children.iter().map(inner_text).collect(),
+    }
+}
+/// How much of an element's text is inside links, from 0 to 1
+fn link_density(element: &Element) -> f32 {
+    fn link_text_len(element: &Element) -> usize {
+        if element.ty.name == "a" {
+            return inner_text(element).trim().len();
+        }
+        element.children.iter().map(link_text_len).sum()
+    }
+    let len = inner_text(element).trim().len();
+    if len == 0 {
+        return 0.0;
+    }
+    link_text_len(element) as f32 / len as f32
+}
+
+/// Scores elements by how likely they are to be the main content of a page
+struct Scorer {
+    positive: Regex,
+    negative: Regex,
+    /// Scores of candidates, by address
+    scores: HashMap<*const Element, f32>,
+}
+impl Scorer {
+    fn new() -> Self {
+        Self {
+            positive: Regex::new(POSITIVE_HINTS).unwrap(),
+            negative: Regex::new(NEGATIVE_HINTS).unwrap(),
+            scores: HashMap::new(),
+        }
+    }
+    /// Score of an element from its type, classes and ID alone
+    fn hint_score(&self, element: &Element) -> f32 {
+        let mut score = match element.ty.name {
+            "article" => 25.0,
+            "main" => 20.0,
+            "div" => 5.0,
+            "pre" | "td" | "blockquote" => 3.0,
+            _ => 0.0,
+        };
+        let names = element.classes.join(" ")
+            + " "
+            + element.get_attribute("id").map_or("", |f| f.as_str());
+        if self.negative.is_match(&names) {
+            score -= 25.0;
+        }
+        if self.positive.is_match(&names) {
+            score += 25.0;
+        }
+        score
+    }
+    fn is_unlikely(&self, element: &Element) -> bool {
+        if REMOVED_ELEMENTS.contains(&element.ty.name) {
+            return true;
+        }
+        let names = element.classes.join(" ")
+            + " "
+            + element.get_attribute("id").map_or("", |f| f.as_str());
+        self.negative.is_match(&names) && !self.positive.is_match(&names)
+    }
+    fn add_score(&mut self, element: &Element, score: f32) {
+        let hint_score = self.hint_score(element);
+        *self.scores.entry(element).or_insert(hint_score) += score;
+    }
+    /// Gives each paragraph-like element's score to its parent and half of it to its grandparent
+    fn score<'a>(&mut self, element: &'a Element, ancestors: &mut Vec<&'a Element>) {
+        if self.is_unlikely(element) {
+            return;
+        }
+        if SCORED_ELEMENTS.contains(&element.ty.name) {
+            let text = inner_text(element);
+            let len = text.trim().chars().count();
+            if len >= 25 {
+                let score = 1.0 + text.matches(',').count() as f32 + (len as f32 / 100.0).min(3.0);
+                let mut parents = ancestors
+                    .iter()
+                    .rev()
+                    .filter(|f| CONTAINER_ELEMENTS.contains(&f.ty.name));
+                if let Some(parent) = parents.next() {
+                    self.add_score(parent, score);
+                }
+                if let Some(grandparent) = parents.next() {
+                    self.add_score(grandparent, score / 2.0);
+                }
+            }
+        }
+        ancestors.push(element);
+        for child in element.children.iter() {
+            self.score(child, ancestors);
+        }
+        ancestors.pop();
+    }
+}
+
+/// Writes the HTML of an element, keeping only what belongs in reader mode
+fn clean_html(element: &Element, scorer: &Scorer, html: &mut String) {
+    let name = element.ty.name;
+    // text and attributes are decoded and escaped again, so nothing in them is read as markup
+    if name == "node" {
+        *html += &sanitize(&parse_special(element.text.as_deref().unwrap_or_default()));
+        return;
+    }
+    if scorer.is_unlikely(element) {
+        return;
+    }
+    // lists and blocks of mostly links are navigation
+    if matches!(name, "ul" | "ol" | "div" | "section" | "table") && link_density(element) > 0.5 {
+        return;
+    }
+    let attribute = |name: &str| {
+        element
+            .get_attribute(name)
+            .map(|f| format!(" {name}=\"{}\"", sanitize(&parse_special(f))))
+            .unwrap_or_default()
+    };
+    let tag = if KEPT_ELEMENTS.contains(&name) {
+        Some(name)
+    } else if CONTAINER_ELEMENTS.contains(&name) || name == "header" {
+        // keep the line breaks of blocks
+        Some("div")
+    } else {
+        None
+    };
+    match tag {
+        Some("img") => {
+            if element.get_attribute("src").is_some() {
+                *html += &format!("<img{}{}>", attribute("src"), attribute("alt"));
+            }
+            return;
+        }
+        Some(tag @ ("br" | "hr")) => {
+            *html += &format!("<{tag}>");
+            return;
+        }
+        Some("a") => *html += &format!("<a{}>", attribute("href")),
+        Some(tag) => *html += &format!("<{tag}>"),
+        None => {}
+    }
+    for child in element.children.iter() {
+        clean_html(child, scorer, html);
+    }
+    if let Some(tag) = tag {
+        *html += &format!("</{tag}>");
+    }
+}
+
+/// Finds the scored element with the highest score, penalized by its link density
+fn find_best<'a>(element: &'a Element, scorer: &Scorer, best: &mut Option<(&'a Element, f32)>) {
+    if let Some(score) = scorer.scores.get(&(element as *const Element)) {
+        let score = score * (1.0 - link_density(element));
+        if best.is_none_or(|f| score > f.1) {
+            *best = Some((element, score));
+        }
+    }
+    for child in element.children.iter() {
+        find_best(child, scorer, best);
+    }
+}
+/// Finds the main content of a page, returning it as cleaned up HTML.
+///
+/// Elements are scored by how much text of their paragraphs isn't in links, boosted by `<article>`, `<main>`, and classes like `content`.
+pub fn extract_article(root: &Element) -> Option<String> {
+    let mut scorer = Scorer::new();
+    scorer.score(root, &mut Vec::new());
+    let mut best = None;
+    find_best(root, &scorer, &mut best);
+    let (best, score) = best?;
+    if score <= 0.0 {
+        return None;
+    }
+    let mut html = String::new();
+    clean_html(best, &scorer, &mut html);
+    Some(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsing::parse_html, reader::extract_article};
+
+    #[test]
+    fn test_extract_article() {
+        let paragraph =
+            "Toads are amphibians, closely related to frogs, with dry and leathery skin. ";
+        let html = format!(
+            "<html><body><nav><ul><li><a href=\"/\">Home</a></li><li><a href=\"/a\">About</a></li></ul></nav>\
+            <div class=\"sidebar\"><p>{}</p></div>\
+            <div id=\"content\"><h1>Toads</h1><p>{}</p><pre>let toad = 1;</pre><p>{}</p><p>&lt;b&gt;not bold&lt;/b&gt; &amp; \"quoted\"</p><img src=\"toad.png\" alt=\"a toad\"></div>\
+            <footer><p>copyright, all rights reserved, etc etc etc etc etc</p></footer></body></html>",
+            "Sponsored links, buy now, great deals. ".repeat(3),
+            paragraph.repeat(4),
+            paragraph.repeat(3),
+        );
+        let page = parse_html(&html).unwrap();
+        let article = extract_article(page.root.as_ref().unwrap()).unwrap();
+        assert!(article.contains("<h1>Toads</h1>"));
+        assert!(article.contains("<pre>let toad = 1;</pre>"));
+        assert!(article.contains("<img src=\"toad.png\" alt=\"a toad\">"));
+        assert!(article.contains("<p>&lt;b&gt;not bold&lt;/b&gt; &amp; &quot;quoted&quot;</p>"));
+        assert!(!article.contains("Sponsored"));
+        assert!(!article.contains("Home"));
+        assert!(!article.contains("copyright"));
+    }
+}
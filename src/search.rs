@@ -0,0 +1,121 @@
+use reqwest::Url;
+
+use crate::parsing::sanitize;
+
+/// Schemes of URLs that don't contain `://`, which would otherwise be mistaken for a host and port
+const SCHEMES_WITHOUT_SLASHES: &[&str] = &["toad", "data", "about", "mailto"];
+
+pub const DEFAULT_SEARCH_ENGINE: &str = "https://html.duckduckgo.com/html/?q=%s";
+
+pub fn default_search_keywords() -> Vec<(String, String)> {
+    vec![
+        (
+            String::from("w"),
+            String::from("https://en.wikipedia.org/w/index.php?search=%s"),
+        ),
+        (
+            String::from("gh"),
+            String::from("https://github.com/search?q=%s"),
+        ),
+    ]
+}
+
+/// Percent encodes everything but unreserved characters, so the terms can be put anywhere in a URL
+fn encode_search_terms(terms: &str) -> String {
+    let mut encoded = String::new();
+    for byte in terms.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded += &format!("%{byte:02X}");
+        }
+    }
+    encoded
+}
+/// Fills in the `%s` of a search template
+pub fn search_url(template: &str, terms: &str) -> Option<Url> {
+    Url::parse(&template.replace("%s", &encode_search_terms(terms.trim()))).ok()
+}
+/// Parses text as a URL if it looks like one, adding `https://` if there's no scheme
+fn parse_url_like(text: &str) -> Option<Url> {
+    if text.contains(char::is_whitespace) {
+        return None;
+    }
+    if let Ok(url) = Url::parse(text)
+        && (text.contains("://") || SCHEMES_WITHOUT_SLASHES.contains(&url.scheme()))
+    {
+        return Some(url);
+    }
+    let url = Url::parse(&format!("https://{text}")).ok()?;
+    let host = url.host_str()?;
+    // a single word is more likely a search than a host, and ipv6 addresses are in brackets
+    (host.contains('.') || host == "localhost" || host.starts_with('[')).then_some(url)
+}
+/// Turns what was typed in the URL bar into a URL.
+///
+/// Text starting with a keyword is searched for with that keyword's template,
+/// and text that doesn't look like a URL is searched for with the search engine.
+pub fn resolve_user_input(
+    text: &str,
+    search_engine: &str,
+    keywords: &[(String, String)],
+) -> Option<Url> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Some((keyword, terms)) = text.split_once(char::is_whitespace)
+        && let Some((_, template)) = keywords.iter().find(|f| f.0 == keyword)
+    {
+        return search_url(template, terms);
+    }
+    parse_url_like(text).or_else(|| search_url(search_engine, text))
+}
+/// Generates the keyword list of toad://settings
+pub fn keywords_to_html(keywords: &[(String, String)]) -> String {
+    let mut html = String::new();
+    for (keyword, template) in keywords {
+        html += &format!(
+            "<p><b>{}</b> {} <form action=\"remove_keyword?keyword={}\"><button type=\"submit\" value=\"Remove\"></button></form></p>",
+            sanitize(keyword),
+            sanitize(template),
+            encode_search_terms(keyword),
+        );
+    }
+    if html.is_empty() {
+        html = String::from("<p>no keywords.</p>");
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{DEFAULT_SEARCH_ENGINE, default_search_keywords, resolve_user_input};
+
+    #[test]
+    fn test_resolve_user_input() {
+        let keywords = default_search_keywords();
+        let resolve = |text| {
+            resolve_user_input(text, DEFAULT_SEARCH_ENGINE, &keywords)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(resolve("example.com"), "https://example.com/");
+        assert_eq!(resolve("localhost:8080/a"), "https://localhost:8080/a");
+        assert_eq!(resolve("toad://home"), "toad://home");
+        assert_eq!(
+            resolve("rust borrow checker"),
+            "https://html.duckduckgo.com/html/?q=rust%20borrow%20checker"
+        );
+        assert_eq!(
+            resolve("toads"),
+            "https://html.duckduckgo.com/html/?q=toads"
+        );
+        assert_eq!(
+            resolve("w hello & bye"),
+            "https://en.wikipedia.org/w/index.php?search=hello%20%26%20bye"
+        );
+        assert_eq!(resolve("gh tokio"), "https://github.com/search?q=tokio");
+        assert!(resolve_user_input("  ", DEFAULT_SEARCH_ENGINE, &keywords).is_none());
+    }
+}
@@ -0,0 +1,393 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    DEFAULT_DRAW_CTX, ElementDrawContext, NonInheritedField::*, Theme, buffer::Buffer,
+    element::Element, reader::inner_text, utils::truncate_to_width,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectOption {
+    pub label: String,
+    pub value: String,
+    /// Label of the `<optgroup>` the option is in
+    pub group: Option<String>,
+    pub selected: bool,
+    pub disabled: bool,
+}
+
+/// State of a `<select>` in a form
+#[derive(Clone, Debug, PartialEq)]
+pub struct Select {
+    pub name: String,
+    pub multiple: bool,
+    pub required: bool,
+    pub options: Vec<SelectOption>,
+}
+
+fn collect_options(
+    element: &Element,
+    group: Option<&String>,
+    disabled: bool,
+    options: &mut Vec<SelectOption>,
+) {
+    for child in element.children.iter() {
+        match child.ty.name {
+            "option" => {
+                let text = inner_text(child);
+                let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+                let label = child
+                    .get_attribute("label")
+                    .cloned()
+                    .unwrap_or(text.clone());
+                options.push(SelectOption {
+                    label,
+                    value: child.get_attribute("value").cloned().unwrap_or(text),
+                    group: group.cloned(),
+                    selected: child.get_attribute("selected").is_some(),
+                    disabled: disabled || child.get_attribute("disabled").is_some(),
+                });
+            }
+            "optgroup" => collect_options(
+                child,
+                child.get_attribute("label"),
+                disabled || child.get_attribute("disabled").is_some(),
+                options,
+            ),
+            _ => collect_options(child, group, disabled, options),
+        }
+    }
+}
+
+impl Select {
+    pub fn new(element: &Element) -> Self {
+        let mut options = Vec::new();
+        collect_options(element, None, false, &mut options);
+        let multiple = element.get_attribute("multiple").is_some();
+        if !multiple {
+            // without `multiple`, exactly one option is selected: the last one marked, or else the first available
+            let chosen = options
+                .iter()
+                .rposition(|f| f.selected)
+                .or(options.iter().position(|f| !f.disabled));
+            for (index, option) in options.iter_mut().enumerate() {
+                option.selected = Some(index) == chosen;
+            }
+        }
+        Self {
+            name: element.get_attribute("name").cloned().unwrap_or_default(),
+            multiple,
+            required: element.get_attribute("required").is_some(),
+            options,
+        }
+    }
+    /// Selects an option, or toggles it if multiple can be selected
+    pub fn choose(&mut self, index: usize) {
+        if self.options.get(index).is_none_or(|f| f.disabled) {
+            return;
+        }
+        if self.multiple {
+            self.options[index].selected ^= true;
+        } else {
+            for (i, option) in self.options.iter_mut().enumerate() {
+                option.selected = i == index;
+            }
+        }
+    }
+    /// What the collapsed control shows, padded to the widest option so its size doesn't change
+    pub fn display_text(&self) -> String {
+        let width = self
+            .options
+            .iter()
+            .map(|f| f.label.width())
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        let selected: Vec<&str> = self
+            .options
+            .iter()
+            .filter(|f| f.selected)
+            .map(|f| f.label.as_str())
+            .collect();
+        let text = truncate_to_width(&selected.join(", "), width);
+        format!("[{text}{} ▾]", " ".repeat(width - text.width()))
+    }
+    /// Whether the select is required, but nothing with a value is selected
+    pub fn is_missing(&self) -> bool {
+        self.required
+            && !self
+                .options
+                .iter()
+                .any(|f| f.selected && !f.disabled && !f.value.is_empty())
+    }
+    /// The names and values this select submits
+    pub fn data(&self) -> Vec<(String, String)> {
+        if self.name.is_empty() {
+            return Vec::new();
+        }
+        self.options
+            .iter()
+            .filter(|f| f.selected && !f.disabled)
+            .map(|f| (self.name.clone(), f.value.clone()))
+            .collect()
+    }
+}
+
+/// A row of the popup list
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PopupRow {
+    /// Label of an `<optgroup>`, by the index of its first option
+    Group(usize),
+    Option(usize),
+}
+
+pub enum SelectInput {
+    Pending,
+    Close,
+}
+
+/// The open list of a `<select>`
+pub struct SelectPopup {
+    /// Identifier of the page the select is on
+    pub page_id: usize,
+    pub form: usize,
+    pub select: usize,
+    /// Position of the collapsed control
+    pub x: u16,
+    pub y: u16,
+    /// Index of the option under the cursor
+    highlighted: usize,
+    /// First row in view
+    scroll: usize,
+    /// Where the rows were last drawn: X, Y, width and visible rows
+    area: (u16, u16, u16, u16),
+}
+
+fn popup_rows(select: &Select) -> Vec<PopupRow> {
+    let mut rows = Vec::new();
+    let mut group = None;
+    for (index, option) in select.options.iter().enumerate() {
+        if option.group.is_some() && option.group != group {
+            rows.push(PopupRow::Group(index));
+        }
+        group = option.group.clone();
+        rows.push(PopupRow::Option(index));
+    }
+    rows
+}
+
+impl SelectPopup {
+    pub fn new(page_id: usize, form: usize, index: usize, select: &Select, x: u16, y: u16) -> Self {
+        Self {
+            page_id,
+            form,
+            select: index,
+            x,
+            y,
+            highlighted: select
+                .options
+                .iter()
+                .position(|f| f.selected)
+                .unwrap_or_default(),
+            scroll: 0,
+            area: (0, 0, 0, 0),
+        }
+    }
+    /// Moves the cursor by `amount` options, skipping disabled ones
+    fn move_highlight(&mut self, select: &Select, amount: isize) {
+        let enabled = |f: &usize| !select.options[*f].disabled;
+        for _ in 0..amount.unsigned_abs() {
+            let next = if amount > 0 {
+                (self.highlighted + 1..select.options.len()).find(enabled)
+            } else {
+                (0..self.highlighted).rev().find(enabled)
+            };
+            match next {
+                Some(index) => self.highlighted = index,
+                None => break,
+            }
+        }
+    }
+    pub fn on_key(&mut self, key: KeyEvent, select: &mut Select) -> SelectInput {
+        match key.code {
+            KeyCode::Up => self.move_highlight(select, -1),
+            KeyCode::Down => self.move_highlight(select, 1),
+            KeyCode::PageUp => self.move_highlight(select, -10),
+            KeyCode::PageDown => self.move_highlight(select, 10),
+            KeyCode::Home => self.move_highlight(select, -(select.options.len() as isize)),
+            KeyCode::End => self.move_highlight(select, select.options.len() as isize),
+            KeyCode::Char(' ') if select.multiple => select.choose(self.highlighted),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                select.choose(self.highlighted);
+                if !select.multiple {
+                    return SelectInput::Close;
+                }
+            }
+            KeyCode::Char(char) => {
+                // jump to the next option starting with the typed character
+                let count = select.options.len();
+                if let Some(index) = (1..=count)
+                    .map(|offset| (self.highlighted + offset) % count)
+                    .find(|f| {
+                        let option = &select.options[*f];
+                        !option.disabled
+                            && option
+                                .label
+                                .to_lowercase()
+                                .starts_with(char.to_ascii_lowercase())
+                    })
+                {
+                    self.highlighted = index;
+                }
+            }
+            _ => return SelectInput::Close,
+        }
+        SelectInput::Pending
+    }
+    /// Handles a click, choosing the option under it. Clicking outside the list closes it
+    pub fn on_click(&mut self, x: u16, y: u16, select: &mut Select) -> SelectInput {
+        let (area_x, area_y, width, height) = self.area;
+        if !(area_x..area_x + width).contains(&x) || !(area_y..area_y + height).contains(&y) {
+            return SelectInput::Close;
+        }
+        let row = popup_rows(select)
+            .get(self.scroll + (y - area_y) as usize)
+            .copied();
+        if let Some(PopupRow::Option(index)) = row
+            && !select.options[index].disabled
+        {
+            self.highlighted = index;
+            select.choose(index);
+            if !select.multiple {
+                return SelectInput::Close;
+            }
+        }
+        SelectInput::Pending
+    }
+    /// Draws the list under the control, or above it if there isn't room below
+    pub fn draw(
+        &mut self,
+        buffer: &mut Buffer,
+        select: &Select,
+        screen_size: (u16, u16),
+        theme: &Theme,
+    ) {
+        let (screen_width, screen_height) = screen_size;
+        let rows = popup_rows(select);
+        let label = |row: &PopupRow| match row {
+            PopupRow::Group(index) => select.options[*index].group.clone().unwrap_or_default(),
+            PopupRow::Option(index) => {
+                let option = &select.options[*index];
+                let indent = if option.group.is_some() { "  " } else { "" };
+                let check = match (select.multiple, option.selected) {
+                    (false, _) => "",
+                    (true, false) => "[ ] ",
+                    (true, true) => "[x] ",
+                };
+                format!("{indent}{check}{}", option.label)
+            }
+        };
+        let width = (rows
+            .iter()
+            .map(|f| label(f).width())
+            .max()
+            .unwrap_or_default() as u16
+            + 2)
+        .min(screen_width);
+        let below = screen_height.saturating_sub(self.y + 2);
+        let above = self.y.saturating_sub(3);
+        let (y, height) = if rows.len() as u16 <= below || below >= above {
+            (self.y + 1, (rows.len() as u16).min(below))
+        } else {
+            let height = (rows.len() as u16).min(above);
+            (self.y - height, height)
+        };
+        let x = self.x.min(screen_width - width);
+        self.area = (x, y, width, height);
+
+        // keep the highlighted option in view
+        let highlighted_row = rows
+            .iter()
+            .position(|f| *f == PopupRow::Option(self.highlighted))
+            .unwrap_or_default();
+        if highlighted_row < self.scroll {
+            self.scroll = highlighted_row;
+        } else if highlighted_row >= self.scroll + height as usize {
+            self.scroll = highlighted_row + 1 - height as usize;
+        }
+
+        for (offset, row) in rows
+            .iter()
+            .skip(self.scroll)
+            .take(height as usize)
+            .enumerate()
+        {
+            let (foreground, background, bold) = match row {
+                PopupRow::Group(_) => (theme.text_color, theme.ui_color, true),
+                PopupRow::Option(index) if *index == self.highlighted => {
+                    (theme.background_color, theme.interactive_color, false)
+                }
+                PopupRow::Option(index) if select.options[*index].disabled => {
+                    (theme.background_color, theme.ui_color, false)
+                }
+                PopupRow::Option(_) => (theme.text_color, theme.ui_color, false),
+            };
+            let ctx = ElementDrawContext {
+                foreground_color: Some(foreground),
+                background_color: Specified(background),
+                bold,
+                ..DEFAULT_DRAW_CTX
+            };
+            let text = truncate_to_width(&format!(" {}", label(row)), width as usize);
+            let padding = " ".repeat(width as usize - text.width());
+            buffer.draw_str(x, y + offset as u16, &(text + &padding), &ctx, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{
+        parsing::parse_html,
+        select::{Select, SelectInput, SelectPopup},
+    };
+
+    #[test]
+    fn test_select() {
+        let page = parse_html(
+            "<html><body><select name=\"lang\"><option value=\"en\">English\
+            <optgroup label=\"Nordic\"><option value=\"sv\" selected>Svenska<option disabled>Norsk</optgroup>\
+            <option>Deutsch</option></select><p>after</p></body></html>",
+        )
+        .unwrap();
+        let body = &page.root.as_ref().unwrap().children[0];
+        // the options don't swallow the rest of the page
+        assert_eq!(body.children.len(), 2);
+        let mut select = Select::new(&body.children[0]);
+        let labels: Vec<&str> = select.options.iter().map(|f| f.label.as_str()).collect();
+        assert_eq!(labels, ["English", "Svenska", "Norsk", "Deutsch"]);
+        assert_eq!(select.options[1].group.as_deref(), Some("Nordic"));
+        assert_eq!(select.options[3].value, "Deutsch");
+        assert_eq!(select.display_text(), "[Svenska ▾]");
+        assert_eq!(select.data(), [(String::from("lang"), String::from("sv"))]);
+
+        // moving down skips the disabled option
+        let mut popup = SelectPopup::new(0, 0, 0, &select, 0, 0);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        popup.on_key(key(KeyCode::Down), &mut select);
+        assert!(matches!(
+            popup.on_key(key(KeyCode::Enter), &mut select),
+            SelectInput::Close
+        ));
+        assert_eq!(
+            select.data(),
+            [(String::from("lang"), String::from("Deutsch"))]
+        );
+
+        select.multiple = true;
+        select.choose(0);
+        assert_eq!(select.data().len(), 2);
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::PathBuf;
+
+use crate::{config::data_dir, consts::*, parsing::*, utils::*};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SessionPage {
+    pub url: String,
+    pub scroll_y: u16,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct SessionTab {
+    /// Pages that can be gone back to, the last one being the current page
+    pub history: Vec<SessionPage>,
+    /// Pages that can be gone forward to, the last one being the next page
+    pub future: Vec<SessionPage>,
+}
+
+/// Open tabs, saved so they can be restored later
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Session {
+    pub tabs: Vec<SessionTab>,
+    pub tab_index: usize,
+}
+
+impl Session {
+    /// Each tab starts with a `tab` line, followed by its pages as `back`, `page` (the current one) or `forward`,
+    /// then the scroll and URL, like `page 12 https://example.com/`
+    pub fn serialize(&self) -> String {
+        let mut text = format!("# toad session\ntab_index = {}\n", self.tab_index);
+        for tab in self.tabs.iter() {
+            text += "tab\n";
+            let current = tab.history.len().saturating_sub(1);
+            for (index, page) in tab.history.iter().enumerate() {
+                let kind = if index == current { "page" } else { "back" };
+                text += &format!("{kind} {} {}\n", page.scroll_y, page.url);
+            }
+            for page in tab.future.iter() {
+                text += &format!("forward {} {}\n", page.scroll_y, page.url);
+            }
+        }
+        text
+    }
+    pub fn deserialize(text: &str) -> Self {
+        let mut session = Self::default();
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some(index) = line.strip_prefix("tab_index =") {
+                session.tab_index = index.trim().parse().unwrap_or_default();
+                continue;
+            }
+            if line == "tab" {
+                session.tabs.push(SessionTab::default());
+                continue;
+            }
+            let mut parts = line.splitn(3, ' ');
+            let (Some(kind), Some(scroll_y), Some(url), Some(tab)) = (
+                parts.next(),
+                parts.next().and_then(|f| f.parse().ok()),
+                parts.next(),
+                session.tabs.last_mut(),
+            ) else {
+                continue;
+            };
+            let page = SessionPage {
+                url: url.to_string(),
+                scroll_y,
+            };
+            match kind {
+                "back" | "page" => tab.history.push(page),
+                "forward" => tab.future.push(page),
+                _ => {}
+            }
+        }
+        session.tabs.retain(|f| !f.history.is_empty());
+        session.tab_index = session.tab_index.min(session.tabs.len().saturating_sub(1));
+        session
+    }
+}
+
+/// Keeps only characters that are safe in a file name
+pub fn sanitize_session_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|f| f.is_alphanumeric() || matches!(f, '-' | '_' | ' '))
+        .collect()
+}
+fn sessions_dir() -> Option<PathBuf> {
+    let dir = data_dir()?.join(SESSIONS_DIRNAME);
+    let _ = std::fs::create_dir_all(&dir);
+    Some(dir)
+}
+fn session_path(name: &str) -> Option<PathBuf> {
+    let name = sanitize_session_name(name);
+    if name.is_empty() {
+        return None;
+    }
+    sessions_dir().map(|d| d.join(format!("{name}.{SESSION_EXTENSION}")))
+}
+pub fn load_session(name: &str) -> Option<Session> {
+    let text = std::fs::read_to_string(session_path(name)?).ok()?;
+    Some(Session::deserialize(&text))
+}
+pub fn write_session(name: &str, session: &Session) {
+    if let Some(path) = session_path(name) {
+        let _ = std::fs::write(path, session.serialize());
+    }
+}
+pub fn delete_session(name: &str) {
+    if let Some(path) = session_path(name) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+/// Names of all saved sessions, with their tab count and when they were saved
+pub fn list_sessions() -> Vec<(String, usize, u64)> {
+    let Some(entries) = sessions_dir().and_then(|f| std::fs::read_dir(f).ok()) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<(String, usize, u64)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != SESSION_EXTENSION {
+                return None;
+            }
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            let saved = entry
+                .metadata()
+                .and_then(|f| f.modified())
+                .ok()
+                .and_then(|f| f.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|f| f.as_secs())
+                .unwrap_or_default();
+            let tabs = Session::deserialize(&std::fs::read_to_string(&path).ok()?)
+                .tabs
+                .len();
+            Some((name, tabs, saved))
+        })
+        .collect();
+    sessions.sort();
+    sessions
+}
+/// Generates the items of toad://sessions
+pub fn sessions_to_html(active: &str) -> String {
+    let mut html = String::new();
+    for (name, tabs, saved) in list_sessions() {
+        let escaped = sanitize(&name);
+        let query = name.replace(' ', "+");
+        let tabs = if tabs == 1 {
+            String::from("1 tab")
+        } else {
+            format!("{tabs} tabs")
+        };
+        html += &format!(
+            "<h2>{escaped}</h2><p class=\"info\">{tabs}, saved {} {}</p>",
+            format_date(saved),
+            format_time(saved)
+        );
+        if name == active {
+            html += "<p class=\"info\">(current session)</p>";
+        } else {
+            html += &format!(
+                "<form action=\"open_session?name={query}\"><button type=\"submit\" value=\"Open\"></button></form> \
+                <form action=\"delete_session?name={query}\"><button type=\"submit\" value=\"Delete\"></button></form>"
+            );
+        }
+        html += "<br>";
+    }
+    if html.is_empty() {
+        html = String::from("<p>no saved sessions yet.</p>");
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::session::{Session, SessionPage, SessionTab, sanitize_session_name};
+
+    #[test]
+    fn test_session_roundtrip() {
+        let page = |url: &str, scroll_y| SessionPage {
+            url: url.to_string(),
+            scroll_y,
+        };
+        let session = Session {
+            tabs: vec![
+                SessionTab {
+                    history: vec![page("toad://home", 0), page("https://example.com/a b", 12)],
+                    future: vec![page("https://example.com/next", 3)],
+                },
+                SessionTab {
+                    history: vec![page("https://toad.example/", 0)],
+                    future: Vec::new(),
+                },
+            ],
+            tab_index: 1,
+        };
+        assert_eq!(Session::deserialize(&session.serialize()), session);
+        // out of range tab indices are clamped
+        let session = Session::deserialize("tab_index = 5\ntab\npage 0 toad://home\n");
+        assert_eq!(session.tab_index, 0);
+        assert_eq!(sanitize_session_name(" ../work/stuff "), "workstuff");
+    }
+}
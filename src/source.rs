@@ -0,0 +1,235 @@
+use reqwest::Url;
+
+use crate::parsing::parse_special;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TokenKind {
+    Text,
+    Tag,
+    Attribute,
+    Value,
+    /// Value of an `href` or `src` attribute
+    Link,
+    Comment,
+}
+impl TokenKind {
+    fn class(&self) -> Option<&'static str> {
+        match self {
+            TokenKind::Text => None,
+            TokenKind::Tag => Some("tag"),
+            TokenKind::Attribute => Some("attribute"),
+            TokenKind::Value | TokenKind::Link => Some("value"),
+            TokenKind::Comment => Some("comment"),
+        }
+    }
+}
+
+/// Elements whose contents aren't HTML
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
+
+/// Splits HTML into parts to color, without building an element tree
+fn tokenize(source: &str) -> Vec<(TokenKind, String)> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens: Vec<(TokenKind, String)> = Vec::new();
+    let mut push = |kind, text: &[char]| {
+        if text.is_empty() {
+            return;
+        }
+        match tokens.last_mut() {
+            Some((last, last_text)) if *last == kind && kind != TokenKind::Link => {
+                last_text.extend(text)
+            }
+            _ => tokens.push((kind, text.iter().collect())),
+        }
+    };
+    let starts_with = |index: usize, pattern: &str| {
+        pattern.chars().enumerate().all(|(offset, char)| {
+            chars.get(index + offset).map(char::to_ascii_lowercase) == Some(char)
+        })
+    };
+    let mut index = 0;
+    while index < chars.len() {
+        let start = index;
+        if starts_with(index, "<!--") {
+            index += 4;
+            while index < chars.len() && !starts_with(index, "-->") {
+                index += 1;
+            }
+            index = (index + 3).min(chars.len());
+            push(TokenKind::Comment, &chars[start..index]);
+            continue;
+        }
+        let is_tag = chars[index] == '<'
+            && chars
+                .get(index + 1)
+                .is_some_and(|f| f.is_ascii_alphabetic() || matches!(f, '/' | '!' | '?'));
+        if !is_tag {
+            while index < chars.len() && (index == start || chars[index] != '<') {
+                index += 1;
+            }
+            push(TokenKind::Text, &chars[start..index]);
+            continue;
+        }
+
+        // tag name
+        index += 1;
+        let closing = chars[index] == '/';
+        while index < chars.len() && !chars[index].is_whitespace() && !matches!(chars[index], '>') {
+            index += 1;
+        }
+        let name: String = chars[start + 1..index]
+            .iter()
+            .collect::<String>()
+            .trim_start_matches('/')
+            .to_lowercase();
+        push(TokenKind::Tag, &chars[start..index]);
+
+        // attributes
+        let mut attribute = String::new();
+        while index < chars.len() {
+            let start = index;
+            let char = chars[index];
+            if char == '>' {
+                index += 1;
+                push(TokenKind::Tag, &chars[start..index]);
+                break;
+            } else if char == '/' && chars.get(index + 1) == Some(&'>') {
+                index += 2;
+                push(TokenKind::Tag, &chars[start..index]);
+                break;
+            } else if char.is_whitespace() || char == '=' {
+                index += 1;
+                push(TokenKind::Text, &chars[start..index]);
+            } else if index > 0 && chars[index - 1] == '=' || char == '"' || char == '\'' {
+                // attribute value
+                if char == '"' || char == '\'' {
+                    index += 1;
+                    while index < chars.len() && chars[index] != char {
+                        index += 1;
+                    }
+                    index = (index + 1).min(chars.len());
+                } else {
+                    while index < chars.len()
+                        && !chars[index].is_whitespace()
+                        && chars[index] != '>'
+                    {
+                        index += 1;
+                    }
+                }
+                let kind = if matches!(attribute.as_str(), "href" | "src") {
+                    TokenKind::Link
+                } else {
+                    TokenKind::Value
+                };
+                push(kind, &chars[start..index]);
+            } else {
+                while index < chars.len()
+                    && !chars[index].is_whitespace()
+                    && !matches!(chars[index], '=' | '>')
+                {
+                    index += 1;
+                }
+                index = index.max(start + 1);
+                attribute = chars[start..index]
+                    .iter()
+                    .collect::<String>()
+                    .to_lowercase();
+                push(TokenKind::Attribute, &chars[start..index]);
+            }
+        }
+
+        // contents of scripts and styles are shown as they are, until their closing tag
+        if !closing && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let start = index;
+            let end = format!("</{name}");
+            while index < chars.len() && !starts_with(index, &end) {
+                index += 1;
+            }
+            push(TokenKind::Text, &chars[start..index]);
+        }
+    }
+    tokens
+}
+
+/// Escapes text with numeric references, so that they are shown as they are even if the text contains references itself
+fn escape(text: &str) -> String {
+    text.replace('&', "&#38;")
+        .replace('<', "&#60;")
+        .replace('>', "&#62;")
+        .replace('"', "&#34;")
+}
+
+/// Turns HTML source into highlighted lines with line numbers, where `href` and `src` values link to what they point to.
+///
+/// The lines are meant to be put in a `<pre>`.
+pub fn source_to_html(source: &str, base: Option<&Url>) -> String {
+    let source = source.replace("\r\n", "\n").replace('\t', "    ");
+    let mut lines = vec![String::new()];
+    for (kind, text) in tokenize(&source) {
+        let link = if kind == TokenKind::Link {
+            let value = parse_special(text.trim_matches(['"', '\'']).trim());
+            Url::options().base_url(base).parse(&value).ok()
+        } else {
+            None
+        };
+        for (index, part) in text.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(String::new());
+            }
+            if part.is_empty() {
+                continue;
+            }
+            let line = lines.last_mut().unwrap();
+            let mut html = escape(part);
+            if let Some(class) = kind.class() {
+                html = format!("<span class=\"{class}\">{html}</span>");
+            }
+            if let Some(link) = &link {
+                html = format!(
+                    "<a href=\"{}\">{html}</a>",
+                    link.as_str().replace('"', "%22")
+                );
+            }
+            *line += &html;
+        }
+    }
+    let width = lines.len().to_string().len();
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            format!(
+                "<span class=\"line-number\">{:>width$} </span>{line}",
+                index + 1
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+
+    use crate::source::{TokenKind, source_to_html, tokenize};
+
+    #[test]
+    fn test_view_source() {
+        let source = "<!-- hi -->\n<a class=\"x\" href=\"/toads?a=1&amp;b=2\">toads &amp; frogs</a>\n<script>if (a<b) {}</script>";
+        let tokens = tokenize(source);
+        assert_eq!(tokens[0], (TokenKind::Comment, String::from("<!-- hi -->")));
+        assert!(tokens.contains(&(TokenKind::Attribute, String::from("class"))));
+        assert!(tokens.contains(&(TokenKind::Text, String::from("if (a<b) {}"))));
+        assert_eq!(
+            tokens.iter().map(|f| f.1.as_str()).collect::<String>(),
+            source
+        );
+
+        let base = Url::parse("https://example.com/a/").unwrap();
+        let html = source_to_html(source, Some(&base));
+        assert_eq!(html.lines().count(), 3);
+        assert!(html.contains("<a href=\"https://example.com/toads?a=1&b=2\">"));
+        assert!(html.contains("toads &#38;amp; frogs"));
+        assert!(html.starts_with("<span class=\"line-number\">1 </span>"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{Client, Method, RequestBuilder, Url, header::CONTENT_TYPE};
+
+use crate::file_picker::mime_type;
+
+/// How the entries of a form are put in the request body, from its `enctype`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Encoding {
+    UrlEncoded,
+    Multipart,
+    TextPlain,
+}
+impl Encoding {
+    pub fn parse(enctype: &str) -> Self {
+        match enctype.trim().to_lowercase().as_str() {
+            "multipart/form-data" => Encoding::Multipart,
+            "text/plain" => Encoding::TextPlain,
+            _ => Encoding::UrlEncoded,
+        }
+    }
+}
+
+/// The value of an entry of a form
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormValue {
+    Text(String),
+    /// A file to upload, or none if a file input was left empty
+    File(Option<PathBuf>),
+}
+impl FormValue {
+    /// The value as text. Files are only sent by their name, unless the form is multipart
+    pub fn text(&self) -> String {
+        match self {
+            FormValue::Text(text) => text.clone(),
+            FormValue::File(path) => path
+                .as_ref()
+                .and_then(|f| f.file_name())
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A form that is ready to be sent
+#[derive(Clone, Debug)]
+pub struct FormSubmission {
+    pub method: Method,
+    pub url: Url,
+    pub encoding: Encoding,
+    pub entries: Vec<(String, FormValue)>,
+}
+impl FormSubmission {
+    pub fn new(
+        method: Method,
+        url: Url,
+        encoding: Encoding,
+        entries: Vec<(String, FormValue)>,
+    ) -> Self {
+        // line breaks are always sent as CRLF
+        let normalize = |text: String| {
+            text.replace("\r\n", "\n")
+                .replace('\r', "\n")
+                .replace('\n', "\r\n")
+        };
+        // only multipart bodies can hold files
+        let multipart = method != Method::GET && encoding == Encoding::Multipart;
+        let entries = entries
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    FormValue::File(_) if multipart => value,
+                    value => FormValue::Text(normalize(value.text())),
+                };
+                (normalize(name), value)
+            })
+            .collect();
+        let mut submission = Self {
+            method,
+            url,
+            encoding,
+            entries,
+        };
+        // GET forms put their entries in the query of the URL, replacing any that was there
+        if submission.method == Method::GET {
+            let entries = submission.text_entries();
+            submission
+                .url
+                .query_pairs_mut()
+                .clear()
+                .extend_pairs(entries);
+        }
+        submission
+    }
+    /// The entries, with files by their name
+    fn text_entries(&self) -> Vec<(String, String)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.clone(), value.text()))
+            .collect()
+    }
+    /// Builds the request, reading the files to upload
+    pub fn request(&self, client: &Client) -> io::Result<RequestBuilder> {
+        let request = client.request(self.method.clone(), self.url.clone());
+        if self.method == Method::GET {
+            return Ok(request);
+        }
+        Ok(match self.encoding {
+            Encoding::UrlEncoded => request.form(&self.text_entries()),
+            Encoding::Multipart => {
+                let nanos = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|f| f.as_nanos())
+                    .unwrap_or_default();
+                let boundary = format!("----toadformboundary{nanos:x}");
+                request
+                    .header(
+                        CONTENT_TYPE,
+                        format!("multipart/form-data; boundary={boundary}"),
+                    )
+                    .body(multipart_body(&self.entries, &boundary)?)
+            }
+            Encoding::TextPlain => request
+                .header(CONTENT_TYPE, "text/plain;charset=UTF-8")
+                .body(text_plain_body(&self.text_entries())),
+        })
+    }
+}
+
+/// Escapes a name in the header of a multipart part
+fn escape_part_name(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+pub fn multipart_body(entries: &[(String, FormValue)], boundary: &str) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    for (name, value) in entries {
+        let name = escape_part_name(name);
+        body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        match value {
+            FormValue::Text(text) => body.extend_from_slice(
+                format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n{text}").as_bytes(),
+            ),
+            FormValue::File(path) => {
+                let (content_type, contents) = match path {
+                    Some(path) => (
+                        mime_type(path),
+                        std::fs::read(path).map_err(|e| {
+                            io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+                        })?,
+                    ),
+                    None => ("application/octet-stream", Vec::new()),
+                };
+                body.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{name}\"; filename=\"{}\"\r\nContent-Type: {content_type}\r\n\r\n",
+                        escape_part_name(&value.text())
+                    )
+                    .as_bytes(),
+                );
+                body.extend_from_slice(&contents);
+            }
+        }
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    Ok(body)
+}
+pub fn text_plain_body(entries: &[(String, String)]) -> String {
+    entries
+        .iter()
+        .map(|(name, value)| format!("{name}={value}\r\n"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Method, Url};
+
+    use crate::submission::{Encoding, FormSubmission, FormValue, multipart_body, text_plain_body};
+
+    #[test]
+    fn test_form_submission() {
+        let entries = vec![
+            (
+                String::from("q"),
+                FormValue::Text(String::from("toad frog")),
+            ),
+            (String::from("note"), FormValue::Text(String::from("a\nb"))),
+        ];
+        let url = Url::parse("https://example.com/search?old=1#top").unwrap();
+        let get = FormSubmission::new(
+            Method::GET,
+            url.clone(),
+            Encoding::UrlEncoded,
+            entries.clone(),
+        );
+        assert_eq!(
+            get.url.as_str(),
+            "https://example.com/search?q=toad+frog&note=a%0D%0Ab#top"
+        );
+
+        let post = FormSubmission::new(Method::POST, url, Encoding::Multipart, entries);
+        // the query of the action is kept when the entries go in the body
+        assert_eq!(post.url.query(), Some("old=1"));
+        assert_eq!(post.entries[1].1, FormValue::Text(String::from("a\r\nb")));
+
+        let body = multipart_body(&post.entries[..1], "XYZ").unwrap();
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--XYZ\r\nContent-Disposition: form-data; name=\"q\"\r\n\r\ntoad frog\r\n--XYZ--\r\n"
+        );
+        assert_eq!(
+            text_plain_body(&post.text_entries()),
+            "q=toad frog\r\nnote=a\r\nb\r\n"
+        );
+
+        // files are uploaded with their name and type, or only named in other encodings
+        let path = std::env::temp_dir().join(format!("toad-upload-{}.txt", std::process::id()));
+        std::fs::write(&path, "ribbit").unwrap();
+        let file = vec![(String::from("doc"), FormValue::File(Some(path.clone())))];
+        let body = multipart_body(&file, "XYZ").unwrap();
+        let name = path.file_name().unwrap().to_string_lossy();
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            format!(
+                "--XYZ\r\nContent-Disposition: form-data; name=\"doc\"; filename=\"{name}\"\r\n\
+                Content-Type: text/plain\r\n\r\nribbit\r\n--XYZ--\r\n"
+            )
+        );
+        let url = Url::parse("https://example.com/upload").unwrap();
+        let get = FormSubmission::new(Method::GET, url, Encoding::Multipart, file);
+        assert_eq!(get.url.query(), Some(format!("doc={name}").as_str()));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Encoding::parse("Multipart/Form-Data"), Encoding::Multipart);
+        assert_eq!(Encoding::parse(""), Encoding::UrlEncoded);
+    }
+}
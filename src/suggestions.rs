@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::{bookmarks::Bookmarks, history::History};
+
+/// Maximum amount of suggestions shown under the URL bar
+pub const MAX_SUGGESTIONS: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SuggestionKind {
+    /// An open tab, by index
+    Tab(usize),
+    Bookmark,
+    History,
+    /// Saved form values, by the index of their record
+    Autofill(usize),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Suggestion {
+    pub url: String,
+    pub title: String,
+    pub kind: SuggestionKind,
+    /// How relevant the suggestion is regardless of what's typed, see [crate::history::HistoryEntry::frecency]
+    pub frecency: u32,
+}
+impl Suggestion {
+    pub fn icon(&self) -> char {
+        match self.kind {
+            SuggestionKind::Tab(_) => '»',
+            SuggestionKind::Bookmark => '★',
+            SuggestionKind::History => ' ',
+            SuggestionKind::Autofill(_) => '*',
+        }
+    }
+}
+
+/// Collects everything that can be suggested, one suggestion per URL.
+///
+/// Open tabs are preferred over bookmarks, which are preferred over history, but all share the frecency of the URL.
+pub fn collect_suggestions(
+    tabs: Vec<(usize, String, String)>,
+    bookmarks: &Bookmarks,
+    history: &History,
+    now: u64,
+) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut add = |suggestion: Suggestion| {
+        if let Some(index) = indices.get(&suggestion.url) {
+            let existing = &mut suggestions[*index];
+            existing.frecency += suggestion.frecency;
+            if existing.title.is_empty() {
+                existing.title = suggestion.title;
+            }
+        } else {
+            indices.insert(suggestion.url.clone(), suggestions.len());
+            suggestions.push(suggestion);
+        }
+    };
+    for (index, url, title) in tabs {
+        add(Suggestion {
+            url,
+            title,
+            kind: SuggestionKind::Tab(index),
+            frecency: 150,
+        });
+    }
+    for bookmark in bookmarks.items.iter() {
+        add(Suggestion {
+            url: bookmark.url.clone(),
+            title: bookmark.title.clone(),
+            kind: SuggestionKind::Bookmark,
+            frecency: 200,
+        });
+    }
+    for entry in history.entries.iter().rev() {
+        add(Suggestion {
+            url: entry.url.clone(),
+            title: entry.title.clone(),
+            kind: SuggestionKind::History,
+            frecency: entry.frecency(now),
+        });
+    }
+    suggestions
+}
+
+/// Scores how well `query` matches `text`, if all its characters appear in order.
+///
+/// Consecutive characters and characters at the start of words score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut last_matched = false;
+    let mut prev: Option<char> = None;
+    for char in text.chars().flat_map(char::to_lowercase) {
+        let Some(wanted) = query.peek() else {
+            break;
+        };
+        if char == *wanted {
+            query.next();
+            score += 1;
+            if last_matched {
+                score += 8;
+            }
+            if prev.is_none_or(|f| !f.is_alphanumeric()) {
+                score += 5;
+            }
+            last_matched = true;
+        } else {
+            last_matched = false;
+        }
+        prev = Some(char);
+    }
+    if query.peek().is_some() {
+        return None;
+    }
+    Some(score)
+}
+
+/// The suggestions matching `query`, most relevant first
+pub fn rank_suggestions(query: &str, suggestions: &[Suggestion]) -> Vec<Suggestion> {
+    let query = query.trim();
+    let mut ranked: Vec<(u64, &Suggestion)> = suggestions
+        .iter()
+        .filter_map(|f| {
+            // the scheme is rarely what's being typed
+            let url = f.url.split_once("://").map_or(f.url.as_str(), |f| f.1);
+            let score = fuzzy_score(query, url)
+                .max(fuzzy_score(query, &f.url))
+                .max(fuzzy_score(query, &f.title))?;
+            Some(((score as u64 + 1) * (f.frecency as u64 + 100), f))
+        })
+        .collect();
+    ranked.sort_by_key(|f| std::cmp::Reverse(f.0));
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|f| f.1.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::suggestions::{Suggestion, SuggestionKind, fuzzy_score, rank_suggestions};
+
+    #[test]
+    fn test_rank_suggestions() {
+        assert!(fuzzy_score("wkp", "wikipedia.org").is_some());
+        assert!(fuzzy_score("pkw", "wikipedia.org").is_none());
+        assert!(fuzzy_score("wiki", "wikipedia.org") > fuzzy_score("wiki", "w.i.k.i"));
+
+        let suggestion = |url: &str, title: &str, frecency| Suggestion {
+            url: url.to_string(),
+            title: title.to_string(),
+            kind: SuggestionKind::History,
+            frecency,
+        };
+        let suggestions = vec![
+            suggestion("https://example.com/toads", "Toads", 100),
+            suggestion(
+                "https://en.wikipedia.org/wiki/Toad",
+                "Toad - Wikipedia",
+                900,
+            ),
+            suggestion("https://example.com/", "Example", 5000),
+        ];
+        let ranked = rank_suggestions("toad", &suggestions);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].url, "https://en.wikipedia.org/wiki/Toad");
+        // matches the title, mid-URL text doesn't have to be typed from the start
+        assert_eq!(rank_suggestions("wikipedia", &suggestions).len(), 1);
+        assert_eq!(rank_suggestions("examp", &suggestions)[0].title, "Example");
+    }
+}
@@ -0,0 +1,274 @@
+use std::io::{Stdout, Write};
+
+use crossterm::{
+    cursor,
+    event::{KeyCode, KeyEvent, KeyModifiers},
+    queue, style,
+};
+use unicode_width::UnicodeWidthChar;
+
+/// Splits text into the rows it takes up when wrapped to `width` columns.
+///
+/// Rows are given as ranges of char indices, not including the newlines between lines.
+pub fn wrap_rows(text: &str, width: u16) -> Vec<(usize, usize)> {
+    let width = width.max(1) as usize;
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut row_width = 0;
+    for (index, char) in text.chars().enumerate() {
+        if char == '\n' {
+            rows.push((start, index));
+            start = index + 1;
+            row_width = 0;
+            continue;
+        }
+        let char_width = char.width().unwrap_or_default();
+        if row_width + char_width > width && index > start {
+            rows.push((start, index));
+            start = index;
+            row_width = 0;
+        }
+        row_width += char_width;
+    }
+    rows.push((start, text.chars().count()));
+    rows
+}
+/// Text wrapped to `width` columns, one string per row
+pub fn wrap_text(text: &str, width: u16) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    wrap_rows(text, width)
+        .into_iter()
+        .map(|(start, end)| chars[start..end].iter().collect())
+        .collect()
+}
+
+pub enum TextAreaState {
+    Active,
+    /// Editing is finished, and the text should be saved to the form
+    Done,
+    /// The text should be opened in the user's editor
+    OpenEditor,
+}
+
+/// Multi-line editor of a `<textarea>`, the counterpart of [crate::utils::InputBox]
+pub struct TextArea {
+    pub x: u16,
+    pub y: u16,
+    /// Columns text wraps at. The box is one column wider, to leave room for the cursor
+    pub width: u16,
+    pub height: u16,
+    pub text: String,
+    /// Char index of the cursor
+    cursor: usize,
+    /// First visible row
+    scroll: usize,
+    pub state: TextAreaState,
+    /// Index of the form and the text field the text is saved to
+    pub form: usize,
+    pub field: usize,
+    max_length: Option<usize>,
+}
+impl TextArea {
+    pub fn new(
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        form: usize,
+        field: usize,
+        text: String,
+    ) -> Self {
+        let mut text_area = Self {
+            x,
+            y,
+            width: width.max(1),
+            height: height.max(1),
+            cursor: text.chars().count(),
+            text,
+            scroll: 0,
+            state: TextAreaState::Active,
+            form,
+            field,
+            max_length: None,
+        };
+        text_area.scroll_to_cursor();
+        text_area
+    }
+    pub fn with_max_length(mut self, max_length: Option<usize>) -> Self {
+        self.max_length = max_length;
+        self
+    }
+    fn rows(&self) -> Vec<(usize, usize)> {
+        wrap_rows(&self.text, self.width)
+    }
+    /// Row and column the cursor is drawn at
+    fn cursor_position(&self, rows: &[(usize, usize)]) -> (usize, u16) {
+        let row = rows
+            .iter()
+            .rposition(|f| f.0 <= self.cursor)
+            .unwrap_or_default();
+        let column = self
+            .text
+            .chars()
+            .skip(rows[row].0)
+            .take(self.cursor - rows[row].0)
+            .map(|f| f.width().unwrap_or_default() as u16)
+            .sum();
+        (row, column)
+    }
+    /// Char index of a column in a row, for moving the cursor between rows
+    fn index_at_column(&self, (start, end): (usize, usize), column: u16) -> usize {
+        let mut index = start;
+        let mut width = 0;
+        for char in self.text.chars().skip(start).take(end - start) {
+            width += char.width().unwrap_or_default() as u16;
+            if width > column {
+                break;
+            }
+            index += 1;
+        }
+        index
+    }
+    fn move_vertically(&mut self, amount: isize) {
+        let rows = self.rows();
+        let (row, column) = self.cursor_position(&rows);
+        let row = row.saturating_add_signed(amount).min(rows.len() - 1);
+        self.cursor = self.index_at_column(rows[row], column);
+    }
+    fn scroll_to_cursor(&mut self) {
+        let rows = self.rows();
+        let (row, _) = self.cursor_position(&rows);
+        let height = self.height as usize;
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + height {
+            self.scroll = row + 1 - height;
+        }
+        self.scroll = self.scroll.min(rows.len().saturating_sub(height));
+    }
+    fn insert(&mut self, text: &str) {
+        let mut chars: Vec<char> = self.text.chars().collect();
+        // text past the maximum length is cut off
+        let room = self
+            .max_length
+            .map_or(usize::MAX, |max| max.saturating_sub(chars.len()));
+        let inserted: Vec<char> = text.chars().take(room).collect();
+        let amount = inserted.len();
+        chars.splice(self.cursor..self.cursor, inserted);
+        self.text = chars.into_iter().collect();
+        self.cursor += amount;
+    }
+    fn remove(&mut self, index: usize) {
+        let mut chars: Vec<char> = self.text.chars().collect();
+        if index < chars.len() {
+            chars.remove(index);
+            self.text = chars.into_iter().collect();
+        }
+    }
+    pub fn paste(&mut self, text: &str) {
+        self.insert(&text.replace("\r\n", "\n").replace('\r', "\n"));
+        self.scroll_to_cursor();
+    }
+    /// Moves the cursor to a clicked position, returning whether it was inside the box
+    pub fn click(&mut self, x: u16, y: u16) -> bool {
+        if x < self.x || x > self.x + self.width || y < self.y || y >= self.y + self.height {
+            return false;
+        }
+        let rows = self.rows();
+        let row = (self.scroll + (y - self.y) as usize).min(rows.len() - 1);
+        self.cursor = self.index_at_column(rows[row], x - self.x);
+        true
+    }
+    pub fn on_event(&mut self, event: KeyEvent) {
+        let control = event.modifiers.contains(KeyModifiers::CONTROL);
+        match event.code {
+            KeyCode::Esc => self.state = TextAreaState::Done,
+            KeyCode::Char('c') if control => self.state = TextAreaState::Done,
+            KeyCode::Char('e') if control => self.state = TextAreaState::OpenEditor,
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            KeyCode::Up => self.move_vertically(-1),
+            KeyCode::Down => self.move_vertically(1),
+            KeyCode::PageUp => self.move_vertically(-(self.height as isize)),
+            KeyCode::PageDown => self.move_vertically(self.height as isize),
+            KeyCode::Home if control => self.cursor = 0,
+            KeyCode::End if control => self.cursor = self.text.chars().count(),
+            KeyCode::Home | KeyCode::End => {
+                let rows = self.rows();
+                let (row, _) = self.cursor_position(&rows);
+                self.cursor = if event.code == KeyCode::Home {
+                    rows[row].0
+                } else {
+                    rows[row].1
+                };
+            }
+            KeyCode::Enter => self.insert("\n"),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.remove(self.cursor);
+            }
+            KeyCode::Delete => self.remove(self.cursor),
+            KeyCode::Char(char) => self.insert(&char.to_string()),
+            _ => {}
+        }
+        self.scroll_to_cursor();
+    }
+    pub fn draw(&self, mut stdout: &Stdout) -> std::io::Result<()> {
+        let rows = self.rows();
+        let chars: Vec<char> = self.text.chars().collect();
+        queue!(stdout, style::ResetColor)?;
+        for i in 0..self.height {
+            let text: String = match rows.get(self.scroll + i as usize) {
+                Some((start, end)) => chars[*start..*end].iter().collect(),
+                None => String::new(),
+            };
+            let width: usize = text.chars().map(|f| f.width().unwrap_or_default()).sum();
+            queue!(stdout, cursor::MoveTo(self.x, self.y + i))?;
+            write!(
+                stdout,
+                "{text}{}",
+                " ".repeat((self.width as usize + 1).saturating_sub(width))
+            )?;
+        }
+        let (row, column) = self.cursor_position(&rows);
+        queue!(
+            stdout,
+            cursor::Show,
+            cursor::MoveTo(self.x + column, self.y + (row - self.scroll) as u16)
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::textarea::{TextArea, TextAreaState, wrap_rows, wrap_text};
+
+    #[test]
+    fn test_textarea() {
+        assert_eq!(wrap_rows("abcdef\ngh", 4), vec![(0, 4), (4, 6), (7, 9)]);
+        assert_eq!(wrap_text("", 4), vec![String::new()]);
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut text_area = TextArea::new(0, 0, 4, 2, 0, 0, String::new());
+        for char in "hello".chars() {
+            text_area.on_event(key(KeyCode::Char(char)));
+        }
+        text_area.on_event(key(KeyCode::Enter));
+        text_area.paste("a\r\nb");
+        assert_eq!(text_area.text, "hello\na\nb");
+        // the first rows have scrolled out of view
+        assert_eq!(text_area.scroll, 2);
+
+        text_area.on_event(key(KeyCode::Up));
+        text_area.on_event(key(KeyCode::Up));
+        text_area.on_event(key(KeyCode::Backspace));
+        assert_eq!(text_area.text, "hell\na\nb");
+        assert_eq!(text_area.scroll, 0);
+
+        text_area.on_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL));
+        assert!(matches!(text_area.state, TextAreaState::OpenEditor));
+    }
+}
@@ -1,15 +1,21 @@
 use std::{
     collections::VecDeque,
+    fs::OpenOptions,
     io::{Stdout, Write, stdout},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
+use base64::{Engine, prelude::BASE64_STANDARD};
 use crossterm::{
     cursor,
     event::{self, KeyCode, KeyModifiers},
     execute, queue, style, terminal,
 };
-use reqwest::Url;
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{autofill::UnlockReason, suggestions::*, validation::mask};
 
 pub fn pop_until<T: PartialEq>(a: &mut Vec<T>, b: &T) -> Vec<T> {
     let mut popped = Vec::new();
@@ -90,6 +96,94 @@ Error: {:?}",
         std::fs::write(path, a).unwrap();
     }));
 }
+/// Creates a new temporary file only the user can read, for text that may be private
+pub fn create_private_temp_file(contents: &str) -> std::io::Result<PathBuf> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|f| f.subsec_nanos())
+        .unwrap_or_default();
+    // a new name is tried if one already exists, instead of following a file someone else made
+    for attempt in 0..16 {
+        let path = std::env::temp_dir().join(format!(
+            "toad-{}-{nanos:x}-{attempt}.txt",
+            std::process::id()
+        ));
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        "no free temporary file name",
+    ))
+}
+/// Opens a file in the user's editor (`$VISUAL` or `$EDITOR`), suspending the TUI until the editor exits.
+pub fn edit_externally(path: &Path) -> std::io::Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or(String::from("vi"));
+    // the editor variable can contain arguments, like "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    terminal::disable_raw_mode()?;
+    execute!(
+        stdout(),
+        cursor::Show,
+        event::DisableMouseCapture,
+        event::DisableBracketedPaste
+    )?;
+    let status = Command::new(program).args(parts).arg(path).status();
+    terminal::enable_raw_mode()?;
+    execute!(
+        stdout(),
+        cursor::Hide,
+        event::EnableMouseCapture,
+        event::EnableBracketedPaste,
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    status.map(|_| ())
+}
+/// Copies text to the system clipboard, using the OSC 52 terminal escape sequence
+pub fn copy_to_clipboard(mut stdout: &Stdout, text: &str) -> std::io::Result<()> {
+    write!(stdout, "\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text))?;
+    stdout.flush()
+}
+/// Seconds since the unix epoch
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|f| f.as_secs())
+        .unwrap_or_default()
+}
+/// Formats a unix timestamp as a UTC date, like "2025-03-14"
+pub fn format_date(secs: u64) -> String {
+    // days to civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
+/// Formats the time of day of a unix timestamp, in UTC, like "13:37"
+pub fn format_time(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 3600 % 24, secs / 60 % 60)
+}
 pub fn remove_whitespace(input: &str) -> String {
     input
         .replace(" ", "")
@@ -98,6 +192,20 @@ pub fn remove_whitespace(input: &str) -> String {
         .replace("\r", "")
 }
 
+/// Cuts off the end of a string so it fits in `width` columns
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    let mut truncated = String::new();
+    let mut used = 0;
+    for char in text.chars() {
+        let char_width = char.width().unwrap_or_default();
+        if used + char_width > width {
+            break;
+        }
+        used += char_width;
+        truncated.push(char);
+    }
+    truncated
+}
 fn insert_char(string: &mut String, insert: char, index: usize) {
     if index >= string.chars().count() {
         string.push(insert);
@@ -122,23 +230,16 @@ fn remove_char(string: &mut String, index: usize) {
     *string = new;
 }
 
-pub fn parse_url_user_input(text: &str) -> Option<Url> {
-    let r = Url::parse(text);
-    if let Ok(r) = r {
-        return Some(r);
-    }
-    let b = Url::parse(&format!("https://{text}"));
-
-    if let Ok(b) = b {
-        return Some(b);
-    }
-    None
-}
-
 pub enum InputBoxSubmitTarget {
     OpenNewTab,
     ChangeAddress,
-    SetFormTextField(usize, String),
+    /// Form index, text field index
+    SetFormTextField(usize, usize),
+    FindInPage,
+    /// Asks for the master passphrase of autofill
+    UnlockAutofill(UnlockReason),
+    /// Asks for a new master passphrase again, which has to match the first one
+    ConfirmAutofill(UnlockReason, String),
 }
 
 pub enum InputBoxState {
@@ -159,6 +260,16 @@ pub struct InputBox {
     pub on_submit: InputBoxSubmitTarget,
     auto_completions: Vec<String>,
     rejected_autocompletion: bool,
+    /// Everything that can be suggested in the dropdown under the box
+    suggestions: Vec<Suggestion>,
+    /// Suggestions matching the current text, shown once the text is edited
+    matching_suggestions: Vec<Suggestion>,
+    selected_suggestion: Option<usize>,
+    /// The suggestion that was selected when submitting
+    pub chosen_suggestion: Option<Suggestion>,
+    /// Whether the text is hidden, for password fields
+    masked: bool,
+    max_length: Option<usize>,
 }
 impl InputBox {
     pub fn new(
@@ -180,10 +291,62 @@ impl InputBox {
             on_submit,
             auto_completions,
             rejected_autocompletion: false,
+            suggestions: Vec::new(),
+            matching_suggestions: Vec::new(),
+            selected_suggestion: None,
+            chosen_suggestion: None,
+            masked: false,
+            max_length: None,
+        }
+    }
+    pub fn masked(mut self, masked: bool) -> Self {
+        self.masked = masked;
+        self
+    }
+    pub fn with_max_length(mut self, max_length: Option<usize>) -> Self {
+        self.max_length = max_length;
+        self
+    }
+    pub fn with_suggestions(mut self, suggestions: Vec<Suggestion>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+    /// Suggests saved values for a form field, shown before anything is typed
+    pub fn with_fills(mut self, fills: Vec<Suggestion>) -> Self {
+        self.matching_suggestions = fills.clone();
+        self.suggestions = fills;
+        self
+    }
+    fn update_suggestions(&mut self) {
+        self.selected_suggestion = None;
+        self.matching_suggestions = if self.text.trim().is_empty() {
+            Vec::new()
+        } else {
+            rank_suggestions(&self.text, &self.suggestions)
+        };
+    }
+    /// How many rows of suggestions are drawn under the box
+    pub fn suggestion_rows(&self) -> u16 {
+        self.matching_suggestions.len() as u16
+    }
+    /// The suggestion drawn at a screen position, if any
+    pub fn suggestion_at(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.x || x >= self.x + self.width || y <= self.y {
+            return None;
+        }
+        let index = (y - self.y - 1) as usize;
+        (index < self.matching_suggestions.len()).then_some(index)
+    }
+    /// Submits the box with a suggestion of the dropdown
+    pub fn choose_suggestion(&mut self, index: usize) {
+        if let Some(suggestion) = self.matching_suggestions.get(index) {
+            self.text = suggestion.url.clone();
+            self.chosen_suggestion = Some(suggestion.clone());
+            self.state = InputBoxState::Submitted;
         }
     }
     fn get_autocompletion(&self) -> Option<String> {
-        if self.rejected_autocompletion {
+        if self.rejected_autocompletion || self.selected_suggestion.is_some() {
             return None;
         }
         self.auto_completions.iter().find_map(|f| {
@@ -211,17 +374,46 @@ impl InputBox {
             style::ResetColor
         )?;
         let autocomplete = self.get_autocompletion().unwrap_or_default();
-        write!(stdout, "{}", self.text)?;
+        let text = if self.masked {
+            mask(&self.text)
+        } else {
+            self.text.clone()
+        };
+        write!(stdout, "{text}")?;
         queue!(stdout, style::SetBackgroundColor(style::Color::Blue))?;
         write!(stdout, "{autocomplete}")?;
         queue!(stdout, style::ResetColor)?;
         write!(
             stdout,
             "{}",
-            " ".repeat(
-                (self.width as usize).saturating_sub(self.text.width() + autocomplete.width())
-            )
+            " ".repeat((self.width as usize).saturating_sub(text.width() + autocomplete.width()))
         )?;
+        for (index, suggestion) in self.matching_suggestions.iter().enumerate() {
+            queue!(stdout, cursor::MoveTo(self.x, self.y + 1 + index as u16))?;
+            if self.selected_suggestion == Some(index) {
+                queue!(stdout, style::SetBackgroundColor(style::Color::Blue))?;
+            }
+            let mut width = self.width as usize;
+            let mut label = format!("{} ", suggestion.icon());
+            if !suggestion.title.is_empty() {
+                label += &suggestion.title;
+                label += "  ";
+            }
+            let label = truncate_to_width(&label, width);
+            width -= label.width();
+            write!(stdout, "{label}")?;
+            queue!(stdout, style::SetForegroundColor(style::Color::DarkGrey))?;
+            let url = if self.masked {
+                mask(&suggestion.url)
+            } else {
+                suggestion.url.clone()
+            };
+            let url = truncate_to_width(&url, width);
+            width -= url.width();
+            write!(stdout, "{url}{}", " ".repeat(width))?;
+            queue!(stdout, style::ResetColor)?;
+        }
+        queue!(stdout, cursor::MoveTo(self.x, self.y))?;
         queue!(
             stdout,
             cursor::MoveToColumn(self.x + self.cursor_pos as u16)
@@ -232,6 +424,7 @@ impl InputBox {
         let mut realize_autocompletion = false;
         let mut jump_to_autocompletion_end = false;
         let autocompletion = self.get_autocompletion();
+        let text_before = self.text.clone();
         match event.code {
             KeyCode::Left => {
                 self.cursor_pos = self.cursor_pos.saturating_sub(1);
@@ -260,9 +453,22 @@ impl InputBox {
                 jump_to_autocompletion_end = true;
                 realize_autocompletion = true;
             }
+            KeyCode::Down if !self.matching_suggestions.is_empty() => {
+                self.selected_suggestion = Some(
+                    self.selected_suggestion
+                        .map_or(0, |f| (f + 1).min(self.matching_suggestions.len() - 1)),
+                );
+            }
+            KeyCode::Up if self.selected_suggestion.is_some() => {
+                self.selected_suggestion = self.selected_suggestion.unwrap().checked_sub(1);
+            }
             KeyCode::Enter => {
-                self.state = InputBoxState::Submitted;
-                realize_autocompletion = true;
+                if let Some(index) = self.selected_suggestion {
+                    self.choose_suggestion(index);
+                } else {
+                    self.state = InputBoxState::Submitted;
+                    realize_autocompletion = true;
+                }
             }
             KeyCode::Esc => {
                 self.state = InputBoxState::Cancelled;
@@ -271,7 +477,10 @@ impl InputBox {
                 self.rejected_autocompletion = false;
                 if char == 'c' && event.modifiers.contains(KeyModifiers::CONTROL) {
                     self.state = InputBoxState::Cancelled;
-                } else {
+                } else if self
+                    .max_length
+                    .is_none_or(|max| self.text.chars().count() < max)
+                {
                     insert_char(&mut self.text, char, self.cursor_pos);
                     self.cursor_pos += 1;
                 }
@@ -285,27 +494,25 @@ impl InputBox {
                 realize_autocompletion = true;
                 jump_to_autocompletion_end = true;
             }
-            KeyCode::Backspace => {
-                if self.cursor_pos > 0 {
-                    self.rejected_autocompletion = true;
-                    if autocompletion.is_none() {
-                        self.cursor_pos -= 1;
-                        remove_char(&mut self.text, self.cursor_pos);
+            KeyCode::Backspace if self.cursor_pos > 0 => {
+                self.rejected_autocompletion = true;
+                if autocompletion.is_none() {
+                    self.cursor_pos -= 1;
+                    remove_char(&mut self.text, self.cursor_pos);
 
-                        // make ctrl+backspace delete until special character
-                        //
-                        // note: if using vscode to test, ctrl+backspace doesnt work in vscode's terminal
-                        // so you'll have to use another terminal
-                        if event.modifiers.contains(event::KeyModifiers::CONTROL) {
-                            let mut chars: Vec<char> = self.text.chars().collect();
-                            while self.cursor_pos > 0
-                                && !SPECIAL_CHARS.contains(&chars[self.cursor_pos - 1])
-                            {
-                                self.cursor_pos -= 1;
-                                chars.remove(self.cursor_pos);
-                            }
-                            self.text = chars.iter().collect();
+                    // make ctrl+backspace delete until special character
+                    //
+                    // note: if using vscode to test, ctrl+backspace doesnt work in vscode's terminal
+                    // so you'll have to use another terminal
+                    if event.modifiers.contains(event::KeyModifiers::CONTROL) {
+                        let mut chars: Vec<char> = self.text.chars().collect();
+                        while self.cursor_pos > 0
+                            && !SPECIAL_CHARS.contains(&chars[self.cursor_pos - 1])
+                        {
+                            self.cursor_pos -= 1;
+                            chars.remove(self.cursor_pos);
                         }
+                        self.text = chars.iter().collect();
                     }
                 }
             }
@@ -331,6 +538,9 @@ impl InputBox {
             }
             _ => {}
         }
+        if self.text != text_before {
+            self.update_suggestions();
+        }
         if realize_autocompletion && let Some(autocompletion) = autocompletion {
             self.text += &autocompletion;
             if jump_to_autocompletion_end {
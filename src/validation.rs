@@ -0,0 +1,173 @@
+use crossterm::style;
+use regex::Regex;
+
+use crate::{DEFAULT_DRAW_CTX, ElementDrawContext, NonInheritedField::*, element::Element};
+
+/// Style of the message shown under a field that failed validation
+pub static INVALID_FIELD_DRAW_CTX: ElementDrawContext = ElementDrawContext {
+    foreground_color: Some(style::Color::White),
+    background_color: Specified(style::Color::DarkRed),
+    ..DEFAULT_DRAW_CTX
+};
+
+/// Hides the characters of a password
+pub fn mask(text: &str) -> String {
+    "•".repeat(text.chars().count())
+}
+
+/// Constraints set by the attributes of a text field
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Constraints {
+    /// The `type` of the input, `textarea` for text areas
+    pub kind: String,
+    pub required: bool,
+    pub readonly: bool,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub pattern: Option<String>,
+    /// Describes the pattern, shown when it isn't matched
+    pub title: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+impl Constraints {
+    pub fn new(element: &Element, kind: &str) -> Self {
+        let number = |name: &str| {
+            element
+                .get_attribute(name)
+                .and_then(|f| f.trim().parse::<f64>().ok())
+        };
+        Self {
+            kind: kind.to_string(),
+            required: element.get_attribute("required").is_some(),
+            readonly: element.get_attribute("readonly").is_some(),
+            min_length: number("minlength").map(|f| f as usize),
+            max_length: number("maxlength").map(|f| f as usize),
+            pattern: element.get_attribute("pattern").cloned(),
+            title: element.get_attribute("title").cloned(),
+            min: number("min"),
+            max: number("max"),
+        }
+    }
+    /// Whether the value should be hidden when drawn
+    pub fn masked(&self) -> bool {
+        self.kind == "password"
+    }
+    /// Checks a value, returning why it's invalid
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        // read-only fields can't be changed, so they aren't validated
+        if self.readonly {
+            return Ok(());
+        }
+        if value.is_empty() {
+            return if self.required {
+                Err(String::from("Please fill in this field."))
+            } else {
+                Ok(())
+            };
+        }
+        let length = value.chars().count();
+        if let Some(max) = self.max_length
+            && length > max
+        {
+            return Err(format!("Use at most {max} characters."));
+        }
+        if let Some(min) = self.min_length
+            && length < min
+        {
+            return Err(format!("Use at least {min} characters."));
+        }
+        match self.kind.as_str() {
+            "email"
+                if !value
+                    .split_once('@')
+                    .is_some_and(|(user, domain)| !user.is_empty() && !domain.is_empty()) =>
+            {
+                return Err(String::from("Enter an email address."));
+            }
+            "number" | "range" => {
+                let Ok(number) = value.trim().parse::<f64>() else {
+                    return Err(String::from("Enter a number."));
+                };
+                if let Some(min) = self.min
+                    && number < min
+                {
+                    return Err(format!("Value must be at least {min}."));
+                }
+                if let Some(max) = self.max
+                    && number > max
+                {
+                    return Err(format!("Value must be at most {max}."));
+                }
+            }
+            _ => {}
+        }
+        // the pattern has to match the whole value. text areas don't have one
+        if let Some(pattern) = &self.pattern
+            && self.kind != "textarea"
+            && let Ok(regex) = Regex::new(&format!("^(?:{pattern})$"))
+            && !regex.is_match(value)
+        {
+            return Err(match &self.title {
+                Some(title) => format!("Match the requested format: {title}"),
+                None => String::from("Match the requested format."),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsing::parse_html, validation::Constraints};
+
+    #[test]
+    fn test_constraints() {
+        let page = parse_html(
+            "<html><body><input type=\"number\" min=\"1\" max=\"10\" required>\
+            <input type=\"text\" pattern=\"[a-z]+\" title=\"lowercase letters\" maxlength=\"4\" readonly>\
+            <textarea pattern=\"[a-z]+\"></textarea>\
+            </body></html>",
+        )
+        .unwrap();
+        let body = &page.root.as_ref().unwrap().children[0];
+
+        let number = Constraints::new(&body.children[0], "number");
+        assert_eq!(
+            number.check(""),
+            Err(String::from("Please fill in this field."))
+        );
+        assert_eq!(number.check("abc"), Err(String::from("Enter a number.")));
+        assert_eq!(
+            number.check("11"),
+            Err(String::from("Value must be at most 10."))
+        );
+        assert_eq!(number.check("5"), Ok(()));
+
+        let mut text = Constraints::new(&body.children[1], "text");
+        // read-only fields are never invalid
+        assert_eq!(text.check("ABCDEF"), Ok(()));
+        text.readonly = false;
+        assert_eq!(
+            text.check("abcde"),
+            Err(String::from("Use at most 4 characters."))
+        );
+        assert_eq!(
+            text.check("AB"),
+            Err(String::from(
+                "Match the requested format: lowercase letters"
+            ))
+        );
+        assert_eq!(text.check("ab"), Ok(()));
+
+        let textarea = Constraints::new(&body.children[2], "textarea");
+        assert_eq!(textarea.check("AB"), Ok(()));
+
+        let email = Constraints {
+            kind: String::from("email"),
+            ..Default::default()
+        };
+        assert!(email.check("toad@").is_err());
+        assert_eq!(email.check("toad@example.com"), Ok(()));
+    }
+}